@@ -0,0 +1,248 @@
+//! Command line access to a Yuflow database, for scripts and quick edits
+//! without the app. The database is locked while the command runs, so it
+//! fails cleanly when the app has the same file open.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use yuflow_core::models::*;
+use yuflow_core::validation::Check;
+use yuflow_core::{BackupManager, Database};
+
+/// Read when `--database` is not given
+const DATABASE_VAR: &str = "YUFLOW_DATABASE";
+/// Unlocks an encrypted database
+const PASSPHRASE_VAR: &str = "YUFLOW_PASSPHRASE";
+
+const USAGE: &str = "Usage: yuflow-cli [--database <path>] [--json] <command>
+
+Commands:
+  help
+  list [--all] [--done | --open] [--search <text>] [--category <id>]
+  add <title> [--priority <name>] [--due <RFC 3339 time>] [--category <id>] [--tag <name>]...
+  done <id>
+  reopen <id>
+  delete <id>
+  categories
+  backup [--dir <path>]
+
+The database defaults to $YUFLOW_DATABASE. An encrypted database is
+unlocked with $YUFLOW_PASSPHRASE.";
+
+#[derive(Debug)]
+enum Command {
+    Help,
+    List(TaskFilters),
+    Add(CreateTaskInput),
+    SetCompleted(i64, bool),
+    Delete(i64),
+    Categories,
+    Backup { dir: Option<PathBuf> },
+}
+
+#[derive(Debug)]
+struct Options {
+    database: Option<PathBuf>,
+    json: bool,
+    command: Command,
+}
+
+fn value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("{} needs a value", flag))
+}
+
+fn id(value: Option<String>) -> Result<i64, String> {
+    let value = value.ok_or("A task ID is required")?;
+    value.parse().map_err(|_| format!("'{}' is not a task ID", value))
+}
+
+fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut args = args.into_iter();
+    let mut database = None;
+    let mut json = false;
+
+    let name = loop {
+        match args.next().as_deref() {
+            Some("--database") | Some("--db") => database = Some(PathBuf::from(value(&mut args, "--database")?)),
+            Some("--json") => json = true,
+            Some("--help") | Some("-h") => break "help".to_string(),
+            Some(name) => break name.to_string(),
+            None => return Err("No command given".to_string()),
+        }
+    };
+
+    let command = match name.as_str() {
+        "help" => Command::Help,
+        "list" => {
+            let mut filters = TaskFilters::default();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--all" => filters.include_archived = Some(true),
+                    "--done" => filters.completed = Some(true),
+                    "--open" => filters.completed = Some(false),
+                    "--search" => filters.search = Some(value(&mut args, &arg)?),
+                    "--category" => filters.category_id = Some(id(args.next())?),
+                    _ => return Err(format!("Unknown option '{}'", arg)),
+                }
+            }
+            Command::List(filters)
+        }
+        "add" => {
+            let mut input = CreateTaskInput {
+                title: String::new(),
+                description: None,
+                priority: None,
+                category_id: None,
+                due_date: None,
+                tags: Vec::new(),
+                status_id: None,
+                parent_id: None,
+            };
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--priority" => input.priority = Some(value(&mut args, &arg)?),
+                    "--due" => input.due_date = Some(value(&mut args, &arg)?),
+                    "--category" => input.category_id = Some(id(args.next())?),
+                    "--tag" => input.tags.push(value(&mut args, &arg)?),
+                    _ if arg.starts_with("--") => return Err(format!("Unknown option '{}'", arg)),
+                    _ if input.title.is_empty() => input.title = arg,
+                    _ => return Err(format!("Unexpected argument '{}'", arg)),
+                }
+            }
+            Command::Add(input)
+        }
+        "done" => Command::SetCompleted(id(args.next())?, true),
+        "reopen" => Command::SetCompleted(id(args.next())?, false),
+        "delete" => Command::Delete(id(args.next())?),
+        "categories" => Command::Categories,
+        "backup" => match args.next().as_deref() {
+            Some("--dir") => Command::Backup { dir: Some(PathBuf::from(value(&mut args, "--dir")?)) },
+            Some(arg) => return Err(format!("Unknown option '{}'", arg)),
+            None => Command::Backup { dir: None },
+        },
+        _ => return Err(format!("Unknown command '{}'", name)),
+    };
+
+    Ok(Options { database, json, command })
+}
+
+fn print_tasks(tasks: &[Task]) {
+    for task in tasks {
+        let mark = if task.completed { "x" } else { " " };
+        let due = task.due_date.as_deref().map(|due| format!("  due {}", due)).unwrap_or_default();
+        println!("{:>5} [{}] {:<8} {}{}", task.id, mark, task.priority, task.title, due);
+    }
+}
+
+fn print_json(value: &impl serde::Serialize) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| format!("Failed to write JSON: {}", e))?;
+    println!("{}", json);
+    Ok(())
+}
+
+async fn run(options: Options) -> Result<(), String> {
+    let path = options.database
+        .or_else(|| std::env::var_os(DATABASE_VAR).map(PathBuf::from))
+        .ok_or_else(|| format!("No database given; pass --database or set {}", DATABASE_VAR))?;
+    let passphrase = std::env::var(PASSPHRASE_VAR).ok();
+    let db = Database::open_with_passphrase(&path, passphrase.as_deref())
+        .await
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    match options.command {
+        Command::Help => {}
+        Command::List(filters) => {
+            let tasks = db.get_tasks(Some(filters))
+                .await
+                .map_err(|e| format!("Failed to get tasks: {}", e))?;
+            if options.json {
+                print_json(&tasks)?;
+            } else {
+                print_tasks(&tasks);
+            }
+        }
+        Command::Add(input) => {
+            input.check().map_err(|e| format!("Validation error: {}", e))?;
+            let task = db.create_task(input)
+                .await
+                .map_err(|e| format!("Failed to create task: {}", e))?;
+            if options.json {
+                print_json(&task)?;
+            } else {
+                print_tasks(&[task]);
+            }
+        }
+        Command::SetCompleted(id, completed) => {
+            let task = db.update_task(UpdateTaskInput {
+                id,
+                completed: Patch::Value(completed),
+                ..UpdateTaskInput::default()
+            })
+            .await
+            .map_err(|e| format!("Failed to update task: {}", e))?
+            .ok_or_else(|| format!("Task {} not found", id))?
+            .task;
+            if options.json {
+                print_json(&task)?;
+            } else {
+                print_tasks(&[task]);
+            }
+        }
+        Command::Delete(id) => {
+            db.delete_task(id)
+                .await
+                .map_err(|e| format!("Failed to delete task: {}", e))?;
+        }
+        Command::Categories => {
+            let categories = db.get_categories()
+                .await
+                .map_err(|e| format!("Failed to get categories: {}", e))?;
+            if options.json {
+                print_json(&categories)?;
+            } else {
+                for category in categories {
+                    println!("{:>5} {}", category.id, category.name);
+                }
+            }
+        }
+        Command::Backup { dir } => {
+            // Same place the app keeps backups of a database it opens by path
+            let dir = dir.unwrap_or_else(|| path.parent().map(PathBuf::from).unwrap_or_default().join("backups"));
+            let backup_manager = BackupManager::with_dir(dir)
+                .map_err(|e| format!("Failed to initialize backup manager: {}", e))?;
+            let metadata = backup_manager.create_backup(&db)
+                .await
+                .map_err(|e| format!("Failed to create backup: {}", e))?;
+            if options.json {
+                print_json(&metadata)?;
+            } else {
+                println!("{}", backup_manager.get_backup_dir().join(&metadata.filename).display());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let options = match parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    if let Command::Help = options.command {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    match run(options).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
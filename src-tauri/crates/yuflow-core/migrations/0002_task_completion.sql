@@ -0,0 +1,11 @@
+-- Completion timestamp and archive state for tasks
+
+ALTER TABLE tasks ADD COLUMN completed_at TEXT;
+ALTER TABLE tasks ADD COLUMN archived_at TEXT;
+
+-- Backfill: the last update is the best available guess for when a task was completed
+UPDATE tasks SET completed_at = COALESCE(updated_at, created_at)
+WHERE completed = 1 AND completed_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_completed_at ON tasks(completed_at);
+CREATE INDEX IF NOT EXISTS idx_tasks_archived_at ON tasks(archived_at);
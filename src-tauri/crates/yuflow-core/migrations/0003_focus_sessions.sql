@@ -0,0 +1,16 @@
+-- Focus (pomodoro) sessions
+
+CREATE TABLE IF NOT EXISTS focus_sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER,
+    kind TEXT CHECK(kind IN ('work', 'short_break', 'long_break')) NOT NULL,
+    started_at TEXT NOT NULL,
+    ended_at TEXT NOT NULL,
+    planned_seconds INTEGER NOT NULL,
+    actual_seconds INTEGER NOT NULL,
+    completed BOOLEAN DEFAULT FALSE,
+    FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_focus_sessions_task ON focus_sessions(task_id);
+CREATE INDEX IF NOT EXISTS idx_focus_sessions_started_at ON focus_sessions(started_at);
@@ -0,0 +1,23 @@
+-- User-defined workflow statuses (board columns)
+
+CREATE TABLE IF NOT EXISTS task_statuses (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT UNIQUE NOT NULL,
+    color TEXT DEFAULT '#6B7280',
+    position INTEGER NOT NULL DEFAULT 0,
+    is_done BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT OR IGNORE INTO task_statuses (id, name, color, position, is_done, created_at) VALUES
+    (1, 'Backlog', '#6B7280', 0, 0, datetime('now')),
+    (2, 'In Progress', '#3B82F6', 1, 0, datetime('now')),
+    (3, 'Review', '#A855F7', 2, 0, datetime('now')),
+    (4, 'Done', '#22C55E', 3, 1, datetime('now'));
+
+ALTER TABLE tasks ADD COLUMN status_id INTEGER REFERENCES task_statuses (id);
+
+-- Existing tasks keep their completion state
+UPDATE tasks SET status_id = CASE WHEN completed = 1 THEN 4 ELSE 1 END;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_status ON tasks(status_id);
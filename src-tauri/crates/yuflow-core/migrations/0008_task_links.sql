@@ -0,0 +1,14 @@
+-- Links between tasks parsed from descriptions
+
+CREATE TABLE IF NOT EXISTS task_links (
+    source_id INTEGER NOT NULL,
+    -- NULL while the reference does not resolve to a task
+    target_id INTEGER,
+    kind TEXT CHECK(kind IN ('title', 'id')) NOT NULL,
+    reference TEXT NOT NULL,
+    PRIMARY KEY (source_id, kind, reference),
+    FOREIGN KEY (source_id) REFERENCES tasks (id) ON DELETE CASCADE,
+    FOREIGN KEY (target_id) REFERENCES tasks (id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_links_target ON task_links(target_id);
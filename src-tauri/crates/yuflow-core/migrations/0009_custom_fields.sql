@@ -0,0 +1,27 @@
+-- User-defined custom fields and their per-task values
+
+CREATE TABLE IF NOT EXISTS custom_fields (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    field_type TEXT CHECK(field_type IN ('text', 'number', 'date', 'select', 'checkbox')) NOT NULL,
+    -- JSON array of choices for select fields
+    options TEXT NOT NULL DEFAULT '[]',
+    -- NULL makes the field available to every category
+    category_id INTEGER,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (category_id) REFERENCES categories (id) ON DELETE SET NULL
+);
+
+-- Values are stored in a canonical text form that sorts correctly within a
+-- field: numbers are compared numerically, dates are YYYY-MM-DD
+CREATE TABLE IF NOT EXISTS task_field_values (
+    task_id INTEGER NOT NULL,
+    field_id INTEGER NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (task_id, field_id),
+    FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE,
+    FOREIGN KEY (field_id) REFERENCES custom_fields (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_field_values_field ON task_field_values(field_id, value);
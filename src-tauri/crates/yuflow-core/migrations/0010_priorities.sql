@@ -0,0 +1,29 @@
+-- User-defined priority scale
+
+CREATE TABLE IF NOT EXISTS priorities (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT UNIQUE NOT NULL COLLATE NOCASE,
+    -- Higher weight means more important
+    weight INTEGER NOT NULL DEFAULT 0,
+    color TEXT DEFAULT '#6B7280',
+    icon TEXT,
+    -- Used for new tasks that do not name a priority
+    is_default BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT OR IGNORE INTO priorities (id, name, weight, color, icon, is_default, created_at) VALUES
+    (1, 'low', 10, '#22C55E', 'arrow-down', 0, datetime('now')),
+    (2, 'medium', 20, '#F59E0B', 'minus', 1, datetime('now')),
+    (3, 'high', 30, '#EF4444', 'arrow-up', 0, datetime('now'));
+
+ALTER TABLE tasks ADD COLUMN priority_id INTEGER REFERENCES priorities (id);
+
+UPDATE tasks SET priority_id = COALESCE((SELECT id FROM priorities WHERE name = tasks.priority), 2);
+
+-- The old column carries the hard-coded CHECK constraint. It is a column
+-- constraint, so the column can be dropped once its index is gone.
+DROP INDEX IF EXISTS idx_tasks_priority;
+ALTER TABLE tasks DROP COLUMN priority;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_priority ON tasks(priority_id);
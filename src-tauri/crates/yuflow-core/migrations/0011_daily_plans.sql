@@ -0,0 +1,23 @@
+-- Daily plans: the ordered tasks chosen for a day and the time available
+
+CREATE TABLE IF NOT EXISTS daily_plans (
+    date TEXT PRIMARY KEY,
+    -- NULL falls back to the daily_capacity_minutes setting
+    capacity_minutes INTEGER,
+    -- Set once unfinished items of the previous plan have been brought over
+    carried_over_at TEXT,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS daily_plan_items (
+    date TEXT NOT NULL,
+    task_id INTEGER NOT NULL,
+    position INTEGER NOT NULL DEFAULT 0,
+    estimate_minutes INTEGER,
+    -- First day the task was planned for, when it was carried over
+    carried_from TEXT,
+    PRIMARY KEY (date, task_id),
+    FOREIGN KEY (task_id) REFERENCES tasks (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_daily_plan_items_task ON daily_plan_items(task_id);
@@ -0,0 +1,4 @@
+-- Private tasks and categories. The title and description of a private task,
+-- or of any task in a private category, are stored encrypted.
+ALTER TABLE tasks ADD COLUMN is_private BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE categories ADD COLUMN is_private BOOLEAN NOT NULL DEFAULT 0;
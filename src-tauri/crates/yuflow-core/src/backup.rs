@@ -0,0 +1,405 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use chrono::Utc;
+use crate::models::*;
+use crate::database::Database;
+use crate::error::AppError;
+use crate::planning::DailyPlanRecord;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupData {
+    pub version: String,
+    pub created_at: String,
+    pub tasks: Vec<Task>,
+    pub categories: Vec<Category>,
+    #[serde(default)]
+    pub statuses: Vec<TaskStatus>,
+    #[serde(default)]
+    pub priorities: Vec<Priority>,
+    pub settings: Vec<AppSetting>,
+    #[serde(default)]
+    pub focus_sessions: Vec<FocusSession>,
+    #[serde(default)]
+    pub checklist_items: Vec<ChecklistItem>,
+    #[serde(default)]
+    pub templates: Vec<TaskTemplate>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    #[serde(default)]
+    pub custom_fields: Vec<CustomField>,
+    #[serde(default)]
+    pub daily_plans: Vec<DailyPlanRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BackupMetadata {
+    pub filename: String,
+    pub created_at: String,
+    pub size_bytes: u64,
+    pub task_count: usize,
+    pub category_count: usize,
+}
+
+pub struct BackupManager {
+    backup_dir: PathBuf,
+}
+
+impl BackupManager {
+    pub fn with_dir(backup_dir: PathBuf) -> Result<Self, std::io::Error> {
+        std::fs::create_dir_all(&backup_dir)?;
+        
+        Ok(BackupManager { backup_dir })
+    }
+
+    pub async fn create_backup(&self, database: &Database) -> Result<BackupMetadata, AppError> {
+        let now = Utc::now();
+        let timestamp = now.format("%Y%m%d_%H%M%S").to_string();
+        let filename = format!("yuflow_backup_{}.json", timestamp);
+        let backup_path = self.backup_dir.join(&filename);
+
+        // Collect all data
+        let tasks = database.get_tasks(Some(TaskFilters {
+            include_archived: Some(true),
+            ..Default::default()
+        })).await?;
+        let categories = database.get_categories().await?;
+        let statuses = database.get_statuses().await?;
+        let priorities = database.get_priorities().await?;
+        let settings = database.get_all_settings().await?;
+        let focus_sessions = database.get_focus_sessions(None).await?;
+        let checklist_items = database.get_checklist(None).await?;
+        let templates = database.get_templates().await?;
+        let attachments = database.get_attachments(None).await?;
+        let custom_fields = database.get_custom_fields(None).await?;
+        let daily_plans = database.get_plan_records().await?;
+
+        let backup_data = BackupData {
+            version: "1.0.0".to_string(),
+            created_at: now.to_rfc3339(),
+            tasks: tasks.clone(),
+            categories: categories.clone(),
+            statuses,
+            priorities,
+            settings,
+            focus_sessions,
+            checklist_items,
+            templates,
+            attachments,
+            custom_fields,
+            daily_plans,
+        };
+
+        // Write backup file
+        let json_data = serde_json::to_string_pretty(&backup_data)?;
+        std::fs::write(&backup_path, &json_data)?;
+
+        // Get file size
+        let metadata = std::fs::metadata(&backup_path)?;
+        
+        Ok(BackupMetadata {
+            filename,
+            created_at: now.to_rfc3339(),
+            size_bytes: metadata.len(),
+            task_count: tasks.len(),
+            category_count: categories.len(),
+        })
+    }
+
+    pub async fn restore_backup(&self, filename: &str, database: &Database) -> Result<(), AppError> {
+        let backup_path = self.backup_dir.join(filename);
+        let json_data = std::fs::read_to_string(backup_path)?;
+        let backup_data: BackupData = serde_json::from_str(&json_data)?;
+
+        // Clear existing data (in a transaction)
+        database.clear_all_data().await?;
+
+        // Restore categories first (due to foreign key constraints). They are
+        // merged by name, since the default category survives the clear, and
+        // get new IDs that everything pointing at them is moved to
+        let existing_categories = database.get_categories().await?;
+        let mut category_ids = HashMap::new();
+        for category in backup_data.categories {
+            let id = match existing_categories.iter().find(|c| c.name == category.name) {
+                Some(existing) => existing.id,
+                None => database.create_category(CreateCategoryInput {
+                    name: category.name,
+                    color: Some(category.color),
+                }).await?.id,
+            };
+            if category.is_private {
+                database.restore_private_flag("categories", id).await?;
+            }
+            category_ids.insert(category.id, id);
+        }
+        let category_id = |id: Option<i64>| id.and_then(|id| category_ids.get(&id).copied());
+
+        // Statuses are merged by name so the built-in ones are reused
+        let existing_statuses = database.get_statuses().await?;
+        let mut status_ids = HashMap::new();
+        for status in &backup_data.statuses {
+            let id = match existing_statuses.iter().find(|s| s.name.eq_ignore_ascii_case(&status.name)) {
+                Some(existing) => {
+                    database.update_status(UpdateStatusInput {
+                        id: existing.id,
+                        name: None,
+                        color: Some(status.color.clone()),
+                        is_done: Some(status.is_done),
+                    }).await?;
+                    existing.id
+                }
+                None => database.create_status(CreateStatusInput {
+                    name: status.name.clone(),
+                    color: Some(status.color.clone()),
+                    is_done: status.is_done,
+                }).await?.id,
+            };
+            status_ids.insert(status.id, id);
+        }
+        let order: Vec<i64> = backup_data.statuses.iter().filter_map(|s| status_ids.get(&s.id).copied()).collect();
+        database.reorder_statuses(&order).await?;
+
+        // Priorities are merged by name as well; tasks refer to them by name
+        let existing_priorities = database.get_priorities().await?;
+        for priority in backup_data.priorities {
+            match existing_priorities.iter().find(|p| p.name.eq_ignore_ascii_case(&priority.name)) {
+                Some(existing) => {
+                    database.update_priority(UpdatePriorityInput {
+                        id: existing.id,
+                        name: None,
+                        weight: Some(priority.weight),
+                        color: Some(priority.color),
+                        icon: priority.icon,
+                        is_default: Some(priority.is_default).filter(|d| *d),
+                    }).await?;
+                }
+                None => {
+                    database.create_priority(CreatePriorityInput {
+                        name: priority.name,
+                        weight: priority.weight,
+                        color: Some(priority.color),
+                        icon: priority.icon,
+                        is_default: priority.is_default,
+                    }).await?;
+                }
+            }
+        }
+
+        let mut field_ids = HashMap::new();
+        for field in backup_data.custom_fields {
+            let created_field = database.create_custom_field(CreateCustomFieldInput {
+                name: field.name,
+                field_type: field.field_type,
+                options: field.options,
+                category_id: category_id(field.category_id),
+            }).await?;
+            field_ids.insert(field.id, created_field.id);
+        }
+
+        // Restore tasks, remembering their new IDs for the rows that point at them
+        let mut task_ids = HashMap::new();
+        let mut parents = Vec::new();
+        let mut descriptions = Vec::new();
+        for task in backup_data.tasks {
+            let description = task.description.clone();
+            let input = CreateTaskInput {
+                title: task.title,
+                description: task.description,
+                priority: Some(task.priority),
+                category_id: category_id(task.category_id),
+                due_date: task.due_date,
+                tags: task.tags,
+                status_id: task.status_id.and_then(|id| status_ids.get(&id).copied()),
+                // Linked below, once every task has its new ID
+                parent_id: None,
+            };
+            let created_task = database.create_task(input).await?;
+            task_ids.insert(task.id, created_task.id);
+            // Title and description were restored as stored, still encrypted
+            if task.is_private {
+                database.restore_private_flag("tasks", created_task.id).await?;
+            }
+            if let Some(parent_id) = task.parent_id {
+                parents.push((created_task.id, parent_id));
+            }
+            if let Some(description) = description {
+                descriptions.push((created_task.id, description));
+            }
+            for (field_id, value) in &task.custom_fields {
+                if let Some(field_id) = field_ids.get(field_id) {
+                    database.set_task_field_value(created_task.id, *field_id, Some(value)).await?;
+                }
+            }
+            
+            // Update completion status if needed
+            if task.completed {
+                database.update_task(UpdateTaskInput {
+                    id: created_task.id,
+                    completed: Patch::Value(true),
+                    ..UpdateTaskInput::default()
+                }).await?;
+            }
+            
+            if task.completed || task.archived_at.is_some() {
+                database.restore_task_timestamps(
+                    created_task.id,
+                    task.completed_at.as_deref(),
+                    task.archived_at.as_deref(),
+                ).await?;
+            }
+        }
+
+        for (id, parent_id) in parents {
+            if let Some(parent_id) = task_ids.get(&parent_id) {
+                database.set_task_parent(id, Some(*parent_id)).await?;
+            }
+        }
+
+        // `#id` references follow their tasks to the new IDs
+        for (id, description) in descriptions {
+            let remapped = crate::links::remap_ids(&description, &task_ids);
+            if remapped != description {
+                database.update_task(UpdateTaskInput {
+                    id,
+                    description: Patch::Value(remapped),
+                    ..UpdateTaskInput::default()
+                }).await?;
+            }
+        }
+
+        for item in backup_data.checklist_items {
+            if let Some(task_id) = task_ids.get(&item.task_id) {
+                let created_item = database.add_checklist_item(*task_id, &item.title).await?;
+                if item.done {
+                    database.update_checklist_item(UpdateChecklistItemInput {
+                        id: created_item.id,
+                        title: None,
+                        done: Some(true),
+                    }).await?;
+                }
+            }
+        }
+
+        for attachment in backup_data.attachments {
+            if let Some(task_id) = task_ids.get(&attachment.task_id) {
+                database.add_attachment(CreateAttachmentInput {
+                    task_id: *task_id,
+                    kind: attachment.kind,
+                    target: attachment.target,
+                    label: attachment.label,
+                }).await?;
+            }
+        }
+
+        for mut plan in backup_data.daily_plans {
+            plan.items.retain_mut(|item| match task_ids.get(&item.task_id) {
+                Some(task_id) => {
+                    item.task_id = *task_id;
+                    true
+                }
+                None => false,
+            });
+            database.restore_plan(&plan).await?;
+        }
+
+        for template in backup_data.templates {
+            database.create_template(CreateTemplateInput {
+                name: template.name,
+                description: template.description,
+                category_id: category_id(template.category_id),
+                tasks: template.tasks,
+            }).await?;
+        }
+
+        for session in backup_data.focus_sessions {
+            database.record_focus_session(NewFocusSession {
+                task_id: session.task_id.and_then(|id| task_ids.get(&id).copied()),
+                kind: session.kind,
+                started_at: session.started_at,
+                ended_at: session.ended_at,
+                planned_seconds: session.planned_seconds,
+                actual_seconds: session.actual_seconds,
+                completed: session.completed,
+            }).await?;
+        }
+
+        // Restore settings
+        for setting in backup_data.settings {
+            database.set_setting(&setting.key, &setting.value).await?;
+        }
+
+        Ok(())
+    }
+
+    pub fn list_backups(&self) -> Result<Vec<BackupMetadata>, std::io::Error> {
+        let mut backups = Vec::new();
+        
+        for entry in std::fs::read_dir(&self.backup_dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                if let Some(filename) = path.file_name().and_then(|s| s.to_str()) {
+                    if filename.starts_with("yuflow_backup_") {
+                        let metadata = std::fs::metadata(&path)?;
+                        
+                        // Try to extract timestamp from filename
+                        let created_at = if let Some(timestamp_part) = filename.strip_prefix("yuflow_backup_").and_then(|s| s.strip_suffix(".json")) {
+                            // Parse timestamp format: YYYYMMDD_HHMMSS
+                            if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(timestamp_part, "%Y%m%d_%H%M%S") {
+                                dt.and_utc().to_rfc3339()
+                            } else {
+                                metadata.modified()
+                                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
+                                    .duration_since(std::time::SystemTime::UNIX_EPOCH)
+                                    .unwrap()
+                                    .as_secs()
+                                    .to_string()
+                            }
+                        } else {
+                            metadata.modified()
+                                .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
+                                .duration_since(std::time::SystemTime::UNIX_EPOCH)
+                                .unwrap()
+                                .as_secs()
+                                .to_string()
+                        };
+
+                        // Try to read backup to get counts
+                        let (task_count, category_count) = if let Ok(json_data) = std::fs::read_to_string(&path) {
+                            if let Ok(backup_data) = serde_json::from_str::<BackupData>(&json_data) {
+                                (backup_data.tasks.len(), backup_data.categories.len())
+                            } else {
+                                (0, 0)
+                            }
+                        } else {
+                            (0, 0)
+                        };
+
+                        backups.push(BackupMetadata {
+                            filename: filename.to_string(),
+                            created_at,
+                            size_bytes: metadata.len(),
+                            task_count,
+                            category_count,
+                        });
+                    }
+                }
+            }
+        }
+
+        // Sort by creation time (newest first)
+        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        
+        Ok(backups)
+    }
+
+    pub fn delete_backup(&self, filename: &str) -> Result<(), std::io::Error> {
+        let backup_path = self.backup_dir.join(filename);
+        std::fs::remove_file(backup_path)
+    }
+
+    pub fn get_backup_dir(&self) -> &PathBuf {
+        &self.backup_dir
+    }
+}
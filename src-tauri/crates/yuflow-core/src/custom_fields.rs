@@ -0,0 +1,86 @@
+use chrono::NaiveDate;
+use crate::models::*;
+
+pub const FIELD_TYPES: [&str; 5] = ["text", "number", "date", "select", "checkbox"];
+
+const MAX_TEXT_LENGTH: usize = 1000;
+
+/// Checks the choices of a field: select fields need at least one distinct,
+/// non-empty option and other field types take none.
+pub fn check_options(field_type: &str, options: &[String]) -> Result<(), String> {
+    if field_type != "select" {
+        return if options.is_empty() {
+            Ok(())
+        } else {
+            Err("Only select fields have options".to_string())
+        };
+    }
+
+    if options.is_empty() {
+        return Err("Select fields need at least one option".to_string());
+    }
+    for (i, option) in options.iter().enumerate() {
+        if option.trim().is_empty() || option.len() > 100 {
+            return Err("Options must be between 1 and 100 characters".to_string());
+        }
+        if options[..i].iter().any(|o| o.eq_ignore_ascii_case(option)) {
+            return Err(format!("Duplicate option '{}'", option));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that a field applies to a task in `category_id`.
+pub fn check_scope(field: &CustomField, category_id: Option<i64>) -> Result<(), String> {
+    match field.category_id {
+        Some(scope) if Some(scope) != category_id => {
+            Err(format!("Field '{}' does not apply to this task's category", field.name))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Validates `value` against the field type and returns the canonical text
+/// form that is stored, compared and sorted.
+pub fn normalize_value(field: &CustomField, value: &str) -> Result<String, String> {
+    let trimmed = value.trim();
+
+    match field.field_type.as_str() {
+        "text" => {
+            if trimmed.is_empty() || value.len() > MAX_TEXT_LENGTH {
+                Err(format!("'{}' must be between 1 and {} characters", field.name, MAX_TEXT_LENGTH))
+            } else {
+                Ok(value.to_string())
+            }
+        }
+        "number" => match trimmed.parse::<f64>() {
+            Ok(number) if number.is_finite() => Ok(format_number(number)),
+            _ => Err(format!("'{}' must be a number", field.name)),
+        },
+        "date" => NaiveDate::parse_from_str(trimmed, "%Y-%m-%d")
+            .map(|date| date.to_string())
+            .map_err(|_| format!("'{}' must be a date (YYYY-MM-DD)", field.name)),
+        "select" => field
+            .options
+            .iter()
+            .find(|option| option.eq_ignore_ascii_case(trimmed))
+            .cloned()
+            .ok_or_else(|| format!("'{}' must be one of: {}", field.name, field.options.join(", "))),
+        "checkbox" => match trimmed.to_lowercase().as_str() {
+            "true" | "1" | "yes" => Ok("true".to_string()),
+            "false" | "0" | "no" => Ok("false".to_string()),
+            _ => Err(format!("'{}' must be true or false", field.name)),
+        },
+        other => Err(format!("Unknown field type '{}'", other)),
+    }
+}
+
+// Whole numbers are stored without a fractional part, so "5" and "5.0" match
+fn format_number(number: f64) -> String {
+    if number.fract() == 0.0 && number.abs() < 1e15 {
+        format!("{}", number as i64)
+    } else {
+        number.to_string()
+    }
+}
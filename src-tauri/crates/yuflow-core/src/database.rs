@@ -0,0 +1,2786 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use sqlx::{sqlite::{SqliteAutoVacuum, SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteRow, SqliteSynchronous}, ConnectOptions, Connection, QueryBuilder, Row, Sqlite, Transaction};
+use crate::models::*;
+use crate::links::{self, Reference};
+use crate::planning::{DailyPlanRecord, DailyPlanRecordItem, PlanItem};
+use crate::encryption::{is_sealed, DerivedKey, KeyInfo, Sealer};
+use crate::error::AppError;
+use crate::lock::DatabaseLock;
+use crate::schema::{self, MigrationStatus};
+use crate::statistics::parse_timestamp;
+use crate::validation::Violations;
+
+// Tags are aggregated with the ASCII unit separator so that names containing
+// commas survive the round trip.
+const TAG_SEPARATOR: char = '\u{1f}';
+
+const TASK_COLUMNS: &str = "id, title, description, completed, status_id,
+    COALESCE((SELECT name FROM priorities WHERE priorities.id = tasks.priority_id), '') AS priority, category_id, due_date, created_at, updated_at, completed_at, archived_at, parent_id, is_private,
+    (SELECT GROUP_CONCAT(tags.name, char(31)) FROM task_tags JOIN tags ON tags.id = task_tags.tag_id WHERE task_tags.task_id = tasks.id) AS tags,
+    (SELECT json_group_object(field_id, value) FROM task_field_values WHERE task_field_values.task_id = tasks.id) AS field_values";
+
+const CUSTOM_FIELD_COLUMNS: &str = "id, name, field_type, options, category_id, position, created_at";
+
+// Binds one task id and yields it together with all of its descendants
+const SUBTREE: &str = "WITH RECURSIVE subtree(id) AS (
+    SELECT ? UNION ALL SELECT tasks.id FROM tasks JOIN subtree ON tasks.parent_id = subtree.id)";
+
+const LINK_QUERY: &str = "SELECT task_links.source_id, source.title AS source_title, task_links.target_id,
+    target.title AS target_title, task_links.kind, task_links.reference
+    FROM task_links
+    JOIN tasks source ON source.id = task_links.source_id
+    LEFT JOIN tasks target ON target.id = task_links.target_id";
+
+const STATUS_COLUMNS: &str = "id, name, color, position, is_done, created_at";
+
+const PRIORITY_COLUMNS: &str = "id, name, weight, color, icon, is_default, created_at";
+
+/// A rule every row of `table` must follow. `condition` selects the rows
+/// that break it, `id` is the column reported for them, and `fix` is the
+/// statement that repairs them once the condition is appended.
+struct Invariant {
+    kind: IntegrityIssueKind,
+    table: &'static str,
+    id: &'static str,
+    condition: &'static str,
+    message: &'static str,
+    fix: &'static str,
+}
+
+const INVARIANTS: &[Invariant] = &[
+    Invariant {
+        kind: IntegrityIssueKind::MissingCategory,
+        table: "tasks",
+        id: "id",
+        condition: "category_id IS NOT NULL AND category_id NOT IN (SELECT id FROM categories)",
+        message: "Task is in a category that no longer exists",
+        fix: "UPDATE tasks SET category_id = NULL",
+    },
+    Invariant {
+        kind: IntegrityIssueKind::MissingParent,
+        table: "tasks",
+        id: "id",
+        condition: "parent_id IS NOT NULL AND parent_id NOT IN (SELECT id FROM tasks)",
+        message: "Subtask of a task that no longer exists",
+        fix: "UPDATE tasks SET parent_id = NULL",
+    },
+    Invariant {
+        kind: IntegrityIssueKind::MissingStatus,
+        table: "tasks",
+        id: "id",
+        condition: "status_id IS NOT NULL AND status_id NOT IN (SELECT id FROM task_statuses)",
+        message: "Task has a status that no longer exists",
+        fix: "UPDATE tasks SET status_id = NULL",
+    },
+    Invariant {
+        kind: IntegrityIssueKind::InvalidPriority,
+        table: "tasks",
+        id: "id",
+        condition: "priority_id IS NULL OR priority_id NOT IN (SELECT id FROM priorities)",
+        message: "Task has no valid priority",
+        fix: "UPDATE tasks SET priority_id = (SELECT id FROM priorities ORDER BY is_default DESC, weight LIMIT 1)",
+    },
+    Invariant {
+        kind: IntegrityIssueKind::OrphanRow,
+        table: "task_tags",
+        id: "task_id",
+        condition: "task_id NOT IN (SELECT id FROM tasks) OR tag_id NOT IN (SELECT id FROM tags)",
+        message: "Tag on a task or with a tag that no longer exists",
+        fix: "DELETE FROM task_tags",
+    },
+    Invariant {
+        kind: IntegrityIssueKind::OrphanRow,
+        table: "checklist_items",
+        id: "id",
+        condition: "task_id NOT IN (SELECT id FROM tasks)",
+        message: "Checklist item of a task that no longer exists",
+        fix: "DELETE FROM checklist_items",
+    },
+    Invariant {
+        kind: IntegrityIssueKind::OrphanRow,
+        table: "task_attachments",
+        id: "id",
+        condition: "task_id NOT IN (SELECT id FROM tasks)",
+        message: "Attachment of a task that no longer exists",
+        fix: "DELETE FROM task_attachments",
+    },
+    Invariant {
+        kind: IntegrityIssueKind::OrphanRow,
+        table: "task_field_values",
+        id: "task_id",
+        condition: "task_id NOT IN (SELECT id FROM tasks) OR field_id NOT IN (SELECT id FROM custom_fields)",
+        message: "Field value of a task or field that no longer exists",
+        fix: "DELETE FROM task_field_values",
+    },
+    Invariant {
+        kind: IntegrityIssueKind::OrphanRow,
+        table: "daily_plan_items",
+        id: "task_id",
+        condition: "task_id NOT IN (SELECT id FROM tasks)",
+        message: "Planned task that no longer exists",
+        fix: "DELETE FROM daily_plan_items",
+    },
+    Invariant {
+        kind: IntegrityIssueKind::OrphanRow,
+        table: "task_links",
+        id: "source_id",
+        condition: "source_id NOT IN (SELECT id FROM tasks)",
+        message: "Link from a task that no longer exists",
+        fix: "DELETE FROM task_links",
+    },
+    Invariant {
+        kind: IntegrityIssueKind::OrphanRow,
+        table: "task_links",
+        id: "source_id",
+        condition: "target_id IS NOT NULL AND target_id NOT IN (SELECT id FROM tasks)",
+        message: "Link to a task that no longer exists",
+        fix: "UPDATE task_links SET target_id = NULL",
+    },
+    Invariant {
+        kind: IntegrityIssueKind::OrphanRow,
+        table: "focus_sessions",
+        id: "id",
+        condition: "task_id IS NOT NULL AND task_id NOT IN (SELECT id FROM tasks)",
+        message: "Focus session on a task that no longer exists",
+        fix: "UPDATE focus_sessions SET task_id = NULL",
+    },
+    Invariant {
+        kind: IntegrityIssueKind::MissingCategory,
+        table: "custom_fields",
+        id: "id",
+        condition: "category_id IS NOT NULL AND category_id NOT IN (SELECT id FROM categories)",
+        message: "Custom field scoped to a category that no longer exists",
+        fix: "UPDATE custom_fields SET category_id = NULL",
+    },
+    Invariant {
+        kind: IntegrityIssueKind::MissingCategory,
+        table: "task_templates",
+        id: "id",
+        condition: "category_id IS NOT NULL AND category_id NOT IN (SELECT id FROM categories)",
+        message: "Template for a category that no longer exists",
+        fix: "UPDATE task_templates SET category_id = NULL",
+    },
+];
+
+/// Timestamp columns of tasks, and whether an unreadable value is cleared
+/// rather than replaced with the current time
+const TASK_DATE_COLUMNS: [(&str, bool); 5] = [
+    ("created_at", false),
+    ("updated_at", false),
+    ("due_date", true),
+    ("completed_at", true),
+    ("archived_at", true),
+];
+
+fn status_from_row(row: &SqliteRow) -> TaskStatus {
+    TaskStatus {
+        id: row.get("id"),
+        name: row.get("name"),
+        color: row.get("color"),
+        position: row.get("position"),
+        is_done: row.get::<i32, _>("is_done") != 0,
+        created_at: row.get("created_at"),
+    }
+}
+
+fn checklist_item_from_row(row: &SqliteRow) -> ChecklistItem {
+    ChecklistItem {
+        id: row.get("id"),
+        task_id: row.get("task_id"),
+        title: row.get("title"),
+        done: row.get::<i32, _>("done") != 0,
+        position: row.get("position"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn priority_from_row(row: &SqliteRow) -> Priority {
+    Priority {
+        id: row.get("id"),
+        name: row.get("name"),
+        weight: row.get("weight"),
+        color: row.get("color"),
+        icon: row.get("icon"),
+        is_default: row.get::<i32, _>("is_default") != 0,
+        created_at: row.get("created_at"),
+    }
+}
+
+fn template_from_row(row: &SqliteRow) -> Result<TaskTemplate, sqlx::Error> {
+    let body: String = row.get("body");
+    let tasks: Vec<TemplateTask> = serde_json::from_str(&body).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+    Ok(TaskTemplate {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        category_id: row.get("category_id"),
+        variables: crate::templates::template_variables(&tasks),
+        tasks,
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+fn link_from_row(row: &SqliteRow) -> TaskLink {
+    TaskLink {
+        source_id: row.get("source_id"),
+        source_title: row.get("source_title"),
+        target_id: row.get("target_id"),
+        target_title: row.get("target_title"),
+        kind: row.get("kind"),
+        reference: row.get("reference"),
+    }
+}
+
+/// ORDER BY clause for `get_tasks`. Ties fall back to newest first.
+fn task_order(sort_by: Option<TaskSortKey>, descending: bool) -> String {
+    let direction = if descending { "DESC" } else { "ASC" };
+
+    let key = match sort_by {
+        // Urgency is computed in Rust by the caller
+        None | Some(TaskSortKey::Urgency) => return "created_at DESC".to_string(),
+        Some(TaskSortKey::CreatedAt) => format!("created_at {}", direction),
+        Some(TaskSortKey::UpdatedAt) => format!("updated_at {}", direction),
+        Some(TaskSortKey::DueDate) => format!("due_date IS NULL, datetime(due_date) {}", direction),
+        Some(TaskSortKey::Priority) => format!(
+            "(SELECT weight FROM priorities WHERE priorities.id = tasks.priority_id) {}",
+            direction
+        ),
+        Some(TaskSortKey::Title) => format!("title COLLATE NOCASE {}", direction),
+        Some(TaskSortKey::CustomField(field_id)) => {
+            // Numbers sort numerically, everything else by its canonical text
+            let value = format!(
+                "(SELECT CASE custom_fields.field_type WHEN 'number' THEN CAST(task_field_values.value AS REAL) ELSE task_field_values.value END 
+                 FROM task_field_values JOIN custom_fields ON custom_fields.id = task_field_values.field_id 
+                 WHERE task_field_values.task_id = tasks.id AND task_field_values.field_id = {})",
+                field_id
+            );
+            format!("{} IS NULL, {} {}", value, value, direction)
+        }
+    };
+
+    format!("{}, created_at DESC", key)
+}
+
+fn custom_field_from_row(row: &SqliteRow) -> CustomField {
+    let options: String = row.get("options");
+
+    CustomField {
+        id: row.get("id"),
+        name: row.get("name"),
+        field_type: row.get("field_type"),
+        options: serde_json::from_str(&options).unwrap_or_default(),
+        category_id: row.get("category_id"),
+        position: row.get("position"),
+        created_at: row.get("created_at"),
+    }
+}
+
+fn task_from_row(row: &SqliteRow) -> Task {
+    let tags: Option<String> = row.get("tags");
+    let field_values: Option<String> = row.get("field_values");
+
+    Task {
+        id: row.get("id"),
+        title: row.get("title"),
+        description: row.get("description"),
+        completed: row.get::<i32, _>("completed") != 0,
+        status_id: row.get("status_id"),
+        priority: row.get("priority"),
+        category_id: row.get("category_id"),
+        due_date: row.get("due_date"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        completed_at: row.get("completed_at"),
+        archived_at: row.get("archived_at"),
+        parent_id: row.get("parent_id"),
+        tags: tags
+            .map(|t| t.split(TAG_SEPARATOR).map(str::to_string).collect())
+            .unwrap_or_default(),
+        custom_fields: field_values
+            .and_then(|v| serde_json::from_str(&v).ok())
+            .unwrap_or_default(),
+        is_private: row.get::<i32, _>("is_private") != 0,
+        locked: false,
+    }
+}
+
+/// `path` with `suffix` appended, like SQLite names its `-wal` file.
+fn sidecar_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut sidecar = path.as_os_str().to_owned();
+    sidecar.push(suffix);
+    PathBuf::from(sidecar)
+}
+
+/// SQLCipher cannot tell a wrong key from a file that is not a database.
+/// The tuning pragmas read the file, so connecting is what fails.
+fn is_wrong_key(error: &sqlx::Error) -> bool {
+    matches!(error, sqlx::Error::Database(e) if e.code().as_deref() == Some("26"))
+}
+
+/// How long a connection waits for another one to finish writing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn connect_options(path: &Path, key: Option<&DerivedKey>) -> SqliteConnectOptions {
+    // WAL lets reads go on while the background tasks write, and NORMAL
+    // sync is safe with it. Incremental vacuum only takes effect for new
+    // files; maintenance converts older ones.
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Normal)
+        .foreign_keys(true)
+        .busy_timeout(BUSY_TIMEOUT)
+        .auto_vacuum(SqliteAutoVacuum::Incremental);
+    
+    // sqlx runs the key pragma before anything else touches the file
+    match key {
+        Some(key) => options.pragma("key", key.pragma_value()),
+        None => options,
+    }
+}
+
+#[derive(Clone)]
+pub struct Database {
+    pool: SqlitePool,
+    /// Keeps other instances from writing to the same file; `None` for an
+    /// in-memory database
+    lock: Option<Arc<DatabaseLock>>,
+}
+
+impl Database {
+    /// Opens (creating it if needed) and migrates the database at `path`.
+    /// Fails when another instance already has the file open.
+    pub async fn open(path: &Path) -> Result<Self, sqlx::Error> {
+        Self::open_with_passphrase(path, None).await
+    }
+
+    pub async fn open_with_passphrase(path: &Path, passphrase: Option<&str>) -> Result<Self, sqlx::Error> {
+        if path.is_dir() {
+            return Err(sqlx::Error::Configuration(
+                format!("{} is a directory", path.display()).into(),
+            ));
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        
+        let lock = DatabaseLock::acquire(path)?;
+        let key = match KeyInfo::load(path)? {
+            Some(info) => {
+                let passphrase = passphrase.ok_or_else(|| {
+                    sqlx::Error::Configuration("The database is encrypted; a passphrase is required".into())
+                })?;
+                Some(info.derive(passphrase).map_err(|e| sqlx::Error::Configuration(e.into()))?)
+            }
+            None => None,
+        };
+        
+        Self::connect(path, key.as_ref(), Arc::new(lock)).await
+    }
+
+    async fn connect(path: &Path, key: Option<&DerivedKey>, lock: Arc<DatabaseLock>) -> Result<Self, sqlx::Error> {
+        let pool = match SqlitePool::connect_with(connect_options(path, key)).await {
+            Err(e) if key.is_some() && is_wrong_key(&e) => {
+                return Err(sqlx::Error::Configuration("Wrong passphrase".into()));
+            }
+            result => result?,
+        };
+        
+        // Run migrations, refusing a newer schema and keeping a snapshot of
+        // the file before upgrading it
+        schema::migrate(&pool, Some(path)).await?;
+        
+        Ok(Database { pool, lock: Some(lock) })
+    }
+
+    /// Opens a fresh, migrated database that only lives in memory, for tests
+    /// and scripts. It is gone once the last clone is dropped.
+    pub async fn open_in_memory() -> Result<Self, sqlx::Error> {
+        let options = SqliteConnectOptions::from_str("sqlite::memory:")?.foreign_keys(true);
+        // Every connection shares the one database, so the pool must never
+        // close its last connection
+        let pool = SqlitePoolOptions::new()
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(options)
+            .await?;
+        
+        schema::migrate(&pool, None).await?;
+        
+        Ok(Database { pool, lock: None })
+    }
+
+    /// The underlying pool, for queries the methods here do not cover.
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// File this database was opened from.
+    pub fn path(&self) -> Option<&Path> {
+        self.lock.as_ref().map(|lock| lock.path())
+    }
+
+    pub async fn migration_status(&self) -> Result<MigrationStatus, sqlx::Error> {
+        schema::status(&self.pool, self.path()).await
+    }
+
+    // Encryption operations
+    pub fn is_encrypted(&self) -> bool {
+        self.path().is_some_and(|path| KeyInfo::path(path).exists())
+    }
+
+    /// Checks `passphrase` against the encrypted file without disturbing the
+    /// open connections.
+    pub async fn verify_passphrase(&self, passphrase: &str) -> Result<bool, sqlx::Error> {
+        let path = self.path().ok_or_else(|| sqlx::Error::Configuration("The database has no file".into()))?;
+        let info = KeyInfo::load(path)?
+            .ok_or_else(|| sqlx::Error::Configuration("The database is not encrypted".into()))?;
+        let key = info.derive(passphrase).map_err(|e| sqlx::Error::Configuration(e.into()))?;
+        
+        match connect_options(path, Some(&key)).read_only(true).connect().await {
+            Ok(connection) => {
+                connection.close().await?;
+                Ok(true)
+            }
+            Err(e) if is_wrong_key(&e) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Rewrites the file encrypted under `passphrase`, or in plaintext when
+    /// it is `None`, and returns the reopened database. Every other handle to
+    /// this database stops working, so callers must replace the shared one.
+    pub async fn set_passphrase(self, passphrase: Option<&str>) -> Result<Database, sqlx::Error> {
+        let lock = self.lock.clone()
+            .ok_or_else(|| sqlx::Error::Configuration("The database has no file".into()))?;
+        let path = lock.path().to_path_buf();
+        
+        let info = passphrase.map(|_| KeyInfo::generate());
+        let key = match (&info, passphrase) {
+            (Some(info), Some(passphrase)) => {
+                Some(info.derive(passphrase).map_err(|e| sqlx::Error::Configuration(e.into()))?)
+            }
+            _ => None,
+        };
+        
+        let temp_path = sidecar_path(&path, ".rekey");
+        if temp_path.exists() {
+            std::fs::remove_file(&temp_path)?;
+        }
+        
+        // An empty key attaches a plaintext database
+        let target_key = key.as_ref().map(|k| k.pragma_value()).unwrap_or_else(|| "''".to_string());
+        let mut connection = self.pool.acquire().await?;
+        sqlx::query(&format!(
+            "ATTACH DATABASE '{}' AS rekeyed KEY {}",
+            temp_path.display().to_string().replace('\'', "''"),
+            target_key
+        ))
+        .execute(&mut *connection)
+        .await?;
+        let exported = sqlx::query("SELECT sqlcipher_export('rekeyed')").execute(&mut *connection).await;
+        sqlx::query("DETACH DATABASE rekeyed").execute(&mut *connection).await?;
+        drop(connection);
+        if let Err(e) = exported {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(e);
+        }
+        
+        self.pool.close().await;
+        // The log of the old file must not be replayed into the new one
+        for suffix in ["-wal", "-shm"] {
+            let sidecar = sidecar_path(&path, suffix);
+            if sidecar.exists() {
+                std::fs::remove_file(sidecar)?;
+            }
+        }
+        std::fs::rename(&temp_path, &path)?;
+        match &info {
+            Some(info) => info.save(&path)?,
+            None => KeyInfo::remove(&path)?,
+        }
+        
+        Self::connect(&path, key.as_ref(), lock).await
+    }
+
+    // Task operations
+    pub async fn get_tasks(&self, filters: Option<TaskFilters>) -> Result<Vec<Task>, sqlx::Error> {
+        let mut query = format!("SELECT {} FROM tasks WHERE 1=1", TASK_COLUMNS);
+        let mut conditions = Vec::new();
+        let mut binds: Vec<String> = Vec::new();
+        let mut include_archived = false;
+        let mut order = task_order(None, false);
+        
+        if let Some(filters) = filters {
+            // Archived tasks stay reachable through search
+            include_archived = filters.include_archived.unwrap_or(filters.search.is_some());
+
+            if let Some(completed) = filters.completed {
+                conditions.push(format!("completed = {}", completed as i32));
+            }
+            if let Some(priority) = filters.priority {
+                conditions.push("priority_id = (SELECT id FROM priorities WHERE name = ?)".to_string());
+                binds.push(priority);
+            }
+            if let Some(category_id) = filters.category_id {
+                conditions.push(format!("category_id = {}", category_id));
+            }
+            if let Some(status_id) = filters.status_id {
+                conditions.push(format!("status_id = {}", status_id));
+            }
+            if let Some(parent_id) = filters.parent_id {
+                conditions.push(format!("parent_id = {}", parent_id));
+            }
+            // Due dates carry their own offsets, so they are compared as instants
+            if let Some(after) = filters.due_date_after {
+                conditions.push("julianday(due_date) >= julianday(?)".to_string());
+                binds.push(after);
+            }
+            if let Some(before) = filters.due_date_before {
+                conditions.push("julianday(due_date) <= julianday(?)".to_string());
+                binds.push(before);
+            }
+            if let Some(search) = filters.search {
+                conditions.push("(title LIKE '%' || ? || '%' OR description LIKE '%' || ? || '%')".to_string());
+                binds.push(search.clone());
+                binds.push(search);
+            }
+            for filter in filters.custom_fields.unwrap_or_default() {
+                let mut checks = Vec::new();
+                for (op, value) in [("=", filter.equals), (">=", filter.min), ("<=", filter.max)] {
+                    if let Some(value) = value {
+                        checks.push(format!(
+                            " AND CASE custom_fields.field_type WHEN 'number' THEN CAST(task_field_values.value AS REAL) {op} CAST(? AS REAL) 
+                              ELSE task_field_values.value {op} ? COLLATE NOCASE END",
+                            op = op
+                        ));
+                        binds.push(value.clone());
+                        binds.push(value);
+                    }
+                }
+                conditions.push(format!(
+                    "EXISTS (SELECT 1 FROM task_field_values JOIN custom_fields ON custom_fields.id = task_field_values.field_id 
+                     WHERE task_field_values.task_id = tasks.id AND task_field_values.field_id = {}{})",
+                    filter.field_id,
+                    checks.concat()
+                ));
+            }
+            order = task_order(filters.sort_by, filters.sort_descending.unwrap_or(false));
+        }
+        
+        if !include_archived {
+            conditions.push("archived_at IS NULL".to_string());
+        }
+        
+        if !conditions.is_empty() {
+            query.push_str(" AND ");
+            query.push_str(&conditions.join(" AND "));
+        }
+        
+        query.push_str(" ORDER BY ");
+        query.push_str(&order);
+        
+        // Search text, due dates and custom field values are user text, so they are bound rather than inlined
+        let mut statement = sqlx::query(&query);
+        for value in &binds {
+            statement = statement.bind(value);
+        }
+        let rows = statement.fetch_all(&self.pool).await?;
+        
+        Ok(rows.iter().map(task_from_row).collect())
+    }
+
+    pub async fn get_task(&self, id: i64) -> Result<Option<Task>, sqlx::Error> {
+        let row = sqlx::query(&format!("SELECT {} FROM tasks WHERE id = ?", TASK_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(row.as_ref().map(task_from_row))
+    }
+
+    /// Creates a task. References to rows that do not exist are reported
+    /// together as a validation error and nothing is written.
+    pub async fn create_task(&self, input: CreateTaskInput) -> Result<Task, AppError> {
+        let mut tx = self.pool.begin().await?;
+        Self::check_task_references(&mut tx, &input).await?.into_result()?;
+        let task = Self::insert_task(&mut tx, input).await?;
+        tx.commit().await?;
+        
+        Ok(task)
+    }
+
+    /// Finds the category, priority, status and parent of a new task that do
+    /// not exist. Runs in the writing transaction, so a row deleted in the
+    /// meantime cannot slip through.
+    async fn check_task_references(
+        tx: &mut Transaction<'_, Sqlite>,
+        input: &CreateTaskInput,
+    ) -> Result<Violations, sqlx::Error> {
+        let mut violations = Self::check_category_and_priority(tx, input.category_id, input.priority.as_deref()).await?;
+        
+        if let Some(status_id) = input.status_id {
+            if !Self::row_exists(tx, "task_statuses", status_id).await? {
+                violations.add("status_id", format!("Status {} does not exist", status_id));
+            }
+        }
+        if let Some(parent_id) = input.parent_id {
+            if !Self::row_exists(tx, "tasks", parent_id).await? {
+                violations.add("parent_id", format!("Task {} does not exist", parent_id));
+            }
+        }
+        
+        Ok(violations)
+    }
+
+    /// Finds the rows an update refers to that do not exist, a status that
+    /// contradicts `completed` and a parent inside the task's own subtree.
+    /// Also returns whether the new status, if any, means done.
+    async fn check_update_references(
+        tx: &mut Transaction<'_, Sqlite>,
+        input: &UpdateTaskInput,
+    ) -> Result<(Violations, Option<bool>), sqlx::Error> {
+        let mut violations = Self::check_category_and_priority(
+            tx,
+            input.category_id.value().copied(),
+            input.priority.value().map(String::as_str),
+        ).await?;
+        
+        let mut status_done = None;
+        if let Patch::Value(status_id) = input.status_id {
+            status_done = sqlx::query_scalar("SELECT is_done FROM task_statuses WHERE id = ?")
+                .bind(status_id)
+                .fetch_optional(&mut **tx)
+                .await?;
+            match (status_done, input.completed.value()) {
+                (None, _) => violations.add("status_id", format!("Status {} does not exist", status_id)),
+                (Some(done), Some(completed)) if done != *completed => violations.add(
+                    "status_id",
+                    format!("Status {} is {}, which contradicts completed", status_id, if done { "done" } else { "open" }),
+                ),
+                _ => {}
+            }
+        }
+        if let Patch::Value(parent_id) = input.parent_id {
+            let inside: bool = sqlx::query_scalar(&format!("{} SELECT EXISTS(SELECT 1 FROM subtree WHERE id = ?)", SUBTREE))
+                .bind(input.id)
+                .bind(parent_id)
+                .fetch_one(&mut **tx)
+                .await?;
+            if !Self::row_exists(tx, "tasks", parent_id).await? {
+                violations.add("parent_id", format!("Task {} does not exist", parent_id));
+            } else if inside {
+                violations.add("parent_id", "A task cannot be moved under itself or one of its subtasks");
+            }
+        }
+        
+        Ok((violations, status_done))
+    }
+
+    async fn check_category_and_priority(
+        tx: &mut Transaction<'_, Sqlite>,
+        category_id: Option<i64>,
+        priority: Option<&str>,
+    ) -> Result<Violations, sqlx::Error> {
+        let mut violations = Violations::default();
+        
+        if let Some(category_id) = category_id {
+            if !Self::row_exists(tx, "categories", category_id).await? {
+                violations.add("category_id", format!("Category {} does not exist", category_id));
+            }
+        }
+        // Priorities are user-defined, so names are checked against the table
+        if let Some(name) = priority {
+            let names: Vec<String> = sqlx::query_scalar("SELECT name FROM priorities ORDER BY weight DESC, id")
+                .fetch_all(&mut **tx)
+                .await?;
+            if !names.iter().any(|p| p.eq_ignore_ascii_case(name)) {
+                violations.add("priority", format!("Priority must be one of: {}", names.join(", ")));
+            }
+        }
+        
+        Ok(violations)
+    }
+
+    async fn row_exists(tx: &mut Transaction<'_, Sqlite>, table: &str, id: i64) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar(&format!("SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?)", table))
+            .bind(id)
+            .fetch_one(&mut **tx)
+            .await
+    }
+
+    async fn insert_task(tx: &mut Transaction<'_, Sqlite>, input: CreateTaskInput) -> Result<Task, sqlx::Error> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let priority = Self::resolve_priority(tx, input.priority.as_deref()).await?;
+        
+        // New tasks land in the requested status or in the first open one
+        let status = match input.status_id {
+            Some(status_id) => Self::fetch_status(tx, status_id).await?,
+            None => Self::default_status(tx, false).await?,
+        };
+        let completed_at = status.is_done.then(|| now.clone());
+        
+        let result = sqlx::query(
+            "INSERT INTO tasks (title, description, completed, status_id, completed_at, priority_id, category_id, due_date, parent_id, created_at, updated_at) 
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&input.title)
+        .bind(&input.description)
+        .bind(status.is_done)
+        .bind(status.id)
+        .bind(&completed_at)
+        .bind(priority.id)
+        .bind(input.category_id)
+        .bind(&input.due_date)
+        .bind(input.parent_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut **tx)
+        .await?;
+        
+        let id = result.last_insert_rowid();
+        let tags = Self::attach_tags(tx, id, &input.tags).await?;
+        Self::sync_links(tx, id, input.description.as_deref()).await?;
+        Self::resolve_dangling_links(tx, id, &input.title).await?;
+        
+        Ok(Task {
+            id,
+            title: input.title,
+            description: input.description,
+            completed: status.is_done,
+            status_id: Some(status.id),
+            priority: priority.name,
+            category_id: input.category_id,
+            due_date: input.due_date,
+            created_at: now.clone(),
+            updated_at: now,
+            completed_at,
+            archived_at: None,
+            parent_id: input.parent_id,
+            tags,
+            custom_fields: Default::default(),
+            is_private: false,
+            locked: false,
+        })
+    }
+
+    /// Links `names` to a task, creating any tag that does not exist yet.
+    /// Returns the normalized, de-duplicated tag names in input order.
+    async fn attach_tags(
+        tx: &mut Transaction<'_, Sqlite>,
+        task_id: i64,
+        names: &[String],
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let mut attached: Vec<String> = Vec::new();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        for name in names {
+            let name = name.trim().to_lowercase();
+            if name.is_empty() || attached.contains(&name) {
+                continue;
+            }
+
+            sqlx::query("INSERT OR IGNORE INTO tags (name, created_at) VALUES (?, ?)")
+                .bind(&name)
+                .bind(&now)
+                .execute(&mut **tx)
+                .await?;
+
+            sqlx::query(
+                "INSERT OR IGNORE INTO task_tags (task_id, tag_id) SELECT ?, id FROM tags WHERE name = ?"
+            )
+            .bind(task_id)
+            .bind(&name)
+            .execute(&mut **tx)
+            .await?;
+
+            attached.push(name);
+        }
+
+        Ok(attached)
+    }
+
+    /// Applies the fields present in `input` to a task and reports what
+    /// changed. Only those columns are written, so a field can be cleared
+    /// with `Patch::Null`. Returns `None` when there is no task with that ID,
+    /// and a validation error when a referenced row does not exist, the
+    /// status contradicts `completed` or the new parent is inside the task.
+    pub async fn update_task(&self, input: UpdateTaskInput) -> Result<Option<TaskUpdate>, AppError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+        
+        let Some(before) = Self::fetch_task(&mut tx, input.id).await? else {
+            return Ok(None);
+        };
+        let (violations, status_done) = Self::check_update_references(&mut tx, &input).await?;
+        violations.into_result()?;
+        
+        let mut query = QueryBuilder::<Sqlite>::new("UPDATE tasks SET updated_at = ");
+        query.push_bind(&now);
+        let mut provided = false;
+        
+        if let Patch::Value(title) = &input.title {
+            query.push(", title = ").push_bind(title);
+            provided = true;
+        }
+        if !input.description.is_absent() {
+            query.push(", description = ").push_bind(input.description.value());
+            provided = true;
+        }
+        // completed_at keeps its original value when a done task is saved
+        // again, and reopening a task also brings it back from the archive.
+        // A new status decides completion; toggling completion alone moves
+        // the task to the first status of the matching kind unless its
+        // current status already fits.
+        if let Some(completed) = status_done.or(input.completed.value().copied()) {
+            query.push(", completed = ").push_bind(completed);
+            match input.status_id {
+                Patch::Value(status_id) => {
+                    query.push(", status_id = ").push_bind(status_id);
+                }
+                _ => {
+                    query.push(", status_id = CASE WHEN (SELECT is_done FROM task_statuses WHERE id = status_id) = ")
+                        .push_bind(completed)
+                        .push(" THEN status_id ELSE (SELECT id FROM task_statuses WHERE is_done = ")
+                        .push_bind(completed)
+                        .push(" ORDER BY position LIMIT 1) END");
+                }
+            }
+            query.push(", completed_at = CASE WHEN ")
+                .push_bind(completed)
+                .push(" THEN COALESCE(completed_at, ")
+                .push_bind(&now)
+                .push(") ELSE NULL END");
+            query.push(", archived_at = CASE WHEN ")
+                .push_bind(completed)
+                .push(" THEN archived_at ELSE NULL END");
+            provided = true;
+        }
+        match &input.priority {
+            Patch::Absent => {}
+            Patch::Null => {
+                query.push(", priority_id = (SELECT id FROM priorities ORDER BY is_default DESC, weight LIMIT 1)");
+                provided = true;
+            }
+            Patch::Value(name) => {
+                query.push(", priority_id = (SELECT id FROM priorities WHERE name = ").push_bind(name).push(")");
+                provided = true;
+            }
+        }
+        if !input.category_id.is_absent() {
+            query.push(", category_id = ").push_bind(input.category_id.value().copied());
+            provided = true;
+        }
+        if !input.due_date.is_absent() {
+            query.push(", due_date = ").push_bind(input.due_date.value());
+            provided = true;
+        }
+        if !input.parent_id.is_absent() {
+            query.push(", parent_id = ").push_bind(input.parent_id.value().copied());
+            provided = true;
+        }
+        
+        // Nothing to write, not even `updated_at`
+        if !provided {
+            return Ok(Some(TaskUpdate::between(&before, before.clone())));
+        }
+        
+        query.push(" WHERE id = ").push_bind(input.id);
+        query.build().execute(&mut *tx).await?;
+        
+        if !input.description.is_absent() {
+            Self::sync_links(&mut tx, input.id, input.description.value().map(String::as_str)).await?;
+        }
+        
+        // Renaming keeps `[[Old title]]` references pointing at this task
+        if let Patch::Value(new) = &input.title {
+            if before.title != *new {
+                Self::rename_links(&mut tx, input.id, &before.title, new).await?;
+                Self::resolve_dangling_links(&mut tx, input.id, new).await?;
+            }
+        }
+        
+        let after = Self::fetch_task(&mut tx, input.id).await?.ok_or(sqlx::Error::RowNotFound)?;
+        tx.commit().await?;
+        
+        Ok(Some(TaskUpdate::between(&before, after)))
+    }
+
+    pub async fn set_task_archived(&self, id: i64, archived: bool) -> Result<Option<Task>, sqlx::Error> {
+        let now = chrono::Utc::now().to_rfc3339();
+        
+        sqlx::query(
+            "UPDATE tasks SET archived_at = CASE WHEN ? THEN COALESCE(archived_at, ?) ELSE NULL END, updated_at = ? WHERE id = ?"
+        )
+        .bind(archived)
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        
+        self.get_task(id).await
+    }
+
+    /// Archives every completed task whose `completed_at` is older than
+    /// `after_days`. Returns the number of tasks archived.
+    pub async fn archive_completed_tasks(&self, after_days: i64) -> Result<u64, sqlx::Error> {
+        let now = chrono::Utc::now();
+        let cutoff = (now - chrono::Duration::days(after_days)).to_rfc3339();
+        
+        let result = sqlx::query(
+            "UPDATE tasks SET archived_at = ? 
+             WHERE completed = 1 AND archived_at IS NULL AND completed_at IS NOT NULL AND completed_at <= ?"
+        )
+        .bind(now.to_rfc3339())
+        .bind(&cutoff)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(result.rows_affected())
+    }
+
+    /// Applies the `auto_archive_after_days` setting, if the user enabled it.
+    pub async fn apply_archive_policy(&self) -> Result<u64, sqlx::Error> {
+        let after_days = self
+            .get_setting(AUTO_ARCHIVE_SETTING)
+            .await?
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|days| *days > 0);
+        
+        match after_days {
+            Some(days) => self.archive_completed_tasks(days).await,
+            None => Ok(0),
+        }
+    }
+
+    /// Writes back lifecycle timestamps that `create_task`/`update_task`
+    /// would otherwise stamp with the current time. Used by restores.
+    pub async fn restore_task_timestamps(
+        &self,
+        id: i64,
+        completed_at: Option<&str>,
+        archived_at: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE tasks SET completed_at = COALESCE(?, completed_at), archived_at = ? WHERE id = ?")
+            .bind(completed_at)
+            .bind(archived_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    pub async fn set_task_parent(&self, id: i64, parent_id: Option<i64>) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE tasks SET parent_id = ? WHERE id = ?")
+            .bind(parent_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    /// Deletes a task together with all of its subtasks.
+    pub async fn delete_task(&self, id: i64) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        
+        for statement in [
+            "DELETE FROM task_tags WHERE task_id IN subtree",
+            "DELETE FROM checklist_items WHERE task_id IN subtree",
+            "DELETE FROM task_attachments WHERE task_id IN subtree",
+            "DELETE FROM task_field_values WHERE task_id IN subtree",
+            "DELETE FROM daily_plan_items WHERE task_id IN subtree",
+            "DELETE FROM task_links WHERE source_id IN subtree",
+            "UPDATE task_links SET target_id = NULL WHERE target_id IN subtree",
+            "UPDATE focus_sessions SET task_id = NULL WHERE task_id IN subtree",
+            "DELETE FROM tasks WHERE id IN subtree",
+        ] {
+            sqlx::query(&format!("{} {}", SUBTREE, statement))
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn fetch_task(tx: &mut Transaction<'_, Sqlite>, id: i64) -> Result<Option<Task>, sqlx::Error> {
+        let row = sqlx::query(&format!("SELECT {} FROM tasks WHERE id = ?", TASK_COLUMNS))
+            .bind(id)
+            .fetch_optional(&mut **tx)
+            .await?;
+        
+        Ok(row.as_ref().map(task_from_row))
+    }
+
+    /// Copies a task with its tags, checklist, attachment references and,
+    /// optionally, its subtasks in a single transaction. The copy sits next
+    /// to the original. Returns `None` when the task does not exist, and a
+    /// validation error when a shifted due date falls outside the calendar.
+    pub async fn duplicate_task(&self, id: i64, options: &DuplicateTaskOptions) -> Result<Option<Task>, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut root: Option<Task> = None;
+        
+        // (task to copy, parent of the copy); the root keeps its own parent
+        let mut pending: Vec<(i64, Option<i64>)> = vec![(id, None)];
+        
+        while let Some((original_id, parent_id)) = pending.pop() {
+            let Some(original) = Self::fetch_task(&mut tx, original_id).await? else {
+                continue;
+            };
+            let is_root = root.is_none();
+            
+            let due_date = match (original.due_date, options.shift_due_minutes) {
+                (Some(due), Some(minutes)) => match chrono::DateTime::parse_from_rfc3339(&due) {
+                    Ok(d) => {
+                        let shifted = chrono::Duration::try_minutes(minutes).and_then(|shift| d.checked_add_signed(shift));
+                        let Some(shifted) = shifted else {
+                            return Err(AppError::invalid_field("shift_due_minutes", "The shifted due date is out of range"));
+                        };
+                        Some(shifted.to_rfc3339())
+                    }
+                    Err(_) => Some(due),
+                },
+                (due, _) => due,
+            };
+            
+            let copy = Self::insert_task(&mut tx, CreateTaskInput {
+                title: match &options.title {
+                    Some(title) if is_root => title.clone(),
+                    _ => original.title,
+                },
+                description: original.description,
+                priority: Some(original.priority),
+                category_id: original.category_id,
+                due_date,
+                tags: original.tags,
+                status_id: if options.reset_completion { None } else { original.status_id },
+                parent_id: if is_root { original.parent_id } else { parent_id },
+            }).await?;
+            
+            if original.is_private {
+                sqlx::query("UPDATE tasks SET is_private = 1 WHERE id = ?")
+                    .bind(copy.id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            
+            let items = sqlx::query("SELECT title, done FROM checklist_items WHERE task_id = ? ORDER BY position, id")
+                .bind(original_id)
+                .fetch_all(&mut *tx)
+                .await?;
+            for item in items {
+                let done = item.get::<i32, _>("done") != 0 && !options.reset_completion;
+                Self::insert_checklist_item(&mut tx, copy.id, item.get("title"), done).await?;
+            }
+            
+            sqlx::query(
+                "INSERT INTO task_attachments (task_id, kind, target, label, created_at) 
+                 SELECT ?, kind, target, label, ? FROM task_attachments WHERE task_id = ? ORDER BY id"
+            )
+            .bind(copy.id)
+            .bind(&now)
+            .bind(original_id)
+            .execute(&mut *tx)
+            .await?;
+            
+            sqlx::query(
+                "INSERT INTO task_field_values (task_id, field_id, value) 
+                 SELECT ?, field_id, value FROM task_field_values WHERE task_id = ?"
+            )
+            .bind(copy.id)
+            .bind(original_id)
+            .execute(&mut *tx)
+            .await?;
+            
+            if options.include_subtasks {
+                // Pushed in reverse so that subtasks are copied in their original order
+                let children: Vec<i64> = sqlx::query_scalar("SELECT id FROM tasks WHERE parent_id = ? ORDER BY id DESC")
+                    .bind(original_id)
+                    .fetch_all(&mut *tx)
+                    .await?;
+                pending.extend(children.into_iter().map(|child| (child, Some(copy.id))));
+            }
+            
+            if is_root {
+                root = Some(copy);
+            }
+        }
+        
+        tx.commit().await?;
+        Ok(root)
+    }
+
+    /// Moves a task to another status, deriving `completed` from it. Returns
+    /// `None` when there is no task with that ID, and a validation error when
+    /// the status does not exist.
+    pub async fn transition_task(&self, id: i64, status_id: i64) -> Result<Option<Task>, AppError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+        
+        if !Self::row_exists(&mut tx, "tasks", id).await? {
+            return Ok(None);
+        }
+        let mut violations = Violations::default();
+        if !Self::row_exists(&mut tx, "task_statuses", status_id).await? {
+            violations.add("status_id", format!("Status {} does not exist", status_id));
+        }
+        violations.into_result()?;
+        let status = Self::fetch_status(&mut tx, status_id).await?;
+        
+        sqlx::query(
+            "UPDATE tasks SET 
+             status_id = ?,
+             completed = ?,
+             completed_at = CASE WHEN ? THEN COALESCE(completed_at, ?) ELSE NULL END,
+             archived_at = CASE WHEN ? THEN archived_at ELSE NULL END,
+             updated_at = ?
+             WHERE id = ?"
+        )
+        .bind(status.id)
+        .bind(status.is_done)
+        .bind(status.is_done)
+        .bind(&now)
+        .bind(status.is_done)
+        .bind(&now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        
+        let task = Self::fetch_task(&mut tx, id).await?;
+        tx.commit().await?;
+        
+        Ok(task)
+    }
+
+    // Checklist operations
+    /// Checklist of one task, or of every task when `task_id` is `None`.
+    pub async fn get_checklist(&self, task_id: Option<i64>) -> Result<Vec<ChecklistItem>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, task_id, title, done, position, created_at FROM checklist_items 
+             WHERE ? IS NULL OR task_id = ? ORDER BY task_id, position, id"
+        )
+        .bind(task_id)
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(rows.iter().map(checklist_item_from_row).collect())
+    }
+
+    /// Appends an item to a task's checklist; a task that does not exist is
+    /// reported as a validation error.
+    pub async fn add_checklist_item(&self, task_id: i64, title: &str) -> Result<ChecklistItem, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let mut violations = Violations::default();
+        if !Self::row_exists(&mut tx, "tasks", task_id).await? {
+            violations.add("task_id", format!("Task {} does not exist", task_id));
+        }
+        violations.into_result()?;
+        let item = Self::insert_checklist_item(&mut tx, task_id, title, false).await?;
+        tx.commit().await?;
+        
+        Ok(item)
+    }
+
+    async fn insert_checklist_item(
+        tx: &mut Transaction<'_, Sqlite>,
+        task_id: i64,
+        title: &str,
+        done: bool,
+    ) -> Result<ChecklistItem, sqlx::Error> {
+        let now = chrono::Utc::now().to_rfc3339();
+        
+        let row = sqlx::query(
+            "INSERT INTO checklist_items (task_id, title, done, position, created_at) 
+             VALUES (?, ?, ?, (SELECT COALESCE(MAX(position), -1) + 1 FROM checklist_items WHERE task_id = ?), ?)
+             RETURNING id, position"
+        )
+        .bind(task_id)
+        .bind(title)
+        .bind(done)
+        .bind(task_id)
+        .bind(&now)
+        .fetch_one(&mut **tx)
+        .await?;
+        
+        Ok(ChecklistItem {
+            id: row.get("id"),
+            task_id,
+            title: title.to_string(),
+            done,
+            position: row.get("position"),
+            created_at: now,
+        })
+    }
+
+    pub async fn get_checklist_item(&self, id: i64) -> Result<Option<ChecklistItem>, sqlx::Error> {
+        let row = sqlx::query("SELECT id, task_id, title, done, position, created_at FROM checklist_items WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(row.as_ref().map(checklist_item_from_row))
+    }
+
+    pub async fn update_checklist_item(&self, input: UpdateChecklistItemInput) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE checklist_items SET title = COALESCE(?, title), done = COALESCE(?, done) WHERE id = ?")
+            .bind(&input.title)
+            .bind(input.done)
+            .bind(input.id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    pub async fn delete_checklist_item(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM checklist_items WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    // Link operations
+    /// Replaces the links of a task with the references in its description.
+    async fn sync_links(
+        tx: &mut Transaction<'_, Sqlite>,
+        source_id: i64,
+        description: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM task_links WHERE source_id = ?")
+            .bind(source_id)
+            .execute(&mut **tx)
+            .await?;
+        
+        let references = description.map(links::find_references).unwrap_or_default();
+        for (_, reference) in references {
+            // Titles are not unique; prefer active tasks, then the oldest one
+            let target_id: Option<i64> = match &reference {
+                Reference::Title(title) => sqlx::query_scalar(
+                    "SELECT id FROM tasks WHERE title = ? COLLATE NOCASE AND id != ? 
+                     ORDER BY archived_at IS NOT NULL, id LIMIT 1"
+                )
+                .bind(title)
+                .bind(source_id)
+                .fetch_optional(&mut **tx)
+                .await?,
+                Reference::Id(id) => sqlx::query_scalar("SELECT id FROM tasks WHERE id = ? AND id != ?")
+                    .bind(id)
+                    .bind(source_id)
+                    .fetch_optional(&mut **tx)
+                    .await?,
+            };
+            let (kind, text) = reference.parts();
+            
+            sqlx::query("INSERT OR IGNORE INTO task_links (source_id, target_id, kind, reference) VALUES (?, ?, ?, ?)")
+                .bind(source_id)
+                .bind(target_id)
+                .bind(kind)
+                .bind(&text)
+                .execute(&mut **tx)
+                .await?;
+        }
+        
+        Ok(())
+    }
+
+    /// Points dangling `[[title]]` links at a task that now carries the title.
+    async fn resolve_dangling_links(
+        tx: &mut Transaction<'_, Sqlite>,
+        task_id: i64,
+        title: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE task_links SET target_id = ? 
+             WHERE target_id IS NULL AND kind = 'title' AND reference = ? COLLATE NOCASE AND source_id != ?"
+        )
+        .bind(task_id)
+        .bind(title.trim())
+        .bind(task_id)
+        .execute(&mut **tx)
+        .await?;
+        
+        Ok(())
+    }
+
+    /// Rewrites `[[old]]` to `[[new]]` in every description linking to the task.
+    async fn rename_links(
+        tx: &mut Transaction<'_, Sqlite>,
+        task_id: i64,
+        old: &str,
+        new: &str,
+    ) -> Result<(), sqlx::Error> {
+        let sources = sqlx::query(
+            "SELECT DISTINCT tasks.id, tasks.description FROM task_links 
+             JOIN tasks ON tasks.id = task_links.source_id 
+             WHERE task_links.target_id = ? AND task_links.kind = 'title'"
+        )
+        .bind(task_id)
+        .fetch_all(&mut **tx)
+        .await?;
+        
+        for row in sources {
+            let source_id: i64 = row.get("id");
+            let Some(description) = row.get::<Option<String>, _>("description") else {
+                continue;
+            };
+            let renamed = links::rename_title(&description, old, new);
+            if renamed == description {
+                continue;
+            }
+            
+            sqlx::query("UPDATE tasks SET description = ? WHERE id = ?")
+                .bind(&renamed)
+                .bind(source_id)
+                .execute(&mut **tx)
+                .await?;
+            Self::sync_links(tx, source_id, Some(&renamed)).await?;
+        }
+        
+        Ok(())
+    }
+
+    /// Outgoing links of a task, dangling ones included.
+    pub async fn get_links(&self, task_id: i64) -> Result<Vec<TaskLink>, sqlx::Error> {
+        let rows = sqlx::query(&format!("{} WHERE task_links.source_id = ? ORDER BY task_links.rowid", LINK_QUERY))
+            .bind(task_id)
+            .fetch_all(&self.pool)
+            .await?;
+        
+        Ok(rows.iter().map(link_from_row).collect())
+    }
+
+    /// Links from other tasks that point at this one.
+    pub async fn get_backlinks(&self, task_id: i64) -> Result<Vec<TaskLink>, sqlx::Error> {
+        let rows = sqlx::query(&format!("{} WHERE task_links.target_id = ? ORDER BY source.title", LINK_QUERY))
+            .bind(task_id)
+            .fetch_all(&self.pool)
+            .await?;
+        
+        Ok(rows.iter().map(link_from_row).collect())
+    }
+
+    /// Links whose reference does not match any task.
+    pub async fn get_dangling_links(&self) -> Result<Vec<TaskLink>, sqlx::Error> {
+        let rows = sqlx::query(&format!("{} WHERE task_links.target_id IS NULL ORDER BY source.title", LINK_QUERY))
+            .fetch_all(&self.pool)
+            .await?;
+        
+        Ok(rows.iter().map(link_from_row).collect())
+    }
+
+    // Daily plan operations
+    /// Tasks planned for `date` (`YYYY-MM-DD`), in order.
+    pub async fn get_plan_items(&self, date: &str) -> Result<Vec<PlanItem>, sqlx::Error> {
+        let rows = sqlx::query(&format!(
+            "SELECT {}, daily_plan_items.estimate_minutes, daily_plan_items.carried_from 
+             FROM daily_plan_items JOIN tasks ON tasks.id = daily_plan_items.task_id 
+             WHERE daily_plan_items.date = ? ORDER BY daily_plan_items.position, daily_plan_items.task_id",
+            TASK_COLUMNS
+        ))
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(rows.iter().map(|row| PlanItem {
+            task: task_from_row(row),
+            estimate_minutes: row.get::<Option<i64>, _>("estimate_minutes").map(|m| m as u32),
+            carried_from: row.get("carried_from"),
+        }).collect())
+    }
+
+    /// Capacity set for the day, if any.
+    pub async fn get_plan_capacity(&self, date: &str) -> Result<Option<u32>, sqlx::Error> {
+        let capacity: Option<Option<i64>> = sqlx::query_scalar("SELECT capacity_minutes FROM daily_plans WHERE date = ?")
+            .bind(date)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(capacity.flatten().map(|m| m as u32))
+    }
+
+    /// Sets the capacity of a day; `None` falls back to the default.
+    pub async fn set_plan_capacity(&self, date: &str, capacity_minutes: Option<u32>) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO daily_plans (date, capacity_minutes, created_at) VALUES (?, ?, ?) 
+             ON CONFLICT (date) DO UPDATE SET capacity_minutes = excluded.capacity_minutes"
+        )
+        .bind(date)
+        .bind(capacity_minutes)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+
+    async fn ensure_plan(tx: &mut Transaction<'_, Sqlite>, date: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("INSERT OR IGNORE INTO daily_plans (date, created_at) VALUES (?, ?)")
+            .bind(date)
+            .bind(chrono::Utc::now().to_rfc3339())
+            .execute(&mut **tx)
+            .await?;
+        
+        Ok(())
+    }
+
+    async fn plan_task_ids(tx: &mut Transaction<'_, Sqlite>, date: &str) -> Result<Vec<i64>, sqlx::Error> {
+        sqlx::query_scalar("SELECT task_id FROM daily_plan_items WHERE date = ? ORDER BY position, task_id")
+            .bind(date)
+            .fetch_all(&mut **tx)
+            .await
+    }
+
+    async fn write_plan_order(tx: &mut Transaction<'_, Sqlite>, date: &str, task_ids: &[i64]) -> Result<(), sqlx::Error> {
+        for (position, task_id) in task_ids.iter().enumerate() {
+            sqlx::query("UPDATE daily_plan_items SET position = ? WHERE date = ? AND task_id = ?")
+                .bind(position as i64)
+                .bind(date)
+                .bind(task_id)
+                .execute(&mut **tx)
+                .await?;
+        }
+        
+        Ok(())
+    }
+
+    /// Adds a task to a day at `position` (the end by default). A task that
+    /// is already planned moves there and keeps its estimate unless a new
+    /// one is given. A task that does not exist is a validation error.
+    pub async fn add_to_plan(
+        &self,
+        date: &str,
+        task_id: i64,
+        estimate_minutes: Option<u32>,
+        position: Option<usize>,
+    ) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        let mut violations = Violations::default();
+        if !Self::row_exists(&mut tx, "tasks", task_id).await? {
+            violations.add("task_id", format!("Task {} does not exist", task_id));
+        }
+        violations.into_result()?;
+        Self::ensure_plan(&mut tx, date).await?;
+        
+        let mut task_ids = Self::plan_task_ids(&mut tx, date).await?;
+        let current = task_ids.iter().position(|id| *id == task_id);
+        
+        sqlx::query(
+            "INSERT INTO daily_plan_items (date, task_id, estimate_minutes) VALUES (?, ?, ?) 
+             ON CONFLICT (date, task_id) DO UPDATE SET estimate_minutes = COALESCE(excluded.estimate_minutes, estimate_minutes)"
+        )
+        .bind(date)
+        .bind(task_id)
+        .bind(estimate_minutes)
+        .execute(&mut *tx)
+        .await?;
+        
+        task_ids.retain(|id| *id != task_id);
+        let at = position.or(current).unwrap_or(task_ids.len()).min(task_ids.len());
+        task_ids.insert(at, task_id);
+        Self::write_plan_order(&mut tx, date, &task_ids).await?;
+        
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn remove_from_plan(&self, date: &str, task_id: i64) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        
+        sqlx::query("DELETE FROM daily_plan_items WHERE date = ? AND task_id = ?")
+            .bind(date)
+            .bind(task_id)
+            .execute(&mut *tx)
+            .await?;
+        
+        let task_ids = Self::plan_task_ids(&mut tx, date).await?;
+        Self::write_plan_order(&mut tx, date, &task_ids).await?;
+        
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Reorders a day's plan to follow `task_ids`; tasks left out keep their
+    /// relative order after the listed ones.
+    pub async fn reorder_plan(&self, date: &str, task_ids: &[i64]) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        
+        let current = Self::plan_task_ids(&mut tx, date).await?;
+        let mut ordered: Vec<i64> = task_ids.iter().copied().filter(|id| current.contains(id)).collect();
+        ordered.extend(current.iter().copied().filter(|id| !task_ids.contains(id)));
+        Self::write_plan_order(&mut tx, date, &ordered).await?;
+        
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Brings the unfinished tasks of the most recent earlier plan that was
+    /// opened or has tasks over to `date`, after the tasks already planned.
+    /// Runs once per day, so tasks removed afterwards stay removed. Returns
+    /// the number of tasks carried.
+    pub async fn carry_over_plan(&self, date: &str) -> Result<u64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        Self::ensure_plan(&mut tx, date).await?;
+        
+        let carried_over_at: Option<String> = sqlx::query_scalar("SELECT carried_over_at FROM daily_plans WHERE date = ?")
+            .bind(date)
+            .fetch_one(&mut *tx)
+            .await?;
+        // A day that was opened counts even if everything was removed from it
+        let previous: Option<String> = sqlx::query_scalar(
+            "SELECT MAX(date) FROM daily_plans 
+             WHERE date < ? AND (carried_over_at IS NOT NULL OR EXISTS (SELECT 1 FROM daily_plan_items WHERE daily_plan_items.date = daily_plans.date))"
+        )
+        .bind(date)
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        let mut carried = 0;
+        if let (None, Some(previous)) = (carried_over_at, previous) {
+            let offset = Self::plan_task_ids(&mut tx, date).await?.len() as i64;
+            
+            carried = sqlx::query(
+                "INSERT OR IGNORE INTO daily_plan_items (date, task_id, position, estimate_minutes, carried_from) 
+                 SELECT ?, items.task_id, items.position + ?, items.estimate_minutes, COALESCE(items.carried_from, items.date) 
+                 FROM daily_plan_items items JOIN tasks ON tasks.id = items.task_id 
+                 WHERE items.date = ? AND tasks.completed = 0 AND tasks.archived_at IS NULL"
+            )
+            .bind(date)
+            .bind(offset)
+            .bind(&previous)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            
+            let task_ids = Self::plan_task_ids(&mut tx, date).await?;
+            Self::write_plan_order(&mut tx, date, &task_ids).await?;
+        }
+        
+        sqlx::query("UPDATE daily_plans SET carried_over_at = COALESCE(carried_over_at, ?) WHERE date = ?")
+            .bind(chrono::Utc::now().to_rfc3339())
+            .bind(date)
+            .execute(&mut *tx)
+            .await?;
+        
+        tx.commit().await?;
+        Ok(carried)
+    }
+
+    /// Every plan as stored, for backups.
+    pub async fn get_plan_records(&self) -> Result<Vec<DailyPlanRecord>, sqlx::Error> {
+        let plans = sqlx::query("SELECT date, capacity_minutes FROM daily_plans ORDER BY date")
+            .fetch_all(&self.pool)
+            .await?;
+        let items = sqlx::query(
+            "SELECT date, task_id, estimate_minutes, carried_from FROM daily_plan_items ORDER BY date, position, task_id"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(plans.iter().map(|plan| {
+            let date: String = plan.get("date");
+            DailyPlanRecord {
+                items: items
+                    .iter()
+                    .filter(|item| item.get::<String, _>("date") == date)
+                    .map(|item| DailyPlanRecordItem {
+                        task_id: item.get("task_id"),
+                        estimate_minutes: item.get::<Option<i64>, _>("estimate_minutes").map(|m| m as u32),
+                        carried_from: item.get("carried_from"),
+                    })
+                    .collect(),
+                capacity_minutes: plan.get::<Option<i64>, _>("capacity_minutes").map(|m| m as u32),
+                date,
+            }
+        }).collect())
+    }
+
+    /// Writes back a plan from a backup whose task IDs are already mapped to
+    /// the restored tasks. Restored plans count as carried over.
+    pub async fn restore_plan(&self, record: &DailyPlanRecord) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        
+        sqlx::query(
+            "INSERT OR REPLACE INTO daily_plans (date, capacity_minutes, carried_over_at, created_at) VALUES (?, ?, ?, ?)"
+        )
+        .bind(&record.date)
+        .bind(record.capacity_minutes)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+        
+        for (position, item) in record.items.iter().enumerate() {
+            sqlx::query(
+                "INSERT OR IGNORE INTO daily_plan_items (date, task_id, position, estimate_minutes, carried_from) VALUES (?, ?, ?, ?, ?)"
+            )
+            .bind(&record.date)
+            .bind(item.task_id)
+            .bind(position as i64)
+            .bind(item.estimate_minutes)
+            .bind(&item.carried_from)
+            .execute(&mut *tx)
+            .await?;
+        }
+        
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Custom field operations
+    /// All fields, or only those that apply to `category_id` (including the
+    /// unscoped ones) when it is given.
+    pub async fn get_custom_fields(&self, category_id: Option<i64>) -> Result<Vec<CustomField>, sqlx::Error> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM custom_fields 
+             WHERE ? IS NULL OR category_id IS NULL OR category_id = ? ORDER BY position, id",
+            CUSTOM_FIELD_COLUMNS
+        ))
+        .bind(category_id)
+        .bind(category_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(rows.iter().map(custom_field_from_row).collect())
+    }
+
+    pub async fn get_custom_field(&self, id: i64) -> Result<Option<CustomField>, sqlx::Error> {
+        let row = sqlx::query(&format!("SELECT {} FROM custom_fields WHERE id = ?", CUSTOM_FIELD_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(row.as_ref().map(custom_field_from_row))
+    }
+
+    /// Adds a custom field after the others; a category that does not exist
+    /// is reported as a validation error.
+    pub async fn create_custom_field(&self, input: CreateCustomFieldInput) -> Result<CustomField, AppError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let options = serde_json::to_string(&input.options).expect("options serialize to JSON");
+        let mut tx = self.pool.begin().await?;
+        Self::check_category_and_priority(&mut tx, input.category_id, None).await?.into_result()?;
+        
+        let row = sqlx::query(
+            "INSERT INTO custom_fields (name, field_type, options, category_id, position, created_at) 
+             VALUES (?, ?, ?, ?, (SELECT COALESCE(MAX(position), -1) + 1 FROM custom_fields), ?)
+             RETURNING id, position"
+        )
+        .bind(&input.name)
+        .bind(&input.field_type)
+        .bind(&options)
+        .bind(input.category_id)
+        .bind(&now)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        
+        Ok(CustomField {
+            id: row.get("id"),
+            name: input.name,
+            field_type: input.field_type,
+            options: input.options,
+            category_id: input.category_id,
+            position: row.get("position"),
+            created_at: now,
+        })
+    }
+
+    pub async fn update_custom_field(&self, input: UpdateCustomFieldInput) -> Result<Option<CustomField>, sqlx::Error> {
+        let options = input
+            .options
+            .as_ref()
+            .map(|o| serde_json::to_string(o).expect("options serialize to JSON"));
+        
+        sqlx::query("UPDATE custom_fields SET name = COALESCE(?, name), options = COALESCE(?, options) WHERE id = ?")
+            .bind(&input.name)
+            .bind(&options)
+            .bind(input.id)
+            .execute(&self.pool)
+            .await?;
+        
+        self.get_custom_field(input.id).await
+    }
+
+    pub async fn delete_custom_field(&self, id: i64) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        
+        sqlx::query("DELETE FROM task_field_values WHERE field_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        
+        sqlx::query("DELETE FROM custom_fields WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Stores a value already normalized by `custom_fields::normalize_value`;
+    /// `None` clears it. A task or field that does not exist is reported as
+    /// a validation error.
+    pub async fn set_task_field_value(
+        &self,
+        task_id: i64,
+        field_id: i64,
+        value: Option<&str>,
+    ) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        
+        match value {
+            Some(value) => {
+                let mut violations = Violations::default();
+                if !Self::row_exists(&mut tx, "tasks", task_id).await? {
+                    violations.add("task_id", format!("Task {} does not exist", task_id));
+                }
+                if !Self::row_exists(&mut tx, "custom_fields", field_id).await? {
+                    violations.add("field_id", format!("Custom field {} does not exist", field_id));
+                }
+                violations.into_result()?;
+                
+                sqlx::query(
+                    "INSERT INTO task_field_values (task_id, field_id, value) VALUES (?, ?, ?) 
+                     ON CONFLICT (task_id, field_id) DO UPDATE SET value = excluded.value"
+                )
+                .bind(task_id)
+                .bind(field_id)
+                .bind(value)
+                .execute(&mut *tx)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM task_field_values WHERE task_id = ? AND field_id = ?")
+                    .bind(task_id)
+                    .bind(field_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+        
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Attachment operations
+    /// Attachments of one task, or of every task when `task_id` is `None`.
+    pub async fn get_attachments(&self, task_id: Option<i64>) -> Result<Vec<Attachment>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, task_id, kind, target, label, created_at FROM task_attachments 
+             WHERE ? IS NULL OR task_id = ? ORDER BY task_id, id"
+        )
+        .bind(task_id)
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|row| Attachment {
+            id: row.get("id"),
+            task_id: row.get("task_id"),
+            kind: row.get("kind"),
+            target: row.get("target"),
+            label: row.get("label"),
+            created_at: row.get("created_at"),
+        }).collect())
+    }
+
+    /// Attaches a link or file to a task; a task that does not exist is
+    /// reported as a validation error.
+    pub async fn add_attachment(&self, input: CreateAttachmentInput) -> Result<Attachment, AppError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+        
+        let mut violations = Violations::default();
+        if !Self::row_exists(&mut tx, "tasks", input.task_id).await? {
+            violations.add("task_id", format!("Task {} does not exist", input.task_id));
+        }
+        violations.into_result()?;
+        
+        let result = sqlx::query(
+            "INSERT INTO task_attachments (task_id, kind, target, label, created_at) VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(input.task_id)
+        .bind(&input.kind)
+        .bind(&input.target)
+        .bind(&input.label)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        
+        Ok(Attachment {
+            id: result.last_insert_rowid(),
+            task_id: input.task_id,
+            kind: input.kind,
+            target: input.target,
+            label: input.label,
+            created_at: now,
+        })
+    }
+
+    pub async fn delete_attachment(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM task_attachments WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    // Template operations
+    pub async fn get_templates(&self) -> Result<Vec<TaskTemplate>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, name, description, category_id, body, created_at, updated_at FROM task_templates ORDER BY name"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.iter().map(template_from_row).collect()
+    }
+
+    pub async fn get_template(&self, id: i64) -> Result<Option<TaskTemplate>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT id, name, description, category_id, body, created_at, updated_at FROM task_templates WHERE id = ?"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        row.as_ref().map(template_from_row).transpose()
+    }
+
+    /// Stores a template; a category that does not exist is reported as a
+    /// validation error.
+    pub async fn create_template(&self, input: CreateTemplateInput) -> Result<TaskTemplate, AppError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let body = serde_json::to_string(&input.tasks).expect("template tasks are always serializable");
+        let mut tx = self.pool.begin().await?;
+        Self::check_category_and_priority(&mut tx, input.category_id, None).await?.into_result()?;
+        
+        let result = sqlx::query(
+            "INSERT INTO task_templates (name, description, category_id, body, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&input.name)
+        .bind(&input.description)
+        .bind(input.category_id)
+        .bind(&body)
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        
+        Ok(TaskTemplate {
+            id: result.last_insert_rowid(),
+            name: input.name,
+            description: input.description,
+            category_id: input.category_id,
+            variables: crate::templates::template_variables(&input.tasks),
+            tasks: input.tasks,
+            created_at: now.clone(),
+            updated_at: now,
+        })
+    }
+
+    pub async fn delete_template(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM task_templates WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    /// Creates tasks and their checklists in a single transaction.
+    pub async fn create_tasks_with_checklists(
+        &self,
+        instances: Vec<crate::templates::TemplateInstance>,
+    ) -> Result<Vec<Task>, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let mut tasks = Vec::with_capacity(instances.len());
+        
+        for instance in instances {
+            Self::check_task_references(&mut tx, &instance.task).await?.into_result()?;
+            let task = Self::insert_task(&mut tx, instance.task).await?;
+            for title in &instance.checklist {
+                Self::insert_checklist_item(&mut tx, task.id, title, false).await?;
+            }
+            tasks.push(task);
+        }
+        
+        tx.commit().await?;
+        Ok(tasks)
+    }
+
+    // Priority operations
+    /// The named priority, or the default one when `name` is `None` or
+    /// unknown. Commands reject unknown names before they get here.
+    async fn resolve_priority(tx: &mut Transaction<'_, Sqlite>, name: Option<&str>) -> Result<Priority, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM priorities ORDER BY name = ? DESC, is_default DESC, weight LIMIT 1",
+            PRIORITY_COLUMNS
+        ))
+        .bind(name)
+        .fetch_one(&mut **tx)
+        .await?;
+        
+        Ok(priority_from_row(&row))
+    }
+
+    async fn fetch_priority(tx: &mut Transaction<'_, Sqlite>, id: i64) -> Result<Priority, sqlx::Error> {
+        let row = sqlx::query(&format!("SELECT {} FROM priorities WHERE id = ?", PRIORITY_COLUMNS))
+            .bind(id)
+            .fetch_one(&mut **tx)
+            .await?;
+        
+        Ok(priority_from_row(&row))
+    }
+
+    /// Priorities from most to least important.
+    pub async fn get_priorities(&self) -> Result<Vec<Priority>, sqlx::Error> {
+        let rows = sqlx::query(&format!("SELECT {} FROM priorities ORDER BY weight DESC, id", PRIORITY_COLUMNS))
+            .fetch_all(&self.pool)
+            .await?;
+        
+        Ok(rows.iter().map(priority_from_row).collect())
+    }
+
+    pub async fn create_priority(&self, input: CreatePriorityInput) -> Result<Priority, sqlx::Error> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let color = input.color.unwrap_or_else(|| "#6B7280".to_string());
+        let mut tx = self.pool.begin().await?;
+        
+        if input.is_default {
+            sqlx::query("UPDATE priorities SET is_default = 0").execute(&mut *tx).await?;
+        }
+        
+        let result = sqlx::query(
+            "INSERT INTO priorities (name, weight, color, icon, is_default, created_at) VALUES (?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&input.name)
+        .bind(input.weight)
+        .bind(&color)
+        .bind(&input.icon)
+        .bind(input.is_default)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+        
+        let priority = Self::fetch_priority(&mut tx, result.last_insert_rowid()).await?;
+        tx.commit().await?;
+        
+        Ok(priority)
+    }
+
+    /// Updates a priority. Tasks reference priorities by ID, so renaming
+    /// carries over to them.
+    pub async fn update_priority(&self, input: UpdatePriorityInput) -> Result<Priority, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        
+        if input.is_default == Some(true) {
+            sqlx::query("UPDATE priorities SET is_default = (id = ?)")
+                .bind(input.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        
+        sqlx::query(
+            "UPDATE priorities SET name = COALESCE(?, name), weight = COALESCE(?, weight), 
+             color = COALESCE(?, color), icon = COALESCE(?, icon) WHERE id = ?"
+        )
+        .bind(&input.name)
+        .bind(input.weight)
+        .bind(&input.color)
+        .bind(&input.icon)
+        .bind(input.id)
+        .execute(&mut *tx)
+        .await?;
+        
+        let priority = Self::fetch_priority(&mut tx, input.id).await?;
+        tx.commit().await?;
+        
+        Ok(priority)
+    }
+
+    /// Deletes a priority, moving its tasks to `reassign_to` or to the
+    /// default priority. The target inherits the default flag if needed. A
+    /// `reassign_to` priority that does not exist is reported as a
+    /// validation error.
+    pub async fn delete_priority(&self, id: i64, reassign_to: Option<i64>) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        
+        let current = Self::fetch_priority(&mut tx, id).await?;
+        
+        let mut violations = Violations::default();
+        if let Some(target) = reassign_to.filter(|target| *target != id) {
+            if !Self::row_exists(&mut tx, "priorities", target).await? {
+                violations.add("reassign_to", format!("Priority {} does not exist", target));
+            }
+        }
+        violations.into_result()?;
+        
+        let target = match reassign_to.filter(|target| *target != id) {
+            Some(target) => Self::fetch_priority(&mut tx, target).await?,
+            None => {
+                let row = sqlx::query(&format!(
+                    "SELECT {} FROM priorities WHERE id != ? ORDER BY is_default DESC, ABS(weight - ?), id LIMIT 1",
+                    PRIORITY_COLUMNS
+                ))
+                .bind(id)
+                .bind(current.weight)
+                .fetch_one(&mut *tx)
+                .await?;
+                priority_from_row(&row)
+            }
+        };
+        
+        sqlx::query("UPDATE tasks SET priority_id = ?, updated_at = ? WHERE priority_id = ?")
+            .bind(target.id)
+            .bind(chrono::Utc::now().to_rfc3339())
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        
+        if current.is_default {
+            sqlx::query("UPDATE priorities SET is_default = 1 WHERE id = ?")
+                .bind(target.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        
+        sqlx::query("DELETE FROM priorities WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Status operations
+    async fn fetch_status(tx: &mut Transaction<'_, Sqlite>, id: i64) -> Result<TaskStatus, sqlx::Error> {
+        let row = sqlx::query(&format!("SELECT {} FROM task_statuses WHERE id = ?", STATUS_COLUMNS))
+            .bind(id)
+            .fetch_one(&mut **tx)
+            .await?;
+        
+        Ok(status_from_row(&row))
+    }
+
+    /// First status, by position, that is done (or open).
+    async fn default_status(tx: &mut Transaction<'_, Sqlite>, is_done: bool) -> Result<TaskStatus, sqlx::Error> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM task_statuses WHERE is_done = ? ORDER BY position, id LIMIT 1",
+            STATUS_COLUMNS
+        ))
+        .bind(is_done)
+        .fetch_one(&mut **tx)
+        .await?;
+        
+        Ok(status_from_row(&row))
+    }
+
+    pub async fn get_statuses(&self) -> Result<Vec<TaskStatus>, sqlx::Error> {
+        let rows = sqlx::query(&format!("SELECT {} FROM task_statuses ORDER BY position, id", STATUS_COLUMNS))
+            .fetch_all(&self.pool)
+            .await?;
+        
+        Ok(rows.iter().map(status_from_row).collect())
+    }
+
+    pub async fn create_status(&self, input: CreateStatusInput) -> Result<TaskStatus, sqlx::Error> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let color = input.color.unwrap_or_else(|| "#6B7280".to_string());
+        
+        let mut tx = self.pool.begin().await?;
+        
+        let result = sqlx::query(
+            "INSERT INTO task_statuses (name, color, position, is_done, created_at) 
+             VALUES (?, ?, (SELECT COALESCE(MAX(position), -1) + 1 FROM task_statuses), ?, ?)"
+        )
+        .bind(&input.name)
+        .bind(&color)
+        .bind(input.is_done)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await?;
+        
+        let status = Self::fetch_status(&mut tx, result.last_insert_rowid()).await?;
+        tx.commit().await?;
+        
+        Ok(status)
+    }
+
+    /// Updates a status. Flipping `is_done` re-derives `completed` for every
+    /// task in it.
+    pub async fn update_status(&self, input: UpdateStatusInput) -> Result<TaskStatus, sqlx::Error> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+        
+        let current = Self::fetch_status(&mut tx, input.id).await?;
+        
+        if let Some(is_done) = input.is_done.filter(|d| *d != current.is_done) {
+            sqlx::query(
+                "UPDATE tasks SET 
+                 completed = ?,
+                 completed_at = CASE WHEN ? THEN COALESCE(completed_at, ?) ELSE NULL END,
+                 archived_at = CASE WHEN ? THEN archived_at ELSE NULL END,
+                 updated_at = ?
+                 WHERE status_id = ?"
+            )
+            .bind(is_done)
+            .bind(is_done)
+            .bind(&now)
+            .bind(is_done)
+            .bind(&now)
+            .bind(current.id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        
+        sqlx::query(
+            "UPDATE task_statuses SET name = COALESCE(?, name), color = COALESCE(?, color), is_done = COALESCE(?, is_done) WHERE id = ?"
+        )
+        .bind(&input.name)
+        .bind(&input.color)
+        .bind(input.is_done)
+        .bind(input.id)
+        .execute(&mut *tx)
+        .await?;
+        
+        let status = Self::fetch_status(&mut tx, input.id).await?;
+        tx.commit().await?;
+        
+        Ok(status)
+    }
+
+    /// Rewrites status positions to follow `ids`; statuses left out keep
+    /// their relative order after the listed ones. IDs of statuses that do
+    /// not exist are reported as a validation error.
+    pub async fn reorder_statuses(&self, ids: &[i64]) -> Result<Vec<TaskStatus>, AppError> {
+        let mut tx = self.pool.begin().await?;
+        
+        let rows = sqlx::query("SELECT id FROM task_statuses ORDER BY position, id")
+            .fetch_all(&mut *tx)
+            .await?;
+        let existing: Vec<i64> = rows.iter().map(|r| r.get::<i64, _>("id")).collect();
+        let mut violations = Violations::default();
+        for id in ids.iter().filter(|id| !existing.contains(id)) {
+            violations.add("ids", format!("Status {} does not exist", id));
+        }
+        violations.into_result()?;
+        
+        let mut ordered: Vec<i64> = ids.to_vec();
+        ordered.extend(existing.into_iter().filter(|id| !ids.contains(id)));
+        
+        for (position, id) in ordered.iter().enumerate() {
+            sqlx::query("UPDATE task_statuses SET position = ? WHERE id = ?")
+                .bind(position as i64)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        
+        tx.commit().await?;
+        Ok(self.get_statuses().await?)
+    }
+
+    /// Deletes a status, moving its tasks to `reassign_to` or, by default, to
+    /// the first other status of the same kind. A `reassign_to` status that
+    /// does not exist is reported as a validation error.
+    pub async fn delete_status(&self, id: i64, reassign_to: Option<i64>) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        
+        let current = Self::fetch_status(&mut tx, id).await?;
+        
+        let mut violations = Violations::default();
+        if let Some(target) = reassign_to.filter(|target| *target != id) {
+            if !Self::row_exists(&mut tx, "task_statuses", target).await? {
+                violations.add("reassign_to", format!("Status {} does not exist", target));
+            }
+        }
+        violations.into_result()?;
+        
+        let target = match reassign_to.filter(|target| *target != id) {
+            Some(target) => Self::fetch_status(&mut tx, target).await?,
+            None => {
+                let row = sqlx::query(&format!(
+                    "SELECT {} FROM task_statuses WHERE is_done = ? AND id != ? ORDER BY position, id LIMIT 1",
+                    STATUS_COLUMNS
+                ))
+                .bind(current.is_done)
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await?;
+                status_from_row(&row)
+            }
+        };
+        
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            "UPDATE tasks SET 
+             status_id = ?,
+             completed = ?,
+             completed_at = CASE WHEN ? THEN COALESCE(completed_at, ?) ELSE NULL END,
+             archived_at = CASE WHEN ? THEN archived_at ELSE NULL END,
+             updated_at = ?
+             WHERE status_id = ?"
+        )
+        .bind(target.id)
+        .bind(target.is_done)
+        .bind(target.is_done)
+        .bind(&now)
+        .bind(target.is_done)
+        .bind(&now)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        
+        sqlx::query("DELETE FROM task_statuses WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Category operations
+    pub async fn get_categories(&self) -> Result<Vec<Category>, sqlx::Error> {
+        let rows = sqlx::query("SELECT id, name, color, created_at, is_private FROM categories ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?;
+        
+        let categories = rows.into_iter().map(|row| Category {
+            id: row.get("id"),
+            name: row.get("name"),
+            color: row.get("color"),
+            created_at: row.get("created_at"),
+            is_private: row.get::<i32, _>("is_private") != 0,
+        }).collect();
+        
+        Ok(categories)
+    }
+
+    pub async fn create_category(&self, input: CreateCategoryInput) -> Result<Category, sqlx::Error> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let color = input.color.unwrap_or_else(|| "#F97316".to_string());
+        
+        let result = sqlx::query(
+            "INSERT INTO categories (name, color, created_at) VALUES (?, ?, ?)"
+        )
+        .bind(&input.name)
+        .bind(&color)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+        
+        let id = result.last_insert_rowid();
+        
+        Ok(Category {
+            id,
+            name: input.name,
+            color,
+            created_at: now,
+            is_private: false,
+        })
+    }
+
+    pub async fn delete_category(&self, id: i64) -> Result<(), sqlx::Error> {
+        // One transaction, so a failure never leaves tasks pointing at a
+        // deleted category
+        let mut tx = self.pool.begin().await?;
+        
+        // Tasks of a private category stay private, and encrypted, on their own
+        sqlx::query(
+            "UPDATE tasks SET is_private = 1 
+             WHERE category_id = ? AND (SELECT is_private FROM categories WHERE id = ?) = 1"
+        )
+        .bind(id)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        
+        sqlx::query("UPDATE tasks SET category_id = NULL WHERE category_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        
+        // Fields scoped to the category become available everywhere
+        sqlx::query("UPDATE custom_fields SET category_id = NULL WHERE category_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        
+        sqlx::query("DELETE FROM categories WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Privacy operations
+    /// Marks a category private or not and converts the content of all of
+    /// its tasks, archived ones included, in the same transaction, so no
+    /// task is left in the wrong form. Returns `false` when there is no such
+    /// category.
+    pub async fn set_category_private(&self, id: i64, private: bool, sealer: &dyn Sealer) -> Result<bool, AppError> {
+        let mut tx = self.pool.begin().await?;
+        
+        let result = sqlx::query("UPDATE categories SET is_private = ? WHERE id = ?")
+            .bind(private)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        
+        let task_ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM tasks WHERE category_id = ?")
+            .bind(id)
+            .fetch_all(&mut *tx)
+            .await?;
+        for task_id in task_ids {
+            Self::seal_task_content(&mut tx, task_id, sealer).await?;
+        }
+        
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn is_category_private(&self, id: Option<i64>) -> Result<bool, sqlx::Error> {
+        let Some(id) = id else {
+            return Ok(false);
+        };
+        let private: Option<bool> = sqlx::query_scalar("SELECT is_private FROM categories WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(private.unwrap_or(false))
+    }
+
+    /// Marks a task private or not and converts its content to match in the
+    /// same transaction. Returns the task as stored, or `None` when there is
+    /// no such task.
+    pub async fn set_task_private(&self, id: i64, private: bool, sealer: &dyn Sealer) -> Result<Option<Task>, AppError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut tx = self.pool.begin().await?;
+        
+        let result = sqlx::query("UPDATE tasks SET is_private = ?, updated_at = ? WHERE id = ?")
+            .bind(private)
+            .bind(&now)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        Self::seal_task_content(&mut tx, id, sealer).await?;
+        
+        let task = Self::fetch_task(&mut tx, id).await?;
+        tx.commit().await?;
+        Ok(task)
+    }
+
+    /// Converts the content of a task to match its own flag and its
+    /// category's, e.g. after it moved to another category. Returns the task
+    /// as stored.
+    pub async fn sync_private_content(&self, id: i64, sealer: &dyn Sealer) -> Result<Option<Task>, AppError> {
+        let mut tx = self.pool.begin().await?;
+        Self::seal_task_content(&mut tx, id, sealer).await?;
+        let task = Self::fetch_task(&mut tx, id).await?;
+        tx.commit().await?;
+        
+        Ok(task)
+    }
+
+    /// Encrypts or decrypts the title, description, checklist, attachments
+    /// and custom field values of a task so that they match whether it is
+    /// private. Unlike `update_task` this does not touch references in other
+    /// tasks. Custom field filters compare stored values, so private tasks
+    /// do not match them.
+    async fn seal_task_content(tx: &mut Transaction<'_, Sqlite>, id: i64, sealer: &dyn Sealer) -> Result<(), AppError> {
+        let row: Option<(String, Option<String>, bool)> = sqlx::query_as(
+            "SELECT tasks.title, tasks.description, tasks.is_private OR COALESCE(categories.is_private, 0)
+             FROM tasks LEFT JOIN categories ON categories.id = tasks.category_id WHERE tasks.id = ?"
+        )
+        .bind(id)
+        .fetch_optional(&mut **tx)
+        .await?;
+        let Some((title, description, private)) = row else {
+            return Ok(());
+        };
+        if private == is_sealed(&title) {
+            return Ok(());
+        }
+        
+        let convert = |text: &str| match (private, is_sealed(text)) {
+            (true, false) => sealer.seal(text),
+            (false, true) => sealer.open(text),
+            _ => Ok(text.to_string()),
+        };
+        let title = convert(&title)?;
+        let description = description.as_deref().map(convert).transpose()?;
+        sqlx::query("UPDATE tasks SET title = ?, description = ? WHERE id = ?")
+            .bind(&title)
+            .bind(&description)
+            .bind(id)
+            .execute(&mut **tx)
+            .await?;
+        
+        let items: Vec<(i64, String)> = sqlx::query_as("SELECT id, title FROM checklist_items WHERE task_id = ?")
+            .bind(id)
+            .fetch_all(&mut **tx)
+            .await?;
+        for (item_id, item_title) in items {
+            sqlx::query("UPDATE checklist_items SET title = ? WHERE id = ?")
+                .bind(convert(&item_title)?)
+                .bind(item_id)
+                .execute(&mut **tx)
+                .await?;
+        }
+        
+        let attachments: Vec<(i64, String, Option<String>)> =
+            sqlx::query_as("SELECT id, target, label FROM task_attachments WHERE task_id = ?")
+                .bind(id)
+                .fetch_all(&mut **tx)
+                .await?;
+        for (attachment_id, target, label) in attachments {
+            sqlx::query("UPDATE task_attachments SET target = ?, label = ? WHERE id = ?")
+                .bind(convert(&target)?)
+                .bind(label.as_deref().map(convert).transpose()?)
+                .bind(attachment_id)
+                .execute(&mut **tx)
+                .await?;
+        }
+        
+        let values: Vec<(i64, String)> = sqlx::query_as("SELECT field_id, value FROM task_field_values WHERE task_id = ?")
+            .bind(id)
+            .fetch_all(&mut **tx)
+            .await?;
+        for (field_id, value) in values {
+            sqlx::query("UPDATE task_field_values SET value = ? WHERE task_id = ? AND field_id = ?")
+                .bind(convert(&value)?)
+                .bind(id)
+                .bind(field_id)
+                .execute(&mut **tx)
+                .await?;
+        }
+        
+        // Encrypted descriptions hold no references, so a sealed task has no links
+        Self::sync_links(tx, id, description.as_deref()).await?;
+        Self::resolve_dangling_links(tx, id, &title).await?;
+        
+        Ok(())
+    }
+
+    /// Sets the privacy flag of a restored category or task, whose content
+    /// is already in the stored form.
+    pub(crate) async fn restore_private_flag(&self, table: &str, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query(&format!("UPDATE {} SET is_private = TRUE WHERE id = ?", table))
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+
+    // Focus session operations
+    pub async fn record_focus_session(&self, session: NewFocusSession) -> Result<FocusSession, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT INTO focus_sessions (task_id, kind, started_at, ended_at, planned_seconds, actual_seconds, completed) 
+             VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+        .bind(session.task_id)
+        .bind(&session.kind)
+        .bind(&session.started_at)
+        .bind(&session.ended_at)
+        .bind(session.planned_seconds)
+        .bind(session.actual_seconds)
+        .bind(session.completed)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(FocusSession {
+            id: result.last_insert_rowid(),
+            task_id: session.task_id,
+            kind: session.kind,
+            started_at: session.started_at,
+            ended_at: session.ended_at,
+            planned_seconds: session.planned_seconds,
+            actual_seconds: session.actual_seconds,
+            completed: session.completed,
+        })
+    }
+
+    pub async fn get_focus_sessions(&self, task_id: Option<i64>) -> Result<Vec<FocusSession>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT id, task_id, kind, started_at, ended_at, planned_seconds, actual_seconds, completed 
+             FROM focus_sessions WHERE ? IS NULL OR task_id = ? ORDER BY started_at DESC"
+        )
+        .bind(task_id)
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|row| FocusSession {
+            id: row.get("id"),
+            task_id: row.get("task_id"),
+            kind: row.get("kind"),
+            started_at: row.get("started_at"),
+            ended_at: row.get("ended_at"),
+            planned_seconds: row.get("planned_seconds"),
+            actual_seconds: row.get("actual_seconds"),
+            completed: row.get::<i32, _>("completed") != 0,
+        }).collect())
+    }
+
+    // Settings operations
+    pub async fn get_setting(&self, key: &str) -> Result<Option<String>, sqlx::Error> {
+        let row = sqlx::query("SELECT value FROM app_settings WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(row.map(|r| r.get("value")))
+    }
+
+    pub async fn set_setting(&self, key: &str, value: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO app_settings (key, value, updated_at) VALUES (?, ?, ?)"
+        )
+        .bind(key)
+        .bind(value)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+
+    pub async fn get_all_settings(&self) -> Result<Vec<AppSetting>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT key, value, updated_at FROM app_settings ORDER BY key"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| AppSetting {
+            key: row.get("key"),
+            value: row.get("value"),
+            updated_at: row.get("updated_at"),
+        }).collect())
+    }
+
+    pub async fn clear_all_data(&self) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        
+        // Delete in correct order due to foreign key constraints
+        sqlx::query("DELETE FROM task_tags").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM focus_sessions").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM checklist_items").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM task_attachments").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM task_links").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM task_field_values").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM custom_fields").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM daily_plan_items").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM daily_plans").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM task_templates").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM tasks").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM categories WHERE name != 'General'").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM app_settings").execute(&mut *tx).await?;
+        
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Maintenance operations
+    /// Refreshes the query planner's statistics, gives free pages back to
+    /// the file system and folds the write-ahead log into the file.
+    pub async fn run_maintenance(&self) -> Result<(), sqlx::Error> {
+        let mut connection = self.pool.acquire().await?;
+        
+        // Files created before incremental vacuum was turned on need one
+        // full vacuum to switch over
+        let auto_vacuum: i64 = sqlx::query_scalar("PRAGMA auto_vacuum")
+            .fetch_one(&mut *connection)
+            .await?;
+        if auto_vacuum != 2 {
+            sqlx::query("PRAGMA auto_vacuum = INCREMENTAL").execute(&mut *connection).await?;
+            sqlx::query("VACUUM").execute(&mut *connection).await?;
+        }
+        
+        sqlx::query("PRAGMA incremental_vacuum").execute(&mut *connection).await?;
+        sqlx::query("PRAGMA optimize").execute(&mut *connection).await?;
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&mut *connection).await?;
+        
+        Ok(())
+    }
+
+    pub async fn size(&self) -> Result<DatabaseSize, sqlx::Error> {
+        let pragma = |name: &str| {
+            let query = format!("PRAGMA {}", name);
+            async move { sqlx::query_scalar::<_, i64>(&query).fetch_one(&self.pool).await }
+        };
+        let page_size = pragma("page_size").await? as u64;
+        let page_count = pragma("page_count").await? as u64;
+        let free_pages = pragma("freelist_count").await? as u64;
+        
+        let file_size = |suffix: &str| {
+            self.path()
+                .and_then(|path| std::fs::metadata(sidecar_path(path, suffix)).ok())
+                .map(|metadata| metadata.len())
+        };
+        
+        Ok(DatabaseSize {
+            file_bytes: file_size("").unwrap_or(page_size * page_count),
+            wal_bytes: file_size("-wal").unwrap_or(0),
+            page_size,
+            page_count,
+            free_pages,
+            reclaimable_bytes: free_pages * page_size,
+        })
+    }
+
+    // Integrity operations
+    /// Runs SQLite's own checks and the app's invariants without changing
+    /// anything.
+    pub async fn check_integrity(&self) -> Result<IntegrityReport, sqlx::Error> {
+        let mut issues = Vec::new();
+        
+        let damage: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+            .fetch_all(&self.pool)
+            .await?;
+        let file_ok = damage.len() == 1 && damage[0] == "ok";
+        if !file_ok {
+            issues.extend(damage.into_iter().map(|message| IntegrityIssue {
+                kind: IntegrityIssueKind::Corruption,
+                table: String::new(),
+                row_id: None,
+                message,
+                repairable: false,
+            }));
+        }
+        
+        for invariant in INVARIANTS {
+            let ids: Vec<Option<i64>> = sqlx::query_scalar(&format!(
+                "SELECT {} FROM {} WHERE {}",
+                invariant.id, invariant.table, invariant.condition
+            ))
+            .fetch_all(&self.pool)
+            .await?;
+            issues.extend(ids.into_iter().map(|id| IntegrityIssue {
+                kind: invariant.kind,
+                table: invariant.table.to_string(),
+                row_id: id,
+                message: invariant.message.to_string(),
+                repairable: true,
+            }));
+        }
+        
+        let rows = sqlx::query("SELECT id, created_at, updated_at, due_date, completed_at, archived_at FROM tasks")
+            .fetch_all(&self.pool)
+            .await?;
+        for row in &rows {
+            for (column, _) in TASK_DATE_COLUMNS {
+                if let Some(value) = row.get::<Option<String>, _>(column) {
+                    if parse_timestamp(&value).is_none() {
+                        issues.push(IntegrityIssue {
+                            kind: IntegrityIssueKind::InvalidDate,
+                            table: "tasks".to_string(),
+                            row_id: Some(row.get("id")),
+                            message: format!("Unreadable {} '{}'", column, value),
+                            repairable: true,
+                        });
+                    }
+                }
+            }
+        }
+        
+        // Violations in tables with an invariant are already reported above,
+        // in more detail
+        let violations = sqlx::query("PRAGMA foreign_key_check")
+            .fetch_all(&self.pool)
+            .await?;
+        for violation in &violations {
+            let table: String = violation.get(0);
+            if INVARIANTS.iter().any(|i| i.table == table) {
+                continue;
+            }
+            let parent: String = violation.get(2);
+            issues.push(IntegrityIssue {
+                kind: IntegrityIssueKind::ForeignKey,
+                message: format!("Refers to a missing row in {}", parent),
+                table,
+                row_id: violation.get(1),
+                repairable: false,
+            });
+        }
+        
+        Ok(IntegrityReport { file_ok, issues })
+    }
+
+    /// Applies the safe fixes for everything `check_integrity` reports as
+    /// repairable, in one transaction, and returns how many rows changed.
+    /// A damaged file is left alone, since writing to it can make it worse.
+    pub async fn repair_integrity(&self) -> Result<u64, sqlx::Error> {
+        if !self.check_integrity().await?.file_ok {
+            return Err(sqlx::Error::Configuration(
+                "The database file is damaged; restore a backup instead".into(),
+            ));
+        }
+        
+        let mut tx = self.pool.begin().await?;
+        let mut repaired = 0;
+        
+        for invariant in INVARIANTS {
+            repaired += sqlx::query(&format!("{} WHERE {}", invariant.fix, invariant.condition))
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+        
+        let now = chrono::Utc::now().to_rfc3339();
+        let rows = sqlx::query("SELECT id, created_at, updated_at, due_date, completed_at, archived_at FROM tasks")
+            .fetch_all(&mut *tx)
+            .await?;
+        for row in &rows {
+            for (column, clear) in TASK_DATE_COLUMNS {
+                let Some(value) = row.get::<Option<String>, _>(column) else {
+                    continue;
+                };
+                if parse_timestamp(&value).is_some() {
+                    continue;
+                }
+                repaired += sqlx::query(&format!("UPDATE tasks SET {} = ? WHERE id = ?", column))
+                    .bind((!clear).then(|| now.clone()))
+                    .bind(row.get::<i64, _>("id"))
+                    .execute(&mut *tx)
+                    .await?
+                    .rows_affected();
+            }
+        }
+        
+        tx.commit().await?;
+        Ok(repaired)
+    }
+}
@@ -0,0 +1,174 @@
+use std::path::{Path, PathBuf};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use serde::{Deserialize, Serialize};
+use crate::error::AppError;
+use crate::models::ModelValidationError;
+
+/// Marks text encrypted with the content key of private tasks. Task titles
+/// may not start with it, so that only encrypted titles do.
+pub const SEALED_PREFIX: &str = "enc:v1:";
+
+pub fn is_sealed(text: &str) -> bool {
+    text.starts_with(SEALED_PREFIX)
+}
+
+/// Encrypts and decrypts the content of private tasks. The content key
+/// stays with the implementor; the database only asks it to convert text
+/// while switching a task between the two forms.
+pub trait Sealer: Send + Sync {
+    fn seal(&self, plaintext: &str) -> Result<String, AppError>;
+
+    /// Plaintext of `text`, which is returned as is when it is not sealed.
+    fn open(&self, text: &str) -> Result<String, AppError>;
+}
+
+/// Passphrases shorter than this are refused when encryption is enabled
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+const KEY_LENGTH: usize = 32;
+const SALT_LENGTH: usize = 16;
+
+/// How the key of an encrypted database is derived from its passphrase.
+/// Stored in plaintext next to the database (`yuflow.db.keyinfo`), since it
+/// is needed before the database can be read; it holds nothing secret.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct KeyInfo {
+    pub kdf: String,
+    pub salt: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl KeyInfo {
+    /// Fresh parameters with a random salt.
+    pub fn generate() -> Self {
+        let mut salt = [0u8; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+
+        KeyInfo {
+            kdf: "argon2id".to_string(),
+            salt: to_hex(&salt),
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+
+    pub fn path(database_path: &Path) -> PathBuf {
+        let mut path = database_path.as_os_str().to_owned();
+        path.push(".keyinfo");
+        PathBuf::from(path)
+    }
+
+    /// Key parameters of the database at `database_path`, or `None` when it
+    /// is not encrypted.
+    pub fn load(database_path: &Path) -> std::io::Result<Option<Self>> {
+        let path = Self::path(database_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let info = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        Ok(Some(info))
+    }
+
+    pub fn save(&self, database_path: &Path) -> std::io::Result<()> {
+        let path = Self::path(database_path);
+        let temp_path = path.with_extension("keyinfo.tmp");
+        std::fs::write(&temp_path, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(temp_path, path)
+    }
+
+    pub fn remove(database_path: &Path) -> std::io::Result<()> {
+        let path = Self::path(database_path);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    pub fn derive(&self, passphrase: &str) -> Result<DerivedKey, String> {
+        if self.kdf != "argon2id" {
+            return Err(format!("Unsupported key derivation '{}'", self.kdf));
+        }
+        let salt = from_hex(&self.salt).ok_or("Invalid salt in key parameters")?;
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(KEY_LENGTH))
+            .map_err(|e| format!("Invalid key parameters: {}", e))?;
+
+        let mut key = [0u8; KEY_LENGTH];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| format!("Failed to derive key: {}", e))?;
+
+        Ok(DerivedKey(key))
+    }
+}
+
+/// Key derived from a passphrase. The passphrase itself is never kept.
+#[derive(Clone)]
+pub struct DerivedKey([u8; KEY_LENGTH]);
+
+impl DerivedKey {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// The key in SQLCipher's raw key syntax, ready for `PRAGMA key` or
+    /// `ATTACH ... KEY`, so SQLCipher skips its own derivation.
+    pub fn pragma_value(&self) -> String {
+        format!("\"x'{}'\"", to_hex(&self.0))
+    }
+}
+
+impl std::fmt::Debug for DerivedKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DerivedKey(..)")
+    }
+}
+
+impl Drop for DerivedKey {
+    fn drop(&mut self) {
+        self.0.fill(0);
+    }
+}
+
+pub fn check_passphrase(passphrase: &str) -> Result<(), ModelValidationError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+        return Err(ModelValidationError::TooShort {
+            field: "passphrase".to_string(),
+            min: MIN_PASSPHRASE_LENGTH,
+        });
+    }
+    Ok(())
+}
+
+/// Argon2id hash of a passphrase in PHC string format, for verification only.
+pub fn hash_passphrase(passphrase: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(passphrase.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| format!("Failed to hash passphrase: {}", e))
+}
+
+pub fn verify_passphrase(passphrase: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|hash| Argon2::default().verify_password(passphrase.as_bytes(), &hash).is_ok())
+        .unwrap_or(false)
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
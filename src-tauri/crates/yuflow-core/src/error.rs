@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use thiserror::Error;
+use crate::models::ModelValidationError;
+use crate::validation::Violations;
+
+/// Error returned by every command. It reaches the frontend as
+/// `{ code, message, fields }`, where `code` tells the kinds apart and
+/// `fields` maps each invalid input field to its messages.
+#[derive(Error, Debug)]
+pub enum AppError {
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{message}")]
+    Validation {
+        message: String,
+        fields: BTreeMap<String, Vec<String>>,
+    },
+    /// The change clashes with existing data, such as a duplicate name
+    #[error("{0}")]
+    Conflict(String),
+    /// The app or the private content is locked
+    #[error("{0}")]
+    Locked(String),
+    #[error("{0}")]
+    Storage(String),
+    #[error("{0}")]
+    Io(String),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound(_) => "not_found",
+            AppError::Validation { .. } => "validation",
+            AppError::Conflict(_) => "conflict",
+            AppError::Locked(_) => "locked",
+            AppError::Storage(_) => "storage",
+            AppError::Io(_) => "io",
+        }
+    }
+
+    /// A validation error that is not about one field.
+    pub fn validation(message: impl Into<String>) -> Self {
+        AppError::Validation {
+            message: message.into(),
+            fields: BTreeMap::new(),
+        }
+    }
+
+    /// A validation error for a single input field.
+    pub fn invalid_field(field: &str, message: impl Into<String>) -> Self {
+        let message = message.into();
+        AppError::Validation {
+            fields: BTreeMap::from([(field.to_string(), vec![message.clone()])]),
+            message,
+        }
+    }
+
+    /// Prefixes the message with what was being done, as in "Failed to get
+    /// tasks: disk I/O error". Errors the user can act on keep their message.
+    pub fn context(self, what: &str) -> Self {
+        match self {
+            AppError::Conflict(message) => AppError::Conflict(format!("{}: {}", what, message)),
+            AppError::Storage(message) => AppError::Storage(format!("{}: {}", what, message)),
+            AppError::Io(message) => AppError::Io(format!("{}: {}", what, message)),
+            error => error,
+        }
+    }
+}
+
+/// Adds context to the error of a result while converting it to `AppError`.
+pub trait Context<T> {
+    fn context(self, what: &str) -> Result<T, AppError>;
+}
+
+impl<T, E: Into<AppError>> Context<T> for Result<T, E> {
+    fn context(self, what: &str) -> Result<T, AppError> {
+        self.map_err(|e| e.into().context(what))
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let empty = BTreeMap::new();
+        let fields = match self {
+            AppError::Validation { fields, .. } => fields,
+            _ => &empty,
+        };
+
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("fields", fields)?;
+        state.end()
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(error: sqlx::Error) -> Self {
+        match error {
+            sqlx::Error::RowNotFound => AppError::NotFound("Not found".to_string()),
+            sqlx::Error::Io(e) => e.into(),
+            sqlx::Error::Database(e) if matches!(
+                e.kind(),
+                sqlx::error::ErrorKind::UniqueViolation | sqlx::error::ErrorKind::ForeignKeyViolation
+            ) => AppError::Conflict(e.to_string()),
+            error => AppError::Storage(error.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::NotFound => AppError::NotFound(error.to_string()),
+            // Another instance holds the database lock
+            std::io::ErrorKind::WouldBlock => AppError::Conflict(error.to_string()),
+            _ => AppError::Io(error.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        AppError::Storage(error.to_string())
+    }
+}
+
+impl From<validator::ValidationErrors> for AppError {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        let message = errors.to_string();
+        match Violations::from(errors).into_result() {
+            Err(error) => error,
+            // Only nested structs were invalid
+            Ok(()) => AppError::validation(message),
+        }
+    }
+}
+
+impl From<ModelValidationError> for AppError {
+    fn from(error: ModelValidationError) -> Self {
+        match &error {
+            ModelValidationError::Invalid(_) => AppError::validation(error.to_string()),
+            ModelValidationError::Required { field }
+            | ModelValidationError::TooShort { field, .. }
+            | ModelValidationError::TooLong { field, .. } => AppError::invalid_field(field, error.to_string()),
+        }
+    }
+}
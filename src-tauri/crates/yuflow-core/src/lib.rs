@@ -0,0 +1,39 @@
+//! Yuflow's data model and storage, without any Tauri dependency. The app
+//! wraps it in commands; tools can open a database file directly:
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), yuflow_core::AppError> {
+//! use yuflow_core::{Database, TaskStore};
+//!
+//! let db = Database::open(std::path::Path::new("yuflow.db")).await?;
+//! for task in db.get_tasks(None).await? {
+//!     println!("{} {}", task.id, task.title);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod backup;
+pub mod custom_fields;
+pub mod database;
+pub mod encryption;
+pub mod error;
+pub mod links;
+pub mod lock;
+pub mod memory_store;
+pub mod models;
+pub mod planning;
+pub mod schema;
+pub mod statistics;
+pub mod store;
+pub mod templates;
+pub mod validation;
+
+#[cfg(test)]
+mod tests;
+
+pub use backup::BackupManager;
+pub use database::Database;
+pub use error::{AppError, Context};
+pub use memory_store::MemoryStore;
+pub use store::TaskStore;
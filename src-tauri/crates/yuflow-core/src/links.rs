@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// A reference to another task found in a description.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reference {
+    /// `[[Task title]]`
+    Title(String),
+    /// `#123`
+    Id(i64),
+}
+
+impl Reference {
+    /// Kind and reference text as stored in `task_links`.
+    pub fn parts(&self) -> (&'static str, String) {
+        match self {
+            Reference::Title(title) => ("title", title.clone()),
+            Reference::Id(id) => ("id", id.to_string()),
+        }
+    }
+}
+
+/// Finds all `[[Task title]]` and `#123` references in `text`, with their
+/// byte ranges. `#` only starts a reference when it is not part of a word
+/// and is followed by digits alone, so `#tag` and `C#7` are left alone.
+pub fn find_references(text: &str) -> Vec<(Range<usize>, Reference)> {
+    let bytes = text.as_bytes();
+    let mut found = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"[[") {
+            if let Some(end) = text[i + 2..].find("]]") {
+                let inner = &text[i + 2..i + 2 + end];
+                let title = inner.trim();
+                if !title.is_empty() && !inner.contains(['[', ']', '\n']) {
+                    found.push((i..i + end + 4, Reference::Title(title.to_string())));
+                    i += end + 4;
+                    continue;
+                }
+            }
+        } else if bytes[i] == b'#' && (i == 0 || !is_word_byte(bytes[i - 1])) {
+            let digits = bytes[i + 1..].iter().take_while(|b| b.is_ascii_digit()).count();
+            let end = i + 1 + digits;
+            if digits > 0 && (end == bytes.len() || !is_word_byte(bytes[end])) {
+                if let Ok(id) = text[i + 1..end].parse() {
+                    found.push((i..end, Reference::Id(id)));
+                    i = end;
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+
+    found
+}
+
+// Non-ASCII bytes count as word characters so that `#` inside a word in
+// any script does not start a reference
+fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80
+}
+
+/// Replaces every reference for which `replace` returns new markup.
+pub fn rewrite(text: &str, mut replace: impl FnMut(&Reference) -> Option<String>) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for (range, reference) in find_references(text) {
+        if let Some(markup) = replace(&reference) {
+            output.push_str(&text[last..range.start]);
+            output.push_str(&markup);
+            last = range.end;
+        }
+    }
+
+    output.push_str(&text[last..]);
+    output
+}
+
+/// Points `[[old]]` references at a renamed task.
+pub fn rename_title(text: &str, old: &str, new: &str) -> String {
+    rewrite(text, |reference| match reference {
+        Reference::Title(title) if title.eq_ignore_ascii_case(old.trim()) => Some(format!("[[{}]]", new.trim())),
+        _ => None,
+    })
+}
+
+/// Rewrites `#id` references using a map of old to new task IDs.
+pub fn remap_ids(text: &str, ids: &HashMap<i64, i64>) -> String {
+    rewrite(text, |reference| match reference {
+        Reference::Id(id) => ids.get(id).map(|new_id| format!("#{}", new_id)),
+        _ => None,
+    })
+}
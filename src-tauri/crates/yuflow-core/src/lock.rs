@@ -0,0 +1,49 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Exclusive lock on a database file, held for as long as the database is
+/// open. The operating system drops it when the process exits, so a crash
+/// never leaves the file locked.
+#[derive(Debug)]
+pub struct DatabaseLock {
+    path: PathBuf,
+    _file: File,
+}
+
+impl DatabaseLock {
+    pub fn acquire(database_path: &Path) -> std::io::Result<Self> {
+        let mut lock_path = database_path.as_os_str().to_owned();
+        lock_path.push(".lock");
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::WouldBlock,
+                    format!("{} is already in use by another instance of Yuflow", database_path.display()),
+                ));
+            }
+            Err(TryLockError::Error(e)) => return Err(e),
+        }
+
+        // Purely informational, to tell which process holds the lock
+        file.set_len(0)?;
+        writeln!(file, "{}", std::process::id())?;
+
+        Ok(DatabaseLock {
+            path: database_path.to_path_buf(),
+            _file: file,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
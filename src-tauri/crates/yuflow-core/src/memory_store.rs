@@ -0,0 +1,375 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use async_trait::async_trait;
+use crate::models::*;
+use crate::error::AppError;
+use crate::store::TaskStore;
+use crate::validation::Violations;
+
+// The rows a new database is seeded with by the migrations
+const DEFAULT_CATEGORY: (i64, &str, &str) = (1, "General", "#F97316");
+const DEFAULT_PRIORITY: &str = "medium";
+const PRIORITIES: [(&str, i64); 3] = [("low", 10), ("medium", 20), ("high", 30)];
+/// Workflow statuses by ID, in position order, and whether they mean done
+const STATUSES: [(i64, bool); 4] = [(1, false), (2, false), (3, false), (4, true)];
+
+#[derive(Default)]
+struct State {
+    tasks: BTreeMap<i64, Task>,
+    categories: BTreeMap<i64, Category>,
+    next_task_id: i64,
+    next_category_id: i64,
+}
+
+/// Keeps tasks and categories in memory, for tests that don't need SQLite.
+/// It starts out like a new database; links, custom field values and
+/// everything else outside `TaskStore` are not modelled.
+pub struct MemoryStore {
+    state: Mutex<State>,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        let (id, name, color) = DEFAULT_CATEGORY;
+        let mut state = State {
+            next_task_id: 1,
+            next_category_id: id + 1,
+            ..State::default()
+        };
+        state.categories.insert(id, Category {
+            id,
+            name: name.to_string(),
+            color: color.to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            is_private: false,
+        });
+
+        MemoryStore { state: Mutex::new(state) }
+    }
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn priority_weight(name: &str) -> i64 {
+    PRIORITIES.iter().find(|(priority, _)| *priority == name).map(|(_, weight)| *weight).unwrap_or(0)
+}
+
+/// Priority names ignore case, as the `COLLATE NOCASE` column does; this
+/// is the name as stored.
+fn canonical_priority(name: &str) -> Option<&'static str> {
+    PRIORITIES.iter().map(|(priority, _)| *priority).find(|priority| priority.eq_ignore_ascii_case(name))
+}
+
+/// The named priority, or the default one when there is no such priority.
+fn resolve_priority(name: Option<&str>) -> String {
+    name.and_then(canonical_priority).unwrap_or(DEFAULT_PRIORITY).to_string()
+}
+
+/// Same checks as `Database::check_category_and_priority`.
+fn check_category_and_priority(state: &State, category_id: Option<i64>, priority: Option<&str>) -> Violations {
+    let mut violations = Violations::default();
+    if let Some(category_id) = category_id.filter(|id| !state.categories.contains_key(id)) {
+        violations.add("category_id", format!("Category {} does not exist", category_id));
+    }
+    if priority.is_some_and(|name| canonical_priority(name).is_none()) {
+        let names: Vec<&str> = PRIORITIES.iter().rev().map(|(p, _)| *p).collect();
+        violations.add("priority", format!("Priority must be one of: {}", names.join(", ")));
+    }
+    violations
+}
+
+/// First status of the given kind.
+fn default_status(done: bool) -> i64 {
+    STATUSES.iter().find(|(_, is_done)| *is_done == done).map(|(id, _)| *id).unwrap_or(1)
+}
+
+/// The task and all of its descendants.
+fn subtree(state: &State, id: i64) -> Vec<i64> {
+    let mut subtree = vec![id];
+    let mut index = 0;
+    while index < subtree.len() {
+        let parent = subtree[index];
+        subtree.extend(state.tasks.values().filter(|task| task.parent_id == Some(parent)).map(|task| task.id));
+        index += 1;
+    }
+    subtree
+}
+
+/// Same bounds as the `julianday` comparisons in `Database::get_tasks`.
+fn due_within(task: &Task, filters: &TaskFilters) -> bool {
+    let instant = |date: Option<&str>| date.and_then(|date| chrono::DateTime::parse_from_rfc3339(date).ok());
+    let after = instant(filters.due_date_after.as_deref());
+    let before = instant(filters.due_date_before.as_deref());
+    if after.is_none() && before.is_none() {
+        return true;
+    }
+    let Some(due) = instant(task.due_date.as_deref()) else {
+        return false;
+    };
+    after.is_none_or(|after| due >= after) && before.is_none_or(|before| due <= before)
+}
+
+fn matches(task: &Task, filters: &TaskFilters) -> bool {
+    let search = filters.search.as_ref().map(|search| search.to_lowercase());
+    let contains = |text: &str| search.as_ref().is_none_or(|search| text.to_lowercase().contains(search));
+
+    filters.completed.is_none_or(|completed| task.completed == completed)
+        && filters.priority.as_ref().is_none_or(|priority| task.priority.eq_ignore_ascii_case(priority))
+        && filters.category_id.is_none_or(|id| task.category_id == Some(id))
+        && filters.status_id.is_none_or(|id| task.status_id == Some(id))
+        && filters.parent_id.is_none_or(|id| task.parent_id == Some(id))
+        && due_within(task, filters)
+        && (contains(&task.title) || task.description.as_deref().is_some_and(contains))
+        && filters.custom_fields.iter().flatten().all(|filter| field_matches(task, filter))
+}
+
+fn compare_values(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+fn field_matches(task: &Task, filter: &CustomFieldFilter) -> bool {
+    let Some(value) = task.custom_fields.get(&filter.field_id) else {
+        return false;
+    };
+    filter.equals.as_ref().is_none_or(|equals| compare_values(value, equals).is_eq())
+        && filter.min.as_ref().is_none_or(|min| compare_values(value, min).is_ge())
+        && filter.max.as_ref().is_none_or(|max| compare_values(value, max).is_le())
+}
+
+// Same order as the SQL in `database::task_order`
+fn sort_tasks(tasks: &mut [Task], sort_by: Option<TaskSortKey>, descending: bool) {
+    let newest_first = |a: &Task, b: &Task| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id));
+    // Missing values come last in either direction
+    let last_if_missing = |a: Option<&String>, b: Option<&String>, compare: fn(&str, &str) -> Ordering| match (a, b) {
+        (Some(a), Some(b)) => {
+            let ordering = compare(a, b);
+            if descending { ordering.reverse() } else { ordering }
+        }
+        (a, b) => a.is_none().cmp(&b.is_none()),
+    };
+    let directed = |ordering: Ordering| if descending { ordering.reverse() } else { ordering };
+
+    tasks.sort_by(|a, b| {
+        let ordering = match sort_by {
+            None | Some(TaskSortKey::Urgency) => Ordering::Equal,
+            Some(TaskSortKey::CreatedAt) => directed(a.created_at.cmp(&b.created_at)),
+            Some(TaskSortKey::UpdatedAt) => directed(a.updated_at.cmp(&b.updated_at)),
+            Some(TaskSortKey::DueDate) => last_if_missing(a.due_date.as_ref(), b.due_date.as_ref(), |a, b| a.cmp(b)),
+            Some(TaskSortKey::Priority) => directed(priority_weight(&a.priority).cmp(&priority_weight(&b.priority))),
+            Some(TaskSortKey::Title) => directed(a.title.to_lowercase().cmp(&b.title.to_lowercase())),
+            Some(TaskSortKey::CustomField(field_id)) => {
+                last_if_missing(a.custom_fields.get(&field_id), b.custom_fields.get(&field_id), compare_values)
+            }
+        };
+        ordering.then_with(|| newest_first(a, b))
+    });
+}
+
+#[async_trait]
+impl TaskStore for MemoryStore {
+    async fn get_tasks(&self, filters: Option<TaskFilters>) -> Result<Vec<Task>, AppError> {
+        let filters = filters.unwrap_or_default();
+        // Archived tasks stay reachable through search
+        let include_archived = filters.include_archived.unwrap_or(filters.search.is_some());
+
+        let mut tasks: Vec<Task> = self.state.lock().unwrap()
+            .tasks
+            .values()
+            .filter(|task| include_archived || task.archived_at.is_none())
+            .filter(|task| matches(task, &filters))
+            .cloned()
+            .collect();
+        sort_tasks(&mut tasks, filters.sort_by, filters.sort_descending.unwrap_or(false));
+
+        Ok(tasks)
+    }
+
+    async fn get_task(&self, id: i64) -> Result<Option<Task>, AppError> {
+        Ok(self.state.lock().unwrap().tasks.get(&id).cloned())
+    }
+
+    async fn create_task(&self, input: CreateTaskInput) -> Result<Task, AppError> {
+        let mut state = self.state.lock().unwrap();
+        let mut violations = check_category_and_priority(&state, input.category_id, input.priority.as_deref());
+        let status = input.status_id.map(|status_id| STATUSES.iter().find(|(id, _)| *id == status_id));
+        if let (Some(status_id), Some(None)) = (input.status_id, status) {
+            violations.add("status_id", format!("Status {} does not exist", status_id));
+        }
+        if let Some(parent_id) = input.parent_id.filter(|id| !state.tasks.contains_key(id)) {
+            violations.add("parent_id", format!("Task {} does not exist", parent_id));
+        }
+        violations.into_result()?;
+        let (status_id, done) = status.flatten().copied().unwrap_or((default_status(false), false));
+
+        // Tags are stored the way `Database::attach_tags` normalizes them
+        let mut tags: Vec<String> = Vec::new();
+        for tag in &input.tags {
+            let tag = tag.trim().to_lowercase();
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let id = state.next_task_id;
+        state.next_task_id += 1;
+
+        let task = Task {
+            id,
+            title: input.title,
+            description: input.description,
+            completed: done,
+            status_id: Some(status_id),
+            priority: resolve_priority(input.priority.as_deref()),
+            category_id: input.category_id,
+            due_date: input.due_date,
+            created_at: now.clone(),
+            updated_at: now.clone(),
+            completed_at: done.then_some(now),
+            archived_at: None,
+            parent_id: input.parent_id,
+            tags,
+            custom_fields: Default::default(),
+            is_private: false,
+            locked: false,
+        };
+        state.tasks.insert(id, task.clone());
+
+        Ok(task)
+    }
+
+    async fn update_task(&self, input: UpdateTaskInput) -> Result<TaskUpdate, AppError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut state = self.state.lock().unwrap();
+        let before = state.tasks
+            .get(&input.id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Task {} not found", input.id)))?;
+        // Same checks as `Database::check_update_references`
+        let mut violations =
+            check_category_and_priority(&state, input.category_id.value().copied(), input.priority.value().map(String::as_str));
+        let mut status_done = None;
+        if let Patch::Value(status_id) = input.status_id {
+            status_done = STATUSES.iter().find(|(id, _)| *id == status_id).map(|(_, done)| *done);
+            match (status_done, input.completed.value()) {
+                (None, _) => violations.add("status_id", format!("Status {} does not exist", status_id)),
+                (Some(done), Some(completed)) if done != *completed => violations.add(
+                    "status_id",
+                    format!("Status {} is {}, which contradicts completed", status_id, if done { "done" } else { "open" }),
+                ),
+                _ => {}
+            }
+        }
+        if let Patch::Value(parent_id) = input.parent_id {
+            if !state.tasks.contains_key(&parent_id) {
+                violations.add("parent_id", format!("Task {} does not exist", parent_id));
+            } else if subtree(&state, input.id).contains(&parent_id) {
+                violations.add("parent_id", "A task cannot be moved under itself or one of its subtasks");
+            }
+        }
+        violations.into_result()?;
+        let task = state.tasks.get_mut(&input.id).unwrap();
+        let mut provided = false;
+
+        if let Patch::Value(title) = input.title {
+            task.title = title;
+            provided = true;
+        }
+        if !input.description.is_absent() {
+            task.description = input.description.value().cloned();
+            provided = true;
+        }
+        if let Some(completed) = status_done.or(input.completed.value().copied()) {
+            task.completed = completed;
+            let fits = STATUSES.iter().any(|(id, done)| Some(*id) == task.status_id && *done == completed);
+            if let Patch::Value(status_id) = input.status_id {
+                task.status_id = Some(status_id);
+            } else if !fits {
+                task.status_id = Some(default_status(completed));
+            }
+            if completed {
+                task.completed_at.get_or_insert_with(|| now.clone());
+            } else {
+                task.completed_at = None;
+                task.archived_at = None;
+            }
+            provided = true;
+        }
+        if !input.priority.is_absent() {
+            task.priority = resolve_priority(input.priority.value().map(String::as_str));
+            provided = true;
+        }
+        if !input.category_id.is_absent() {
+            task.category_id = input.category_id.value().copied();
+            provided = true;
+        }
+        if !input.due_date.is_absent() {
+            task.due_date = input.due_date.value().cloned();
+            provided = true;
+        }
+        if !input.parent_id.is_absent() {
+            task.parent_id = input.parent_id.value().copied();
+            provided = true;
+        }
+        // Like the database, an update that provides nothing writes nothing
+        if provided {
+            task.updated_at = now;
+        }
+
+        Ok(TaskUpdate::between(&before, task.clone()))
+    }
+
+    async fn delete_task(&self, id: i64) -> Result<(), AppError> {
+        let mut state = self.state.lock().unwrap();
+
+        for id in subtree(&state, id) {
+            state.tasks.remove(&id);
+        }
+
+        Ok(())
+    }
+
+    async fn get_categories(&self) -> Result<Vec<Category>, AppError> {
+        let mut categories: Vec<Category> = self.state.lock().unwrap().categories.values().cloned().collect();
+        categories.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(categories)
+    }
+
+    async fn create_category(&self, input: CreateCategoryInput) -> Result<Category, AppError> {
+        let mut state = self.state.lock().unwrap();
+        if state.categories.values().any(|category| category.name == input.name) {
+            return Err(AppError::Conflict(format!("A category named '{}' already exists", input.name)));
+        }
+
+        let id = state.next_category_id;
+        state.next_category_id += 1;
+        let category = Category {
+            id,
+            name: input.name,
+            color: input.color.unwrap_or_else(|| DEFAULT_CATEGORY.2.to_string()),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            is_private: false,
+        };
+        state.categories.insert(id, category.clone());
+
+        Ok(category)
+    }
+
+    async fn delete_category(&self, id: i64) -> Result<(), AppError> {
+        let mut state = self.state.lock().unwrap();
+        for task in state.tasks.values_mut().filter(|task| task.category_id == Some(id)) {
+            task.category_id = None;
+        }
+        state.categories.remove(&id);
+
+        Ok(())
+    }
+}
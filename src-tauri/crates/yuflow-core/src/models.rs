@@ -0,0 +1,695 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use validator::{Validate, ValidationError};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ModelValidationError {
+    #[error("Validation failed: {0}")]
+    Invalid(String),
+    #[error("Field '{field}' is required")]
+    Required { field: String },
+    #[error("Field '{field}' must be at least {min} characters")]
+    TooShort { field: String, min: usize },
+    #[error("Field '{field}' exceeds maximum length of {max}")]
+    TooLong { field: String, max: usize },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Task {
+    pub id: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub completed: bool,
+    #[serde(default)]
+    pub status_id: Option<i64>,
+    pub priority: String,
+    pub category_id: Option<i64>,
+    pub due_date: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    #[serde(default)]
+    pub completed_at: Option<String>,
+    #[serde(default)]
+    pub archived_at: Option<String>,
+    #[serde(default)]
+    pub parent_id: Option<i64>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Custom field values by field ID, in their canonical text form
+    #[serde(default)]
+    pub custom_fields: BTreeMap<i64, String>,
+    #[serde(default)]
+    pub is_private: bool,
+    /// Title and description are encrypted and the key is not unlocked
+    #[serde(default)]
+    pub locked: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+pub struct CreateTaskInput {
+    #[validate(length(min = 1, max = 255, message = "Title must be between 1 and 255 characters"))]
+    #[validate(custom = "validate_title_text")]
+    pub title: String,
+    #[validate(length(max = 1000, message = "Description cannot exceed 1000 characters"))]
+    pub description: Option<String>,
+    /// Name of a priority; defaults to the default priority
+    #[validate(length(min = 1, max = 50, message = "Priority must be between 1 and 50 characters"))]
+    pub priority: Option<String>,
+    pub category_id: Option<i64>,
+    #[validate(custom = "validate_date_format")]
+    pub due_date: Option<String>,
+    #[serde(default)]
+    #[validate(custom = "validate_tags")]
+    pub tags: Vec<String>,
+    /// Defaults to the first open status
+    #[serde(default)]
+    pub status_id: Option<i64>,
+    /// Makes the new task a subtask of this task
+    #[serde(default)]
+    pub parent_id: Option<i64>,
+}
+
+/// One field of a partial update. A field left out of the JSON is `Absent`
+/// and keeps its value, `null` clears it and anything else replaces it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Patch<T> {
+    #[default]
+    Absent,
+    Null,
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Patch::Absent)
+    }
+
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Patch::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Whether applying the patch to `current` gives a different value.
+    pub fn differs_from(&self, current: Option<&T>) -> bool
+    where
+        T: PartialEq,
+    {
+        match self {
+            Patch::Absent => false,
+            Patch::Null => current.is_some(),
+            Patch::Value(value) => current != Some(value),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    // Only called for fields that are present; `#[serde(default)]` covers the rest
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(value) => Patch::Value(value),
+            None => Patch::Null,
+        })
+    }
+}
+
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Patch::Value(value) => value.serialize(serializer),
+            _ => serializer.serialize_none(),
+        }
+    }
+}
+
+/// Changes to a task. `description`, `category_id`, `due_date` and
+/// `parent_id` are cleared with `null`, and a `null` priority goes back to
+/// the default one. A new status sets `completed` to match it.
+#[derive(Debug, Serialize, Deserialize, Validate, Default)]
+pub struct UpdateTaskInput {
+    pub id: i64,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[validate(custom = "validate_title_patch")]
+    pub title: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[validate(custom = "validate_description_patch")]
+    pub description: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[validate(custom = "validate_completed_patch")]
+    pub completed: Patch<bool>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[validate(custom = "validate_priority_patch")]
+    pub priority: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    pub category_id: Patch<i64>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[validate(custom = "validate_due_date_patch")]
+    pub due_date: Patch<String>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    #[validate(custom = "validate_status_patch")]
+    pub status_id: Patch<i64>,
+    #[serde(default, skip_serializing_if = "Patch::is_absent")]
+    pub parent_id: Patch<i64>,
+}
+
+/// A field whose value an update changed.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+/// Result of `update_task`: the task as it is now and what changed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskUpdate {
+    pub task: Task,
+    /// Empty when every provided value was already current
+    pub changes: Vec<FieldChange>,
+}
+
+impl TaskUpdate {
+    /// Compares the fields an update can touch, directly or through the
+    /// completion status; `updated_at` is left out.
+    pub fn between(before: &Task, after: Task) -> Self {
+        let mut changes = Vec::new();
+        let mut compare = |field: &str, before: serde_json::Value, after: serde_json::Value| {
+            if before != after {
+                changes.push(FieldChange { field: field.to_string(), before, after });
+            }
+        };
+        compare("title", before.title.as_str().into(), after.title.as_str().into());
+        compare("description", before.description.as_deref().into(), after.description.as_deref().into());
+        compare("completed", before.completed.into(), after.completed.into());
+        compare("status_id", before.status_id.into(), after.status_id.into());
+        compare("priority", before.priority.as_str().into(), after.priority.as_str().into());
+        compare("category_id", before.category_id.into(), after.category_id.into());
+        compare("due_date", before.due_date.as_deref().into(), after.due_date.as_deref().into());
+        compare("parent_id", before.parent_id.into(), after.parent_id.into());
+        compare("completed_at", before.completed_at.as_deref().into(), after.completed_at.as_deref().into());
+        compare("archived_at", before.archived_at.as_deref().into(), after.archived_at.as_deref().into());
+
+        TaskUpdate { task: after, changes }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Category {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+    pub created_at: String,
+    /// Every task in the category is private
+    #[serde(default)]
+    pub is_private: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateCategoryInput {
+    #[validate(length(min = 1, max = 100, message = "Category name must be between 1 and 100 characters"))]
+    pub name: String,
+    #[validate(custom = "validate_color")]
+    pub color: Option<String>,
+}
+
+/// A level on the priority scale. Tasks sort by `weight`, highest first
+/// when sorting descending.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Priority {
+    pub id: i64,
+    pub name: String,
+    pub weight: i64,
+    pub color: String,
+    pub icon: Option<String>,
+    /// Given to new tasks that do not name a priority
+    pub is_default: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreatePriorityInput {
+    #[validate(length(min = 1, max = 50, message = "Priority name must be between 1 and 50 characters"))]
+    pub name: String,
+    pub weight: i64,
+    #[validate(custom = "validate_color")]
+    pub color: Option<String>,
+    #[validate(length(max = 50, message = "Icon cannot exceed 50 characters"))]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub is_default: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdatePriorityInput {
+    pub id: i64,
+    #[validate(length(min = 1, max = 50, message = "Priority name must be between 1 and 50 characters"))]
+    pub name: Option<String>,
+    pub weight: Option<i64>,
+    #[validate(custom = "validate_color")]
+    pub color: Option<String>,
+    #[validate(length(max = 50, message = "Icon cannot exceed 50 characters"))]
+    pub icon: Option<String>,
+    /// Only `true` has an effect; another priority has to become the default
+    pub is_default: Option<bool>,
+}
+
+/// A workflow status (board column). `completed` on tasks is derived from
+/// the `is_done` flag of their status.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskStatus {
+    pub id: i64,
+    pub name: String,
+    pub color: String,
+    pub position: i64,
+    pub is_done: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateStatusInput {
+    #[validate(length(min = 1, max = 50, message = "Status name must be between 1 and 50 characters"))]
+    pub name: String,
+    #[validate(custom = "validate_color")]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub is_done: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateStatusInput {
+    pub id: i64,
+    #[validate(length(min = 1, max = 50, message = "Status name must be between 1 and 50 characters"))]
+    pub name: Option<String>,
+    #[validate(custom = "validate_color")]
+    pub color: Option<String>,
+    pub is_done: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Validate)]
+pub struct TaskFilters {
+    pub completed: Option<bool>,
+    pub priority: Option<String>,
+    pub category_id: Option<i64>,
+    pub status_id: Option<i64>,
+    /// Only the direct subtasks of this task
+    pub parent_id: Option<i64>,
+    pub search: Option<String>,
+    /// Only tasks due at or before this time; tasks without a due date are
+    /// left out by either bound
+    #[validate(custom = "validate_date_format")]
+    pub due_date_before: Option<String>,
+    /// Only tasks due at or after this time
+    #[validate(custom = "validate_date_format")]
+    pub due_date_after: Option<String>,
+    /// Defaults to true when searching and false otherwise
+    pub include_archived: Option<bool>,
+    /// Every filter must match
+    pub custom_fields: Option<Vec<CustomFieldFilter>>,
+    /// Defaults to newest first
+    pub sort_by: Option<TaskSortKey>,
+    pub sort_descending: Option<bool>,
+}
+
+/// Matches tasks by the value of one custom field. Number fields compare
+/// numerically, other fields compare their text (dates are `YYYY-MM-DD`).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomFieldFilter {
+    pub field_id: i64,
+    pub equals: Option<String>,
+    pub min: Option<String>,
+    pub max: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSortKey {
+    CreatedAt,
+    UpdatedAt,
+    DueDate,
+    Priority,
+    Title,
+    /// Tasks without a value come last
+    CustomField(i64),
+    /// Computed score, see `urgency`; sorted after the query
+    Urgency,
+}
+
+/// A user-defined field. `field_type` is "text", "number", "date", "select"
+/// or "checkbox"; `options` lists the choices of a select field.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomField {
+    pub id: i64,
+    pub name: String,
+    pub field_type: String,
+    pub options: Vec<String>,
+    /// `None` makes the field available to every category
+    pub category_id: Option<i64>,
+    pub position: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateCustomFieldInput {
+    #[validate(length(min = 1, max = 100, message = "Field name must be between 1 and 100 characters"))]
+    pub name: String,
+    #[validate(custom = "validate_field_type")]
+    pub field_type: String,
+    #[serde(default)]
+    pub options: Vec<String>,
+    pub category_id: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateCustomFieldInput {
+    pub id: i64,
+    #[validate(length(min = 1, max = 100, message = "Field name must be between 1 and 100 characters"))]
+    pub name: Option<String>,
+    pub options: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChecklistItem {
+    pub id: i64,
+    pub task_id: i64,
+    pub title: String,
+    pub done: bool,
+    pub position: i64,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateChecklistItemInput {
+    pub id: i64,
+    #[validate(length(min = 1, max = 255, message = "Checklist item must be between 1 and 255 characters"))]
+    pub title: Option<String>,
+    pub done: Option<bool>,
+}
+
+/// Reference to a file or URL; the app never copies the file itself.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Attachment {
+    pub id: i64,
+    pub task_id: i64,
+    pub kind: String,
+    pub target: String,
+    pub label: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateAttachmentInput {
+    pub task_id: i64,
+    #[validate(custom = "validate_attachment_kind")]
+    pub kind: String,
+    #[validate(length(min = 1, max = 2048, message = "Attachment target must be between 1 and 2048 characters"))]
+    pub target: String,
+    #[validate(length(max = 255, message = "Label cannot exceed 255 characters"))]
+    pub label: Option<String>,
+}
+
+/// A `[[Task title]]` or `#123` reference from one task's description.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskLink {
+    pub source_id: i64,
+    pub source_title: String,
+    /// `None` when the reference is dangling
+    pub target_id: Option<i64>,
+    pub target_title: Option<String>,
+    /// "title" or "id"
+    pub kind: String,
+    pub reference: String,
+}
+
+/// Options for `duplicate_task`.
+#[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+pub struct DuplicateTaskOptions {
+    /// Title of the copy; defaults to the original title
+    #[validate(length(min = 1, max = 255, message = "Title must be between 1 and 255 characters"))]
+    #[validate(custom = "validate_title_text")]
+    pub title: Option<String>,
+    /// Moves the due date of every copied task by this many minutes
+    #[serde(default)]
+    pub shift_due_minutes: Option<i64>,
+    /// Copies start open with unchecked checklists
+    #[serde(default = "default_true")]
+    pub reset_completion: bool,
+    #[serde(default = "default_true")]
+    pub include_subtasks: bool,
+}
+
+impl Default for DuplicateTaskOptions {
+    fn default() -> Self {
+        Self {
+            title: None,
+            shift_due_minutes: None,
+            reset_completion: true,
+            include_subtasks: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A task inside a template. Text fields may contain `{{variable}}`
+/// placeholders that are filled in on instantiation.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TemplateTask {
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub checklist: Vec<String>,
+    /// Due date relative to the anchor date, in minutes
+    pub due_offset_minutes: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TaskTemplate {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub category_id: Option<i64>,
+    pub tasks: Vec<TemplateTask>,
+    /// Placeholder names used anywhere in the template
+    pub variables: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateTemplateInput {
+    #[validate(length(min = 1, max = 100, message = "Template name must be between 1 and 100 characters"))]
+    pub name: String,
+    #[validate(length(max = 1000, message = "Description cannot exceed 1000 characters"))]
+    pub description: Option<String>,
+    pub category_id: Option<i64>,
+    #[validate(length(min = 1, message = "A template needs at least one task"))]
+    pub tasks: Vec<TemplateTask>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FocusSession {
+    pub id: i64,
+    pub task_id: Option<i64>,
+    pub kind: String,
+    pub started_at: String,
+    pub ended_at: String,
+    pub planned_seconds: i64,
+    pub actual_seconds: i64,
+    pub completed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NewFocusSession {
+    pub task_id: Option<i64>,
+    pub kind: String,
+    pub started_at: String,
+    pub ended_at: String,
+    pub planned_seconds: i64,
+    pub actual_seconds: i64,
+    pub completed: bool,
+}
+
+/// Setting key holding the number of days after which completed tasks are
+/// archived. Missing or `0` disables auto-archiving.
+pub const AUTO_ARCHIVE_SETTING: &str = "auto_archive_after_days";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AppSetting {
+    pub key: String,
+    pub value: String,
+    pub updated_at: String,
+}
+
+/// What `check_database` found wrong with a row.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityIssueKind {
+    /// Reported by `PRAGMA integrity_check`; only a backup can fix it
+    Corruption,
+    /// Reported by `PRAGMA foreign_key_check`
+    ForeignKey,
+    MissingCategory,
+    MissingParent,
+    MissingStatus,
+    InvalidPriority,
+    /// A row that belongs to a task or tag that no longer exists
+    OrphanRow,
+    InvalidDate,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IntegrityIssue {
+    pub kind: IntegrityIssueKind,
+    pub table: String,
+    /// Row the issue was found in, when it has an ID
+    pub row_id: Option<i64>,
+    pub message: String,
+    /// `repair_database` knows a safe fix for it
+    pub repairable: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// `PRAGMA integrity_check` found the file intact
+    pub file_ok: bool,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RepairReport {
+    /// Number of rows changed or removed
+    pub repaired: u64,
+    /// The check run again after repairing
+    pub report: IntegrityReport,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseSize {
+    /// The database file itself
+    pub file_bytes: u64,
+    /// Changes in the write-ahead log not yet folded into the file
+    pub wal_bytes: u64,
+    pub page_size: u64,
+    pub page_count: u64,
+    pub free_pages: u64,
+    /// Space held by free pages that maintenance can give back
+    pub reclaimable_bytes: u64,
+}
+
+// Validation functions
+fn validate_date_format(date: &str) -> Result<(), ValidationError> {
+    if chrono::DateTime::parse_from_rfc3339(date).is_ok() {
+        Ok(())
+    } else {
+        Err(ValidationError::new("Date must be in ISO 8601 format"))
+    }
+}
+
+fn validate_title_patch(title: &Patch<String>) -> Result<(), ValidationError> {
+    match title {
+        Patch::Absent => Ok(()),
+        Patch::Null => Err(ValidationError::new("Title cannot be removed")),
+        Patch::Value(title) if (1..=255).contains(&title.chars().count()) => validate_title_text(title),
+        Patch::Value(_) => Err(ValidationError::new("Title must be between 1 and 255 characters")),
+    }
+}
+
+/// A title needs more than whitespace, and only encrypted titles start
+/// with the sealed prefix; private tasks are recognised by it.
+fn validate_title_text(title: &str) -> Result<(), ValidationError> {
+    if title.trim().is_empty() {
+        Err(ValidationError::new("Title cannot be blank"))
+    } else if title.starts_with(crate::encryption::SEALED_PREFIX) {
+        Err(ValidationError::new("Title cannot start with 'enc:v1:'"))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_description_patch(description: &Patch<String>) -> Result<(), ValidationError> {
+    match description {
+        Patch::Value(description) if description.chars().count() > 1000 => {
+            Err(ValidationError::new("Description cannot exceed 1000 characters"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_completed_patch(completed: &Patch<bool>) -> Result<(), ValidationError> {
+    match completed {
+        Patch::Null => Err(ValidationError::new("Completed must be true or false")),
+        _ => Ok(()),
+    }
+}
+
+fn validate_status_patch(status_id: &Patch<i64>) -> Result<(), ValidationError> {
+    match status_id {
+        Patch::Null => Err(ValidationError::new("Status cannot be removed")),
+        _ => Ok(()),
+    }
+}
+
+fn validate_priority_patch(priority: &Patch<String>) -> Result<(), ValidationError> {
+    match priority {
+        Patch::Value(priority) if !(1..=50).contains(&priority.chars().count()) => {
+            Err(ValidationError::new("Priority must be between 1 and 50 characters"))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_due_date_patch(due_date: &Patch<String>) -> Result<(), ValidationError> {
+    match due_date {
+        Patch::Value(due_date) => validate_date_format(due_date),
+        _ => Ok(()),
+    }
+}
+
+fn validate_tags(tags: &[String]) -> Result<(), ValidationError> {
+    if tags.iter().all(|t| !t.is_empty() && t.len() <= 50 && !t.contains(char::is_whitespace)) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("Tags must be single words of at most 50 characters"))
+    }
+}
+
+fn validate_color(color: &str) -> Result<(), ValidationError> {
+    if color.starts_with('#') && color.len() == 7 {
+        if color[1..].chars().all(|c| c.is_ascii_hexdigit()) {
+            Ok(())
+        } else {
+            Err(ValidationError::new("Color must be a valid hex color (e.g., #FF5733)"))
+        }
+    } else {
+        Err(ValidationError::new("Color must be a valid hex color (e.g., #FF5733)"))
+    }
+}
+
+fn validate_attachment_kind(kind: &str) -> Result<(), ValidationError> {
+    match kind {
+        "file" | "url" => Ok(()),
+        _ => Err(ValidationError::new("Attachment kind must be 'file' or 'url'")),
+    }
+}
+
+fn validate_field_type(field_type: &str) -> Result<(), ValidationError> {
+    if crate::custom_fields::FIELD_TYPES.contains(&field_type) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("Field type must be 'text', 'number', 'date', 'select', or 'checkbox'"))
+    }
+}
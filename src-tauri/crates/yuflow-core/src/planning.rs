@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use crate::models::*;
+
+pub const CAPACITY_SETTING: &str = "daily_capacity_minutes";
+pub const DEFAULT_CAPACITY_MINUTES: u32 = 480;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlanItem {
+    pub task: Task,
+    pub estimate_minutes: Option<u32>,
+    /// First day the task was planned for, when it was carried over
+    pub carried_from: Option<String>,
+}
+
+/// The tasks chosen for a day, in order, with the estimated load.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailyPlan {
+    pub date: String,
+    pub capacity_minutes: u32,
+    pub items: Vec<PlanItem>,
+    /// Sum of all estimates
+    pub planned_minutes: u32,
+    /// Estimates of the items already completed
+    pub completed_minutes: u32,
+    /// Items without an estimate; they are not part of the load
+    pub unestimated: u32,
+    /// Capacity minus the planned load; negative when over capacity
+    pub free_minutes: i64,
+    pub over_capacity: bool,
+}
+
+/// Plan as stored, for backups.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DailyPlanRecord {
+    pub date: String,
+    pub capacity_minutes: Option<u32>,
+    pub items: Vec<DailyPlanRecordItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DailyPlanRecordItem {
+    pub task_id: i64,
+    pub estimate_minutes: Option<u32>,
+    pub carried_from: Option<String>,
+}
+
+pub fn build(date: String, capacity_minutes: u32, items: Vec<PlanItem>) -> DailyPlan {
+    let planned_minutes = items.iter().filter_map(|i| i.estimate_minutes).sum();
+    let completed_minutes = items
+        .iter()
+        .filter(|i| i.task.completed)
+        .filter_map(|i| i.estimate_minutes)
+        .sum();
+    let unestimated = items.iter().filter(|i| i.estimate_minutes.is_none()).count() as u32;
+    let free_minutes = capacity_minutes as i64 - planned_minutes as i64;
+
+    DailyPlan {
+        date,
+        capacity_minutes,
+        items,
+        planned_minutes,
+        completed_minutes,
+        unestimated,
+        free_minutes,
+        over_capacity: free_minutes < 0,
+    }
+}
@@ -0,0 +1,162 @@
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use sqlx::migrate::Migrator;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use crate::encryption::KeyInfo;
+
+/// Migrations built into the app. The path is relative to the crate root,
+/// so the app and the tests resolve the same directory.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+    /// When it was applied; `None` while pending
+    pub installed_on: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigrationStatus {
+    /// Highest applied version; 0 for a new database
+    pub current_version: i64,
+    /// Highest version this build of the app knows about
+    pub latest_version: i64,
+    pub applied: Vec<MigrationInfo>,
+    pub pending: Vec<MigrationInfo>,
+    /// Copies taken before earlier upgrades, oldest first
+    pub snapshots: Vec<String>,
+}
+
+pub fn latest_version() -> i64 {
+    MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0)
+}
+
+/// Versions recorded in the database, in order.
+async fn applied_versions(connection: &mut SqliteConnection) -> Result<Vec<(i64, String)>, sqlx::Error> {
+    let tracked = sqlx::query("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'")
+        .fetch_optional(&mut *connection)
+        .await?
+        .is_some();
+    if !tracked {
+        return Ok(Vec::new());
+    }
+
+    let rows = sqlx::query("SELECT version, CAST(installed_on AS TEXT) AS installed_on FROM _sqlx_migrations WHERE success = 1 ORDER BY version")
+        .fetch_all(&mut *connection)
+        .await?;
+    Ok(rows.iter().map(|row| (row.get("version"), row.get("installed_on"))).collect())
+}
+
+pub async fn status(pool: &SqlitePool, database_path: Option<&Path>) -> Result<MigrationStatus, sqlx::Error> {
+    let applied_versions = applied_versions(&mut *pool.acquire().await?).await?;
+
+    let mut applied = Vec::new();
+    let mut pending = Vec::new();
+    for migration in MIGRATOR.iter() {
+        let installed_on = applied_versions
+            .iter()
+            .find(|(version, _)| *version == migration.version)
+            .map(|(_, installed_on)| installed_on.clone());
+        let info = MigrationInfo {
+            version: migration.version,
+            description: migration.description.to_string(),
+            installed_on,
+        };
+        if info.installed_on.is_some() {
+            applied.push(info);
+        } else {
+            pending.push(info);
+        }
+    }
+
+    Ok(MigrationStatus {
+        current_version: applied_versions.last().map(|(version, _)| *version).unwrap_or(0),
+        latest_version: latest_version(),
+        applied,
+        pending,
+        snapshots: database_path
+            .map(list_snapshots)
+            .unwrap_or_default()
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect(),
+    })
+}
+
+/// Brings the schema up to date. A database written by a newer version of
+/// the app is refused instead of being half understood. When an existing
+/// database has pending migrations, it is first copied to
+/// `<file>.v<version>.snapshot`, so a failed upgrade can be rolled back by
+/// hand. Returns the snapshot, if one was taken.
+pub async fn migrate(pool: &SqlitePool, database_path: Option<&Path>) -> Result<Option<PathBuf>, sqlx::Error> {
+    // Everything runs on one connection; another one opened before the
+    // migrations would keep a stale copy of the schema
+    let mut connection = pool.acquire().await?;
+    let applied = applied_versions(&mut connection).await?;
+    let current = applied.last().map(|(version, _)| *version).unwrap_or(0);
+    let latest = latest_version();
+    if current > latest {
+        return Err(sqlx::Error::Configuration(format!(
+            "The database uses schema version {}, but this version of Yuflow only supports up to {}. Update Yuflow to open it.",
+            current, latest
+        ).into()));
+    }
+
+    let pending = MIGRATOR.iter().any(|m| !applied.iter().any(|(version, _)| *version == m.version));
+    let snapshot = match database_path {
+        Some(path) if pending && !applied.is_empty() => Some(snapshot(&mut connection, path, current).await?),
+        _ => None,
+    };
+
+    MIGRATOR.run(&mut *connection).await?;
+    Ok(snapshot)
+}
+
+fn snapshot_path(database_path: &Path, version: i64) -> PathBuf {
+    let mut path = database_path.as_os_str().to_owned();
+    path.push(format!(".v{}.snapshot", version));
+    PathBuf::from(path)
+}
+
+// VACUUM INTO keeps the key of an encrypted database, so the snapshot gets a
+// copy of the key parameters to be opened with the same passphrase
+async fn snapshot(connection: &mut SqliteConnection, database_path: &Path, version: i64) -> Result<PathBuf, sqlx::Error> {
+    let path = snapshot_path(database_path, version);
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.display().to_string())
+        .execute(&mut *connection)
+        .await?;
+
+    match KeyInfo::load(database_path)? {
+        Some(info) => info.save(&path)?,
+        None => KeyInfo::remove(&path)?,
+    }
+    Ok(path)
+}
+
+fn list_snapshots(database_path: &Path) -> Vec<PathBuf> {
+    let (Some(directory), Some(name)) = (database_path.parent(), database_path.file_name()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.v", name.to_string_lossy());
+
+    let mut snapshots: Vec<(i64, PathBuf)> = std::fs::read_dir(directory)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let file_name = entry.file_name().to_string_lossy().into_owned();
+                    let version = file_name.strip_prefix(&prefix)?.strip_suffix(".snapshot")?.parse().ok()?;
+                    Some((version, entry.path()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    snapshots.sort();
+    snapshots.into_iter().map(|(_, path)| path).collect()
+}
@@ -0,0 +1,221 @@
+use std::collections::{BTreeMap, BTreeSet};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::models::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    #[default]
+    Day,
+    Week,
+}
+
+/// Inclusive range of local calendar days (`YYYY-MM-DD`) to report on.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatisticsRange {
+    pub from: String,
+    pub to: String,
+    #[serde(default)]
+    pub granularity: Granularity,
+    /// User's offset east of UTC; defaults to the system timezone
+    pub utc_offset_minutes: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ThroughputBucket {
+    /// First day of the bucket
+    pub period: String,
+    pub created: u32,
+    pub completed: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CompletionRate {
+    pub key: String,
+    pub total: u32,
+    pub completed: u32,
+    pub rate: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Statistics {
+    pub throughput: Vec<ThroughputBucket>,
+    pub by_category: Vec<CompletionRate>,
+    pub by_priority: Vec<CompletionRate>,
+    pub average_hours_to_complete: Option<f64>,
+    pub overdue: u32,
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    /// Completed work sessions in the range
+    pub focus_sessions: u32,
+    /// Minutes spent in work sessions in the range, including interrupted ones
+    pub focus_minutes: f64,
+}
+
+const UNCATEGORIZED: &str = "Uncategorized";
+
+/// Computes all statistics from the given tasks; nothing leaves the machine.
+///
+/// Throughput, completion rates and time-to-complete cover tasks created
+/// (or completed) inside the range. Overdue counts and streaks describe the
+/// current state and ignore the range.
+pub fn compute(
+    tasks: &[Task],
+    categories: &[Category],
+    sessions: &[FocusSession],
+    from: NaiveDate,
+    to: NaiveDate,
+    granularity: Granularity,
+    now: DateTime<FixedOffset>,
+) -> Statistics {
+    let offset = *now.offset();
+    let local_day = |value: &str| parse_timestamp(value).map(|t| t.with_timezone(&offset).date_naive());
+    let in_range = |day: &NaiveDate| *day >= from && *day <= to;
+
+    let mut buckets: BTreeMap<NaiveDate, ThroughputBucket> = BTreeMap::new();
+    let mut day = from;
+    while day <= to {
+        let start = bucket_start(day, granularity);
+        buckets.entry(start).or_insert_with(|| ThroughputBucket {
+            period: start.to_string(),
+            created: 0,
+            completed: 0,
+        });
+        day += Duration::days(1);
+    }
+
+    let category_names: BTreeMap<i64, &str> = categories.iter().map(|c| (c.id, c.name.as_str())).collect();
+    let mut by_category: BTreeMap<String, (u32, u32)> = BTreeMap::new();
+    let mut by_priority: BTreeMap<String, (u32, u32)> = BTreeMap::new();
+    let mut completion_hours: Vec<f64> = Vec::new();
+    let mut completion_days: BTreeSet<NaiveDate> = BTreeSet::new();
+    let mut overdue = 0;
+
+    for task in tasks {
+        let created = parse_timestamp(&task.created_at);
+        let completed = task.completed_at.as_deref().and_then(parse_timestamp).filter(|_| task.completed);
+
+        if let Some(created_day) = local_day(&task.created_at).filter(in_range) {
+            if let Some(bucket) = buckets.get_mut(&bucket_start(created_day, granularity)) {
+                bucket.created += 1;
+            }
+
+            let category = task
+                .category_id
+                .and_then(|id| category_names.get(&id).copied())
+                .unwrap_or(UNCATEGORIZED);
+            for (map, key) in [(&mut by_category, category), (&mut by_priority, task.priority.as_str())] {
+                let entry = map.entry(key.to_string()).or_insert((0, 0));
+                entry.0 += 1;
+                entry.1 += u32::from(task.completed);
+            }
+        }
+
+        if let Some(completed) = completed {
+            let completed_day = completed.with_timezone(&offset).date_naive();
+            completion_days.insert(completed_day);
+
+            if in_range(&completed_day) {
+                if let Some(bucket) = buckets.get_mut(&bucket_start(completed_day, granularity)) {
+                    bucket.completed += 1;
+                }
+                if let Some(created) = created {
+                    completion_hours.push((completed - created).num_minutes() as f64 / 60.0);
+                }
+            }
+        }
+
+        let is_overdue = task
+            .due_date
+            .as_deref()
+            .and_then(parse_timestamp)
+            .is_some_and(|due| due < now);
+        if !task.completed && task.archived_at.is_none() && is_overdue {
+            overdue += 1;
+        }
+    }
+
+    let (current_streak, longest_streak) = streaks(&completion_days, now.date_naive());
+
+    let mut focus_sessions = 0;
+    let mut focus_seconds = 0;
+    for session in sessions.iter().filter(|s| s.kind == "work") {
+        if local_day(&session.ended_at).filter(in_range).is_some() {
+            focus_sessions += u32::from(session.completed);
+            focus_seconds += session.actual_seconds;
+        }
+    }
+
+    Statistics {
+        throughput: buckets.into_values().collect(),
+        by_category: into_rates(by_category),
+        by_priority: into_rates(by_priority),
+        average_hours_to_complete: if completion_hours.is_empty() {
+            None
+        } else {
+            Some(completion_hours.iter().sum::<f64>() / completion_hours.len() as f64)
+        },
+        overdue,
+        current_streak,
+        longest_streak,
+        focus_sessions,
+        focus_minutes: focus_seconds as f64 / 60.0,
+    }
+}
+
+/// Parses the timestamp formats found in the database: RFC 3339 written by
+/// the app and SQLite's `CURRENT_TIMESTAMP` (UTC) used as column default.
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|t| t.and_utc())
+        })
+}
+
+fn bucket_start(day: NaiveDate, granularity: Granularity) -> NaiveDate {
+    match granularity {
+        Granularity::Day => day,
+        Granularity::Week => day - Duration::days(day.weekday().num_days_from_monday() as i64),
+    }
+}
+
+fn into_rates(map: BTreeMap<String, (u32, u32)>) -> Vec<CompletionRate> {
+    map.into_iter()
+        .map(|(key, (total, completed))| CompletionRate {
+            key,
+            total,
+            completed,
+            rate: if total == 0 { 0.0 } else { completed as f64 / total as f64 },
+        })
+        .collect()
+}
+
+/// Returns the current and longest runs of consecutive days with at least
+/// one completion. The current streak survives until the end of today, so
+/// a streak ending yesterday still counts.
+fn streaks(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> (u32, u32) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+
+    for day in days {
+        run = match previous {
+            Some(p) if *day - p == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*day);
+    }
+
+    let current = match previous {
+        Some(last) if last == today || last == today - Duration::days(1) => run,
+        _ => 0,
+    };
+
+    (current, longest)
+}
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use crate::database::Database;
+use crate::error::AppError;
+use crate::models::*;
+
+/// The task and category operations every storage backend offers, so code
+/// written against it runs on the SQLite database as well as in memory.
+#[async_trait]
+pub trait TaskStore: Send + Sync {
+    async fn get_tasks(&self, filters: Option<TaskFilters>) -> Result<Vec<Task>, AppError>;
+    async fn get_task(&self, id: i64) -> Result<Option<Task>, AppError>;
+    async fn create_task(&self, input: CreateTaskInput) -> Result<Task, AppError>;
+    /// Applies the fields present in `input` and reports what changed.
+    async fn update_task(&self, input: UpdateTaskInput) -> Result<TaskUpdate, AppError>;
+    /// Deletes a task together with all of its subtasks.
+    async fn delete_task(&self, id: i64) -> Result<(), AppError>;
+
+    async fn get_categories(&self) -> Result<Vec<Category>, AppError>;
+    async fn create_category(&self, input: CreateCategoryInput) -> Result<Category, AppError>;
+    /// Deletes a category; its tasks are kept without one.
+    async fn delete_category(&self, id: i64) -> Result<(), AppError>;
+}
+
+#[async_trait]
+impl TaskStore for Database {
+    async fn get_tasks(&self, filters: Option<TaskFilters>) -> Result<Vec<Task>, AppError> {
+        Ok(Database::get_tasks(self, filters).await?)
+    }
+
+    async fn get_task(&self, id: i64) -> Result<Option<Task>, AppError> {
+        Ok(Database::get_task(self, id).await?)
+    }
+
+    async fn create_task(&self, input: CreateTaskInput) -> Result<Task, AppError> {
+        Ok(Database::create_task(self, input).await?)
+    }
+
+    async fn update_task(&self, input: UpdateTaskInput) -> Result<TaskUpdate, AppError> {
+        let id = input.id;
+        Database::update_task(self, input)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))
+    }
+
+    async fn delete_task(&self, id: i64) -> Result<(), AppError> {
+        Ok(Database::delete_task(self, id).await?)
+    }
+
+    async fn get_categories(&self) -> Result<Vec<Category>, AppError> {
+        Ok(Database::get_categories(self).await?)
+    }
+
+    async fn create_category(&self, input: CreateCategoryInput) -> Result<Category, AppError> {
+        Ok(Database::create_category(self, input).await?)
+    }
+
+    async fn delete_category(&self, id: i64) -> Result<(), AppError> {
+        Ok(Database::delete_category(self, id).await?)
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::{BTreeSet, HashMap};
+use chrono::{DateTime, Duration, FixedOffset};
+use crate::error::AppError;
+use crate::models::*;
+use crate::statistics::parse_timestamp;
+use crate::validation::Violations;
+
+/// A task ready to be created from a template, with its checklist.
+#[derive(Debug)]
+pub struct TemplateInstance {
+    pub task: CreateTaskInput,
+    pub checklist: Vec<String>,
+}
+
+/// Returns the names of all `{{name}}` placeholders in `text`.
+pub fn placeholders(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        let name = after[..end].trim();
+        if is_variable_name(name) {
+            names.push(name.to_string());
+        }
+        rest = &after[end + 2..];
+    }
+
+    names
+}
+
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Replaces every `{{name}}` placeholder with its value. Placeholders
+/// without a value are collected in `missing` and left untouched.
+pub fn substitute(text: &str, vars: &HashMap<String, String>, missing: &mut BTreeSet<String>) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            break;
+        };
+        let name = after[..end].trim();
+
+        output.push_str(&rest[..start]);
+        match vars.get(name).filter(|_| is_variable_name(name)) {
+            Some(value) => output.push_str(value),
+            None => {
+                if is_variable_name(name) {
+                    missing.insert(name.to_string());
+                }
+                output.push_str(&rest[start..start + end + 4]);
+            }
+        }
+        rest = &after[end + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Distinct placeholder names used by the template tasks, sorted.
+pub fn template_variables(tasks: &[TemplateTask]) -> Vec<String> {
+    let mut names = BTreeSet::new();
+
+    for task in tasks {
+        let texts = std::iter::once(&task.title)
+            .chain(task.description.iter())
+            .chain(task.checklist.iter());
+        for text in texts {
+            names.extend(placeholders(text));
+        }
+    }
+
+    names.into_iter().collect()
+}
+
+/// Builds template tasks from existing tasks. Due dates become offsets from
+/// the earliest due date among them.
+pub fn template_tasks_from(tasks: &[(Task, Vec<ChecklistItem>)]) -> Vec<TemplateTask> {
+    let anchor = tasks
+        .iter()
+        .filter_map(|(task, _)| task.due_date.as_deref().and_then(parse_timestamp))
+        .min();
+
+    tasks
+        .iter()
+        .map(|(task, checklist)| TemplateTask {
+            title: task.title.clone(),
+            description: task.description.clone(),
+            priority: Some(task.priority.clone()),
+            tags: task.tags.clone(),
+            checklist: checklist.iter().map(|item| item.title.clone()).collect(),
+            due_offset_minutes: task
+                .due_date
+                .as_deref()
+                .and_then(parse_timestamp)
+                .zip(anchor)
+                .map(|(due, anchor)| (due - anchor).num_minutes()),
+        })
+        .collect()
+}
+
+/// Expands a template into tasks. Fails with a validation error naming the
+/// variables that have no value and the tasks whose due date would fall
+/// outside the calendar.
+pub fn instantiate(
+    template: &TaskTemplate,
+    vars: &HashMap<String, String>,
+    anchor: DateTime<FixedOffset>,
+) -> Result<Vec<TemplateInstance>, AppError> {
+    let mut missing = BTreeSet::new();
+    let mut violations = Violations::default();
+
+    let instances = template
+        .tasks
+        .iter()
+        .enumerate()
+        .map(|(index, task)| TemplateInstance {
+            task: CreateTaskInput {
+                title: substitute(&task.title, vars, &mut missing),
+                description: task.description.as_ref().map(|d| substitute(d, vars, &mut missing)),
+                priority: task.priority.clone(),
+                category_id: template.category_id,
+                due_date: task.due_offset_minutes.and_then(|offset| {
+                    let due = Duration::try_minutes(offset).and_then(|offset| anchor.checked_add_signed(offset));
+                    if due.is_none() {
+                        violations.add("anchor_date", format!("Due date of task {} is out of range", index + 1));
+                    }
+                    due.map(|due| due.to_rfc3339())
+                }),
+                tags: task.tags.clone(),
+                status_id: None,
+                parent_id: None,
+            },
+            checklist: task
+                .checklist
+                .iter()
+                .map(|item| substitute(item, vars, &mut missing))
+                .collect(),
+        })
+        .collect();
+
+    if !missing.is_empty() {
+        let names: Vec<String> = missing.into_iter().collect();
+        violations.add("vars", format!("Missing template variables: {}", names.join(", ")));
+    }
+    violations.into_result().map(|()| instances)
+}
@@ -0,0 +1,1803 @@
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validator::Validate;
+    use crate::models::*;
+    use crate::database::Database;
+    use tempfile::TempDir;
+    use std::path::PathBuf;
+
+    struct TestApp {
+        _temp_dir: TempDir,
+        app_dir: PathBuf,
+    }
+
+    impl TestApp {
+        fn new() -> Self {
+            let temp_dir = TempDir::new().expect("Failed to create temp dir");
+            let app_dir = temp_dir.path().to_path_buf();
+            
+            TestApp {
+                _temp_dir: temp_dir,
+                app_dir,
+            }
+        }
+
+        fn path(&self) -> MockPath {
+            MockPath { app_dir: self.app_dir.clone() }
+        }
+    }
+
+    struct MockPath {
+        app_dir: PathBuf,
+    }
+
+    impl MockPath {
+        fn app_data_dir(&self) -> Result<PathBuf, Box<dyn std::error::Error>> {
+            Ok(self.app_dir.clone())
+        }
+    }
+
+    async fn setup_test_database() -> (TestApp, Database) {
+        let test_app = TestApp::new();
+        
+        let database = Database::open_in_memory().await
+            .expect("Failed to open test database");
+        
+        (test_app, database)
+    }
+
+    #[tokio::test]
+    async fn test_create_and_get_task() {
+        let (_app, db) = setup_test_database().await;
+        
+        let task_input = CreateTaskInput {
+            title: "Test Task".to_string(),
+            description: Some("Test Description".to_string()),
+            priority: Some("high".to_string()),
+            category_id: None,
+            due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        };
+
+        // Test validation
+        assert!(task_input.validate().is_ok());
+
+        // Create task
+        let created_task = db.create_task(task_input).await
+            .expect("Failed to create task");
+        
+        assert_eq!(created_task.title, "Test Task");
+        assert_eq!(created_task.description, Some("Test Description".to_string()));
+        assert_eq!(created_task.priority, "high");
+        assert!(!created_task.completed);
+
+        // Get task
+        let retrieved_task = db.get_task(created_task.id).await
+            .expect("Failed to get task")
+            .expect("Task not found");
+        
+        assert_eq!(retrieved_task.id, created_task.id);
+        assert_eq!(retrieved_task.title, created_task.title);
+    }
+
+    #[tokio::test]
+    async fn test_update_task() {
+        let (_app, db) = setup_test_database().await;
+        
+        let task_input = CreateTaskInput {
+            title: "Original Title".to_string(),
+            description: None,
+            priority: Some("low".to_string()),
+            category_id: None,
+            due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        };
+
+        let created_task = db.create_task(task_input).await
+            .expect("Failed to create task");
+
+        let update_input = UpdateTaskInput {
+            id: created_task.id,
+            title: Patch::Value("Updated Title".to_string()),
+            description: Patch::Value("New Description".to_string()),
+            completed: Patch::Value(true),
+            priority: Patch::Value("high".to_string()),
+            ..UpdateTaskInput::default()
+        };
+
+        let updated_task = db.update_task(update_input).await
+            .expect("Failed to update task")
+            .expect("Task not found")
+            .task;
+
+        assert_eq!(updated_task.title, "Updated Title");
+        assert_eq!(updated_task.description, Some("New Description".to_string()));
+        assert_eq!(updated_task.priority, "high");
+        assert!(updated_task.completed);
+    }
+
+    #[tokio::test]
+    async fn test_delete_task() {
+        let (_app, db) = setup_test_database().await;
+        
+        let task_input = CreateTaskInput {
+            title: "Task to Delete".to_string(),
+            description: None,
+            priority: Some("medium".to_string()),
+            category_id: None,
+            due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        };
+
+        let created_task = db.create_task(task_input).await
+            .expect("Failed to create task");
+
+        // Delete task
+        db.delete_task(created_task.id).await
+            .expect("Failed to delete task");
+
+        // Verify task is deleted
+        let retrieved_task = db.get_task(created_task.id).await
+            .expect("Failed to query task");
+        
+        assert!(retrieved_task.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_create_and_get_category() {
+        let (_app, db) = setup_test_database().await;
+        
+        let category_input = CreateCategoryInput {
+            name: "Work".to_string(),
+            color: Some("#FF5733".to_string()),
+        };
+
+        // Test validation
+        assert!(category_input.validate().is_ok());
+
+        let created_category = db.create_category(category_input).await
+            .expect("Failed to create category");
+
+        assert_eq!(created_category.name, "Work");
+        assert_eq!(created_category.color, "#FF5733");
+
+        // Get all categories
+        let categories = db.get_categories().await
+            .expect("Failed to get categories");
+        
+        assert!(categories.iter().any(|c| c.name == "Work"));
+    }
+
+    #[tokio::test]
+    async fn test_task_filters() {
+        let (_app, db) = setup_test_database().await;
+        
+        // Create test tasks
+        let task1 = CreateTaskInput {
+            title: "High Priority Task".to_string(),
+            description: None,
+            priority: Some("high".to_string()),
+            category_id: None,
+            due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        };
+        
+        let task2 = CreateTaskInput {
+            title: "Low Priority Task".to_string(),
+            description: None,
+            priority: Some("low".to_string()),
+            category_id: None,
+            due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        };
+
+        let created_task1 = db.create_task(task1).await.expect("Failed to create task1");
+        let created_task2 = db.create_task(task2).await.expect("Failed to create task2");
+
+        // Mark task1 as completed
+        db.update_task(UpdateTaskInput {
+            id: created_task1.id,
+            completed: Patch::Value(true),
+            ..UpdateTaskInput::default()
+        }).await.expect("Failed to update task1");
+
+        // Test filter by completed status
+        let completed_filter = TaskFilters {
+            completed: Some(true),
+            priority: None,
+            category_id: None,
+            status_id: None,
+            parent_id: None,
+            search: None,
+            due_date_before: None,
+            due_date_after: None,
+            include_archived: None,
+            custom_fields: None,
+            sort_by: None,
+            sort_descending: None,
+        };
+
+        let completed_tasks = db.get_tasks(Some(completed_filter)).await
+            .expect("Failed to get completed tasks");
+        
+        assert_eq!(completed_tasks.len(), 1);
+        assert_eq!(completed_tasks[0].title, "High Priority Task");
+
+        // Test filter by priority
+        let priority_filter = TaskFilters {
+            completed: None,
+            priority: Some("low".to_string()),
+            category_id: None,
+            status_id: None,
+            parent_id: None,
+            search: None,
+            due_date_before: None,
+            due_date_after: None,
+            include_archived: None,
+            custom_fields: None,
+            sort_by: None,
+            sort_descending: None,
+        };
+
+        let low_priority_tasks = db.get_tasks(Some(priority_filter)).await
+            .expect("Failed to get low priority tasks");
+        
+        assert_eq!(low_priority_tasks.len(), 1);
+        assert_eq!(low_priority_tasks[0].title, "Low Priority Task");
+    }
+
+    #[tokio::test]
+    async fn test_settings() {
+        let (_app, db) = setup_test_database().await;
+        
+        // Set a setting
+        db.set_setting("theme", "dark").await
+            .expect("Failed to set setting");
+
+        // Get the setting
+        let value = db.get_setting("theme").await
+            .expect("Failed to get setting");
+        
+        assert_eq!(value, Some("dark".to_string()));
+
+        // Get non-existent setting
+        let missing = db.get_setting("nonexistent").await
+            .expect("Failed to query missing setting");
+        
+        assert!(missing.is_none());
+
+        // Update setting
+        db.set_setting("theme", "light").await
+            .expect("Failed to update setting");
+
+        let updated_value = db.get_setting("theme").await
+            .expect("Failed to get updated setting");
+        
+        assert_eq!(updated_value, Some("light".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_validation_errors() {
+        // Test invalid task title (empty)
+        let invalid_task = CreateTaskInput {
+            title: "".to_string(),
+            description: None,
+            priority: Some("high".to_string()),
+            category_id: None,
+            due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        };
+
+        assert!(invalid_task.validate().is_err());
+
+        // Test invalid priority (empty name); known names are checked against the database
+        let invalid_priority = CreateTaskInput {
+            title: "Valid Title".to_string(),
+            description: None,
+            priority: Some("".to_string()),
+            category_id: None,
+            due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        };
+
+        assert!(invalid_priority.validate().is_err());
+
+        // Test invalid category name (empty)
+        let invalid_category = CreateCategoryInput {
+            name: "".to_string(),
+            color: Some("#FF5733".to_string()),
+        };
+
+        assert!(invalid_category.validate().is_err());
+
+        // Test invalid color format
+        let invalid_color = CreateCategoryInput {
+            name: "Valid Name".to_string(),
+            color: Some("invalid-color".to_string()),
+        };
+
+        assert!(invalid_color.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_functionality() {
+        let (_app, db) = setup_test_database().await;
+        
+        // Create test tasks
+        let task1 = CreateTaskInput {
+            title: "Learn Rust Programming".to_string(),
+            description: Some("Study Rust language fundamentals".to_string()),
+            priority: Some("high".to_string()),
+            category_id: None,
+            due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        };
+        
+        let task2 = CreateTaskInput {
+            title: "Buy Groceries".to_string(),
+            description: Some("Milk, bread, and fruits".to_string()),
+            priority: Some("medium".to_string()),
+            category_id: None,
+            due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        };
+
+        db.create_task(task1).await.expect("Failed to create task1");
+        db.create_task(task2).await.expect("Failed to create task2");
+
+        // Search by title
+        let search_filter = TaskFilters {
+            completed: None,
+            priority: None,
+            category_id: None,
+            status_id: None,
+            parent_id: None,
+            search: Some("Rust".to_string()),
+            due_date_before: None,
+            due_date_after: None,
+            include_archived: None,
+            custom_fields: None,
+            sort_by: None,
+            sort_descending: None,
+        };
+
+        let search_results = db.get_tasks(Some(search_filter)).await
+            .expect("Failed to search tasks");
+        
+        assert_eq!(search_results.len(), 1);
+        assert_eq!(search_results[0].title, "Learn Rust Programming");
+
+        // Search by description
+        let desc_search_filter = TaskFilters {
+            completed: None,
+            priority: None,
+            category_id: None,
+            status_id: None,
+            parent_id: None,
+            search: Some("Milk".to_string()),
+            due_date_before: None,
+            due_date_after: None,
+            include_archived: None,
+            custom_fields: None,
+            sort_by: None,
+            sort_descending: None,
+        };
+
+        let desc_results = db.get_tasks(Some(desc_search_filter)).await
+            .expect("Failed to search by description");
+        
+        assert_eq!(desc_results.len(), 1);
+        assert_eq!(desc_results[0].title, "Buy Groceries");
+
+        // Quotes in the search text are matched, not run as SQL
+        db.create_task(CreateTaskInput {
+            title: "Call O'Brien".to_string(),
+            description: None,
+            priority: None,
+            category_id: None,
+            due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        }).await.expect("Failed to create task");
+        let quoted = db.get_tasks(Some(TaskFilters {
+            search: Some("O'Brien".to_string()),
+            ..TaskFilters::default()
+        })).await.expect("Failed to search with a quote");
+        assert_eq!(quoted.len(), 1);
+        assert_eq!(quoted[0].title, "Call O'Brien");
+        let injected = db.get_tasks(Some(TaskFilters {
+            search: Some("x' OR '1'='1".to_string()),
+            ..TaskFilters::default()
+        })).await.expect("Failed to search with a quote");
+        assert!(injected.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_task_with_tags() {
+        let (_app, db) = setup_test_database().await;
+        
+        let task_input = CreateTaskInput {
+            title: "Tagged Task".to_string(),
+            description: None,
+            priority: None,
+            category_id: None,
+            due_date: None,
+            tags: vec!["billing".to_string(), "Billing".to_string(), "acme".to_string()],
+            status_id: None,
+            parent_id: None,
+        };
+
+        assert!(task_input.validate().is_ok());
+
+        let created_task = db.create_task(task_input).await
+            .expect("Failed to create task");
+        
+        assert_eq!(created_task.tags, vec!["billing", "acme"]);
+
+        let mut retrieved_tags = db.get_task(created_task.id).await
+            .expect("Failed to get task")
+            .expect("Task not found")
+            .tags;
+        retrieved_tags.sort();
+        
+        assert_eq!(retrieved_tags, vec!["acme", "billing"]);
+    }
+
+    #[tokio::test]
+    async fn test_completion_timestamp_and_archive() {
+        let (_app, db) = setup_test_database().await;
+        
+        let task = db.create_task(CreateTaskInput {
+            title: "Ship release".to_string(),
+            description: None,
+            priority: None,
+            category_id: None,
+            due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        }).await.expect("Failed to create task");
+        
+        assert!(task.completed_at.is_none());
+
+        let complete = |completed| UpdateTaskInput {
+            id: task.id,
+            completed: Patch::Value(completed),
+            ..UpdateTaskInput::default()
+        };
+
+        let done = db.update_task(complete(true)).await.expect("Failed to complete task").expect("Task not found").task;
+        let completed_at = done.completed_at.clone().expect("completed_at not set");
+
+        // Saving a completed task again keeps the original timestamp
+        let again = db.update_task(complete(true)).await.expect("Failed to update task").expect("Task not found").task;
+        assert_eq!(again.completed_at, Some(completed_at));
+
+        let reopened = db.update_task(complete(false)).await.expect("Failed to reopen task").expect("Task not found").task;
+        assert!(reopened.completed_at.is_none());
+
+        db.update_task(complete(true)).await.expect("Failed to complete task");
+
+        // Policy disabled: nothing is archived
+        assert_eq!(db.apply_archive_policy().await.expect("Failed to apply policy"), 0);
+
+        db.set_setting(AUTO_ARCHIVE_SETTING, "14").await.expect("Failed to set setting");
+        assert_eq!(db.apply_archive_policy().await.expect("Failed to apply policy"), 0);
+
+        assert_eq!(db.archive_completed_tasks(0).await.expect("Failed to archive"), 1);
+
+        let visible = db.get_tasks(None).await.expect("Failed to get tasks");
+        assert!(visible.is_empty());
+
+        let search = TaskFilters {
+            completed: None,
+            priority: None,
+            category_id: None,
+            status_id: None,
+            parent_id: None,
+            search: Some("release".to_string()),
+            due_date_before: None,
+            due_date_after: None,
+            include_archived: None,
+            custom_fields: None,
+            sort_by: None,
+            sort_descending: None,
+        };
+        let found = db.get_tasks(Some(search)).await.expect("Failed to search tasks");
+        assert_eq!(found.len(), 1);
+        assert!(found[0].archived_at.is_some());
+
+        // Reopening brings the task back from the archive
+        let reopened = db.update_task(complete(false)).await.expect("Failed to reopen task").expect("Task not found").task;
+        assert!(reopened.archived_at.is_none());
+    }
+
+    #[test]
+    fn test_statistics() {
+        use crate::statistics::{self, Granularity};
+        use chrono::{FixedOffset, NaiveDate, TimeZone};
+
+        let task = |id, priority: &str, created: &str, completed: Option<&str>, due: Option<&str>| Task {
+            id,
+            title: format!("Task {}", id),
+            description: None,
+            completed: completed.is_some(),
+            status_id: None,
+            priority: priority.to_string(),
+            category_id: Some(1),
+            due_date: due.map(str::to_string),
+            created_at: created.to_string(),
+            updated_at: created.to_string(),
+            completed_at: completed.map(str::to_string),
+            archived_at: None,
+            parent_id: None,
+            tags: Vec::new(),
+            custom_fields: Default::default(),
+            is_private: false,
+            locked: false,
+        };
+
+        let tasks = vec![
+            task(1, "high", "2025-03-10T09:00:00+00:00", Some("2025-03-10T15:00:00+00:00"), None),
+            task(2, "high", "2025-03-10 10:00:00", Some("2025-03-11T10:00:00+00:00"), None),
+            task(3, "low", "2025-03-11T08:00:00+00:00", None, Some("2025-03-11T12:00:00+00:00")),
+            task(4, "low", "2025-03-12T08:00:00+00:00", Some("2025-03-12T20:00:00+00:00"), None),
+            task(5, "medium", "2025-02-01T08:00:00+00:00", Some("2025-03-05T08:00:00+00:00"), None),
+        ];
+        let categories = vec![Category {
+            id: 1,
+            name: "General".to_string(),
+            color: "#F97316".to_string(),
+            created_at: "2025-01-01T00:00:00+00:00".to_string(),
+            is_private: false,
+        }];
+        let now = FixedOffset::east_opt(0).unwrap().with_ymd_and_hms(2025, 3, 13, 9, 0, 0).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+
+        let stats = statistics::compute(&tasks, &categories, &[], day(10), day(12), Granularity::Day, now);
+
+        let throughput: Vec<(u32, u32)> = stats.throughput.iter().map(|b| (b.created, b.completed)).collect();
+        assert_eq!(throughput, vec![(2, 1), (1, 1), (1, 1)]);
+
+        assert_eq!(stats.by_category.len(), 1);
+        assert_eq!(stats.by_category[0].total, 4);
+        assert_eq!(stats.by_category[0].completed, 3);
+
+        let high = stats.by_priority.iter().find(|r| r.key == "high").unwrap();
+        assert_eq!(high.rate, 1.0);
+        let low = stats.by_priority.iter().find(|r| r.key == "low").unwrap();
+        assert_eq!(low.rate, 0.5);
+
+        // (6h + 24h + 12h) / 3
+        assert_eq!(stats.average_hours_to_complete, Some(14.0));
+        assert_eq!(stats.overdue, 1);
+        assert_eq!(stats.current_streak, 3);
+        assert_eq!(stats.longest_streak, 3);
+
+        let weekly = statistics::compute(&tasks, &categories, &[], day(1), day(12), Granularity::Week, now);
+        let periods: Vec<&str> = weekly.throughput.iter().map(|b| b.period.as_str()).collect();
+        assert_eq!(periods, vec!["2025-02-24", "2025-03-03", "2025-03-10"]);
+        assert_eq!(weekly.throughput[1].completed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_workflow_statuses() {
+        let (_app, db) = setup_test_database().await;
+        
+        let statuses = db.get_statuses().await.expect("Failed to get statuses");
+        let names: Vec<&str> = statuses.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Backlog", "In Progress", "Review", "Done"]);
+
+        let task = db.create_task(CreateTaskInput {
+            title: "Write changelog".to_string(),
+            description: None,
+            priority: None,
+            category_id: None,
+            due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        }).await.expect("Failed to create task");
+        assert_eq!(task.status_id, Some(1));
+        assert!(!task.completed);
+
+        let done = db.transition_task(task.id, 4).await
+            .expect("Failed to move task")
+            .expect("Task not found");
+        assert!(done.completed);
+        assert!(done.completed_at.is_some());
+
+        // Reopening through update_task picks the first open status
+        let reopened = db.update_task(UpdateTaskInput {
+            id: task.id,
+            completed: Patch::Value(false),
+            ..UpdateTaskInput::default()
+        }).await.expect("Failed to update task").expect("Task not found").task;
+        assert_eq!(reopened.status_id, Some(1));
+        assert!(reopened.completed_at.is_none());
+
+        let in_review = db.transition_task(task.id, 3).await
+            .expect("Failed to move task")
+            .expect("Task not found");
+        assert!(!in_review.completed);
+
+        // Turning a column into a done column completes its tasks
+        db.update_status(UpdateStatusInput {
+            id: 3,
+            name: Some("Approved".to_string()),
+            color: None,
+            is_done: Some(true),
+        }).await.expect("Failed to update status");
+        let approved = db.get_task(task.id).await
+            .expect("Failed to get task")
+            .expect("Task not found");
+        assert!(approved.completed);
+
+        let shipped = db.create_status(CreateStatusInput {
+            name: "Shipped".to_string(),
+            color: Some("#10B981".to_string()),
+            is_done: true,
+        }).await.expect("Failed to create status");
+        assert_eq!(shipped.position, 4);
+
+        // Deleting a status moves its tasks to the next status of the same kind
+        db.delete_status(3, None).await.expect("Failed to delete status");
+        let moved = db.get_task(task.id).await
+            .expect("Failed to get task")
+            .expect("Task not found");
+        assert_eq!(moved.status_id, Some(4));
+        assert!(moved.completed);
+
+        let reordered = db.reorder_statuses(&[shipped.id, 4]).await
+            .expect("Failed to reorder statuses");
+        let names: Vec<&str> = reordered.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Shipped", "Done", "Backlog", "In Progress"]);
+
+        // Reopened by moving out of a deleted done status, a task leaves the archive
+        db.transition_task(task.id, shipped.id).await.expect("Failed to move task");
+        db.set_task_archived(task.id, true).await.expect("Failed to archive task");
+        db.delete_status(shipped.id, Some(1)).await.expect("Failed to delete status");
+        let reopened = db.get_task(task.id).await
+            .expect("Failed to get task")
+            .expect("Task not found");
+        assert_eq!(reopened.status_id, Some(1));
+        assert!(!reopened.completed);
+        assert!(reopened.archived_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_task_templates() {
+        use crate::templates;
+        use crate::validation::Check;
+        use std::collections::HashMap;
+
+        let (_app, db) = setup_test_database().await;
+        
+        let kickoff = db.create_task(CreateTaskInput {
+            title: "Kickoff call with {{client}}".to_string(),
+            description: None,
+            priority: Some("high".to_string()),
+            category_id: Some(1),
+            due_date: Some("2025-03-10T09:00:00+00:00".to_string()),
+            tags: vec!["onboarding".to_string()],
+            status_id: None,
+            parent_id: None,
+        }).await.expect("Failed to create task");
+        db.add_checklist_item(kickoff.id, "Send agenda to {{contact}}").await
+            .expect("Failed to add checklist item");
+        
+        db.create_task(CreateTaskInput {
+            title: "Send welcome pack".to_string(),
+            description: None,
+            priority: None,
+            category_id: Some(1),
+            due_date: Some("2025-03-12T17:00:00+00:00".to_string()),
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        }).await.expect("Failed to create task");
+
+        let tasks = db.get_tasks(None).await.expect("Failed to get tasks");
+        let mut with_checklists = Vec::new();
+        for task in tasks {
+            let checklist = db.get_checklist(Some(task.id)).await.expect("Failed to get checklist");
+            with_checklists.push((task, checklist));
+        }
+
+        let template = db.create_template(CreateTemplateInput {
+            name: "Client onboarding".to_string(),
+            description: None,
+            category_id: Some(1),
+            tasks: templates::template_tasks_from(&with_checklists),
+        }).await.expect("Failed to create template");
+        assert_eq!(template.variables, vec!["client", "contact"]);
+
+        let template = db.get_template(template.id).await
+            .expect("Failed to get template")
+            .expect("Template not found");
+        let kickoff_template = template.tasks.iter().find(|t| t.title.starts_with("Kickoff")).unwrap();
+        assert_eq!(kickoff_template.due_offset_minutes, Some(0));
+        assert_eq!(kickoff_template.checklist, vec!["Send agenda to {{contact}}"]);
+
+        let anchor = chrono::DateTime::parse_from_rfc3339("2025-06-02T09:00:00+02:00").unwrap();
+        let mut vars = HashMap::new();
+        vars.insert("client".to_string(), "ACME".to_string());
+
+        let missing = templates::instantiate(&template, &vars, anchor).unwrap_err();
+        assert_eq!(missing.to_string(), "Missing template variables: contact");
+        
+        // Offsets are bounded when stored and checked again when expanded
+        let far = CreateTemplateInput {
+            name: "Far".to_string(),
+            description: None,
+            category_id: None,
+            tasks: vec![TemplateTask { due_offset_minutes: Some(i64::MAX), ..template.tasks[0].clone() }],
+        };
+        assert!(far.check().is_err());
+        let far = TaskTemplate { tasks: far.tasks, ..template.clone() };
+        let mut all_vars = vars.clone();
+        all_vars.insert("contact".to_string(), "Ana".to_string());
+        assert!(templates::instantiate(&far, &all_vars, anchor).is_err());
+
+        vars.insert("contact".to_string(), "Ana".to_string());
+        let instances = templates::instantiate(&template, &vars, anchor).unwrap();
+        let created = db.create_tasks_with_checklists(instances).await
+            .expect("Failed to instantiate template");
+        assert_eq!(created.len(), 2);
+
+        let call = created.iter().find(|t| t.title == "Kickoff call with ACME").unwrap();
+        assert_eq!(call.due_date.as_deref(), Some("2025-06-02T09:00:00+02:00"));
+        assert_eq!(call.tags, vec!["onboarding"]);
+        assert_eq!(call.category_id, Some(1));
+        let pack = created.iter().find(|t| t.title == "Send welcome pack").unwrap();
+        assert_eq!(pack.due_date.as_deref(), Some("2025-06-04T17:00:00+02:00"));
+
+        let checklist = db.get_checklist(Some(call.id)).await.expect("Failed to get checklist");
+        assert_eq!(checklist.len(), 1);
+        assert_eq!(checklist[0].title, "Send agenda to Ana");
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_task() {
+        use crate::validation::Check;
+        
+        let (_app, db) = setup_test_database().await;
+        
+        let release = db.create_task(CreateTaskInput {
+            title: "Release 1.2".to_string(),
+            description: Some("Ship it".to_string()),
+            priority: Some("high".to_string()),
+            category_id: Some(1),
+            due_date: Some("2025-03-10T09:00:00+00:00".to_string()),
+            tags: vec!["release".to_string()],
+            status_id: None,
+            parent_id: None,
+        }).await.expect("Failed to create task");
+        let item = db.add_checklist_item(release.id, "Tag the commit").await
+            .expect("Failed to add checklist item");
+        db.update_checklist_item(UpdateChecklistItemInput { id: item.id, title: None, done: Some(true) }).await
+            .expect("Failed to update checklist item");
+        db.add_attachment(CreateAttachmentInput {
+            task_id: release.id,
+            kind: "url".to_string(),
+            target: "https://example.com/changelog".to_string(),
+            label: Some("Changelog".to_string()),
+        }).await.expect("Failed to add attachment");
+        
+        let notes = db.create_task(CreateTaskInput {
+            title: "Write release notes".to_string(),
+            description: None,
+            priority: None,
+            category_id: Some(1),
+            due_date: Some("2025-03-09T17:00:00+00:00".to_string()),
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: Some(release.id),
+        }).await.expect("Failed to create subtask");
+        db.transition_task(notes.id, 4).await.expect("Failed to complete subtask");
+        
+        let options = DuplicateTaskOptions {
+            title: Some("Release 1.3".to_string()),
+            shift_due_minutes: Some(7 * 24 * 60),
+            ..Default::default()
+        };
+        let copy = db.duplicate_task(release.id, &options).await
+            .expect("Failed to duplicate task")
+            .expect("Task should exist");
+        
+        assert_eq!(copy.title, "Release 1.3");
+        assert_eq!(copy.tags, vec!["release"]);
+        assert_eq!(copy.parent_id, None);
+        assert_eq!(copy.due_date, Some("2025-03-17T09:00:00+00:00".to_string()));
+        
+        // Shifts beyond the calendar are rejected instead of overflowing
+        let oversized = DuplicateTaskOptions { shift_due_minutes: Some(i64::MAX), ..Default::default() };
+        assert!(oversized.check().is_err());
+        let error = db.duplicate_task(release.id, &oversized).await.unwrap_err();
+        assert!(matches!(error, crate::error::AppError::Validation { .. }));
+        
+        let checklist = db.get_checklist(Some(copy.id)).await.expect("Failed to get checklist");
+        assert_eq!(checklist.len(), 1);
+        assert!(!checklist[0].done);
+        
+        let attachments = db.get_attachments(Some(copy.id)).await.expect("Failed to get attachments");
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].target, "https://example.com/changelog");
+        
+        let subtasks = db.get_tasks(Some(TaskFilters {
+            parent_id: Some(copy.id),
+            ..Default::default()
+        })).await.expect("Failed to get subtasks");
+        assert_eq!(subtasks.len(), 1);
+        assert_eq!(subtasks[0].title, "Write release notes");
+        assert!(!subtasks[0].completed);
+        assert_eq!(subtasks[0].due_date, Some("2025-03-16T17:00:00+00:00".to_string()));
+        
+        // Deleting the copy takes its subtasks and attachments along
+        db.delete_task(copy.id).await.expect("Failed to delete task");
+        assert!(db.get_task(subtasks[0].id).await.expect("Failed to get task").is_none());
+        assert!(db.get_attachments(Some(copy.id)).await.expect("Failed to get attachments").is_empty());
+        assert!(db.get_task(notes.id).await.expect("Failed to get task").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_task_links() {
+        use crate::links::{self, Reference};
+
+        let references: Vec<Reference> = links::find_references("See [[Draft spec]], #12 and #tag, not C#3 or [[ ]]")
+            .into_iter()
+            .map(|(_, reference)| reference)
+            .collect();
+        assert_eq!(references, vec![Reference::Title("Draft spec".to_string()), Reference::Id(12)]);
+
+        let (_app, db) = setup_test_database().await;
+        let input = |title: &str, description: Option<String>| CreateTaskInput {
+            title: title.to_string(),
+            description,
+            priority: None,
+            category_id: Some(1),
+            due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        };
+        
+        let spec = db.create_task(input("Draft spec", None)).await.expect("Failed to create task");
+        let review = db.create_task(input(
+            "Review",
+            Some(format!("Read [[draft spec]] and #{}, then [[Budget]]", spec.id)),
+        )).await.expect("Failed to create task");
+        
+        let backlinks = db.get_backlinks(spec.id).await.expect("Failed to get backlinks");
+        assert_eq!(backlinks.len(), 2);
+        assert!(backlinks.iter().all(|l| l.source_id == review.id));
+        
+        let dangling = db.get_dangling_links().await.expect("Failed to get dangling links");
+        assert_eq!(dangling.len(), 1);
+        assert_eq!(dangling[0].reference, "Budget");
+        
+        // Creating the missing task resolves the dangling link
+        let budget = db.create_task(input("Budget", None)).await.expect("Failed to create task");
+        assert!(db.get_dangling_links().await.expect("Failed to get dangling links").is_empty());
+        assert_eq!(db.get_backlinks(budget.id).await.expect("Failed to get backlinks").len(), 1);
+        
+        // Renaming rewrites the references that point at the task
+        db.update_task(UpdateTaskInput {
+            id: spec.id,
+            title: Patch::Value("Final spec".to_string()),
+            ..UpdateTaskInput::default()
+        }).await.expect("Failed to rename task");
+        let review = db.get_task(review.id).await.expect("Failed to get task").expect("Task should exist");
+        assert_eq!(
+            review.description,
+            Some(format!("Read [[Final spec]] and #{}, then [[Budget]]", spec.id))
+        );
+        assert_eq!(db.get_backlinks(spec.id).await.expect("Failed to get backlinks").len(), 2);
+        
+        // Deleting the target leaves the links dangling
+        db.delete_task(budget.id).await.expect("Failed to delete task");
+        let links = db.get_links(review.id).await.expect("Failed to get links");
+        assert_eq!(links.len(), 3);
+        assert_eq!(links.iter().filter(|l| l.target_id.is_none()).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_custom_fields() {
+        use crate::custom_fields;
+
+        let (_app, db) = setup_test_database().await;
+        
+        let points = db.create_custom_field(CreateCustomFieldInput {
+            name: "Story points".to_string(),
+            field_type: "number".to_string(),
+            options: Vec::new(),
+            category_id: None,
+        }).await.expect("Failed to create field");
+        let client = db.create_custom_field(CreateCustomFieldInput {
+            name: "Client".to_string(),
+            field_type: "select".to_string(),
+            options: vec!["ACME".to_string(), "Globex".to_string()],
+            category_id: Some(1),
+        }).await.expect("Failed to create field");
+        
+        assert_eq!(custom_fields::normalize_value(&points, " 5.0 "), Ok("5".to_string()));
+        assert!(custom_fields::normalize_value(&points, "five").is_err());
+        assert_eq!(custom_fields::normalize_value(&client, "acme"), Ok("ACME".to_string()));
+        assert!(custom_fields::normalize_value(&client, "Initech").is_err());
+        assert!(custom_fields::check_scope(&client, None).is_err());
+        assert!(custom_fields::check_options("select", &[]).is_err());
+        
+        let mut ids = Vec::new();
+        for (title, estimate, customer) in [("Small", "2", Some("ACME")), ("Large", "13", Some("Globex")), ("Medium", "8", None)] {
+            let task = db.create_task(CreateTaskInput {
+                title: title.to_string(),
+                description: None,
+                priority: None,
+                category_id: Some(1),
+                due_date: None,
+                tags: Vec::new(),
+                status_id: None,
+                parent_id: None,
+            }).await.expect("Failed to create task");
+            db.set_task_field_value(task.id, points.id, Some(estimate)).await.expect("Failed to set value");
+            if let Some(customer) = customer {
+                db.set_task_field_value(task.id, client.id, Some(customer)).await.expect("Failed to set value");
+            }
+            ids.push(task.id);
+        }
+        
+        let task = db.get_task(ids[0]).await.expect("Failed to get task").expect("Task should exist");
+        assert_eq!(task.custom_fields.get(&points.id), Some(&"2".to_string()));
+        
+        // Numbers sort numerically, not as text
+        let sorted = db.get_tasks(Some(TaskFilters {
+            sort_by: Some(TaskSortKey::CustomField(points.id)),
+            sort_descending: Some(true),
+            ..Default::default()
+        })).await.expect("Failed to get tasks");
+        let titles: Vec<&str> = sorted.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["Large", "Medium", "Small"]);
+        
+        let filtered = db.get_tasks(Some(TaskFilters {
+            custom_fields: Some(vec![CustomFieldFilter {
+                field_id: points.id,
+                equals: None,
+                min: Some("3".to_string()),
+                max: None,
+            }]),
+            ..Default::default()
+        })).await.expect("Failed to get tasks");
+        assert_eq!(filtered.len(), 2);
+        
+        let filtered = db.get_tasks(Some(TaskFilters {
+            custom_fields: Some(vec![CustomFieldFilter {
+                field_id: client.id,
+                equals: Some("acme".to_string()),
+                min: None,
+                max: None,
+            }]),
+            ..Default::default()
+        })).await.expect("Failed to get tasks");
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].id, ids[0]);
+        
+        db.delete_custom_field(client.id).await.expect("Failed to delete field");
+        let task = db.get_task(ids[0]).await.expect("Failed to get task").expect("Task should exist");
+        assert_eq!(task.custom_fields.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_priority_scale() {
+        let (_app, db) = setup_test_database().await;
+        
+        let urgent = db.create_priority(CreatePriorityInput {
+            name: "urgent".to_string(),
+            weight: 40,
+            color: Some("#DC2626".to_string()),
+            icon: Some("alert".to_string()),
+            is_default: false,
+        }).await.expect("Failed to create priority");
+        
+        let names: Vec<String> = db.get_priorities().await.expect("Failed to get priorities")
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["urgent", "high", "medium", "low"]);
+        
+        for (title, priority) in [("Later", Some("low")), ("Now", Some("Urgent")), ("Soon", Some("high")), ("Someday", None)] {
+            db.create_task(CreateTaskInput {
+                title: title.to_string(),
+                description: None,
+                priority: priority.map(str::to_string),
+                category_id: Some(1),
+                due_date: None,
+                tags: Vec::new(),
+                status_id: None,
+                parent_id: None,
+            }).await.expect("Failed to create task");
+        }
+        
+        // Sorting follows weights, not names
+        let tasks = db.get_tasks(Some(TaskFilters {
+            sort_by: Some(TaskSortKey::Priority),
+            sort_descending: Some(true),
+            ..Default::default()
+        })).await.expect("Failed to get tasks");
+        let titles: Vec<&str> = tasks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["Now", "Soon", "Someday", "Later"]);
+        assert_eq!(tasks[0].priority, "urgent");
+        assert_eq!(tasks[2].priority, "medium");
+        
+        // Renaming carries over to tasks; deleting reassigns them
+        db.update_priority(UpdatePriorityInput {
+            id: urgent.id,
+            name: Some("P0".to_string()),
+            weight: None,
+            color: None,
+            icon: None,
+            is_default: None,
+        }).await.expect("Failed to update priority");
+        let now = db.get_tasks(Some(TaskFilters {
+            priority: Some("p0".to_string()),
+            ..Default::default()
+        })).await.expect("Failed to get tasks");
+        assert_eq!(now.len(), 1);
+        assert_eq!(now[0].priority, "P0");
+        
+        db.delete_priority(urgent.id, Some(3)).await.expect("Failed to delete priority");
+        let task = db.get_task(now[0].id).await.expect("Failed to get task").expect("Task should exist");
+        assert_eq!(task.priority, "high");
+    }
+
+    #[tokio::test]
+    async fn test_daily_plan() {
+        use crate::planning;
+
+        let (_app, db) = setup_test_database().await;
+        
+        let mut ids = Vec::new();
+        for title in ["Write report", "Review PR", "Call supplier"] {
+            let task = db.create_task(CreateTaskInput {
+                title: title.to_string(),
+                description: None,
+                priority: None,
+                category_id: Some(1),
+                due_date: None,
+                tags: Vec::new(),
+                status_id: None,
+                parent_id: None,
+            }).await.expect("Failed to create task");
+            ids.push(task.id);
+        }
+        
+        let monday = "2025-03-10";
+        db.add_to_plan(monday, ids[0], Some(240), None).await.expect("Failed to plan task");
+        db.add_to_plan(monday, ids[1], Some(60), None).await.expect("Failed to plan task");
+        db.add_to_plan(monday, ids[2], None, Some(0)).await.expect("Failed to plan task");
+        db.set_plan_capacity(monday, Some(240)).await.expect("Failed to set capacity");
+        
+        let capacity = db.get_plan_capacity(monday).await.expect("Failed to get capacity").unwrap_or(planning::DEFAULT_CAPACITY_MINUTES);
+        let plan = planning::build(monday.to_string(), capacity, db.get_plan_items(monday).await.expect("Failed to get plan"));
+        let order: Vec<i64> = plan.items.iter().map(|i| i.task.id).collect();
+        assert_eq!(order, vec![ids[2], ids[0], ids[1]]);
+        assert_eq!(plan.planned_minutes, 300);
+        assert_eq!(plan.unestimated, 1);
+        assert_eq!(plan.free_minutes, -60);
+        assert!(plan.over_capacity);
+        
+        db.reorder_plan(monday, &[ids[1]]).await.expect("Failed to reorder plan");
+        db.remove_from_plan(monday, ids[2]).await.expect("Failed to remove task");
+        db.transition_task(ids[1], 4).await.expect("Failed to complete task");
+        
+        let items = db.get_plan_items(monday).await.expect("Failed to get plan");
+        let order: Vec<i64> = items.iter().map(|i| i.task.id).collect();
+        assert_eq!(order, vec![ids[1], ids[0]]);
+        assert_eq!(planning::build(monday.to_string(), 480, items).completed_minutes, 60);
+        
+        // Only the unfinished task moves on, and only once
+        let tuesday = "2025-03-11";
+        assert_eq!(db.carry_over_plan(tuesday).await.expect("Failed to carry over"), 1);
+        db.remove_from_plan(tuesday, ids[0]).await.expect("Failed to remove task");
+        assert_eq!(db.carry_over_plan(tuesday).await.expect("Failed to carry over"), 0);
+        assert!(db.get_plan_items(tuesday).await.expect("Failed to get plan").is_empty());
+        
+        // Removing it from Tuesday keeps it out of Wednesday as well
+        assert_eq!(db.carry_over_plan("2025-03-12").await.expect("Failed to carry over"), 0);
+        
+        db.add_to_plan("2025-03-12", ids[0], None, None).await.expect("Failed to plan task");
+        db.carry_over_plan("2025-03-13").await.expect("Failed to carry over");
+        let items = db.get_plan_items("2025-03-13").await.expect("Failed to get plan");
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].carried_from, Some("2025-03-12".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_database_encryption() {
+        let test_app = TestApp::new();
+        let path = test_app.app_dir.join("secret.db");
+        let header = |path: &std::path::Path| std::fs::read(path).expect("Failed to read file")[..15].to_vec();
+        
+        let db = Database::open(&path).await.expect("Failed to open database");
+        db.create_category(CreateCategoryInput { name: "Private".to_string(), color: None }).await
+            .expect("Failed to create category");
+        let categories = db.get_categories().await.expect("Failed to get categories").len();
+        assert!(!db.is_encrypted());
+        
+        let db = db.set_passphrase(Some("correct horse")).await.expect("Failed to encrypt database");
+        assert!(db.is_encrypted());
+        assert_eq!(db.get_categories().await.expect("Failed to get categories").len(), categories);
+        assert!(db.verify_passphrase("correct horse").await.expect("Failed to verify passphrase"));
+        assert!(!db.verify_passphrase("wrong horse").await.expect("Failed to verify passphrase"));
+        drop(db);
+        assert_ne!(header(&path), b"SQLite format 3");
+        
+        // The file cannot be opened without the right passphrase
+        assert!(Database::open(&path).await.is_err());
+        assert!(Database::open_with_passphrase(&path, Some("wrong horse")).await.is_err());
+        let db = Database::open_with_passphrase(&path, Some("correct horse")).await
+            .expect("Failed to unlock database");
+        
+        let db = db.set_passphrase(Some("battery staple")).await.expect("Failed to change passphrase");
+        drop(db);
+        assert!(Database::open_with_passphrase(&path, Some("correct horse")).await.is_err());
+        let db = Database::open_with_passphrase(&path, Some("battery staple")).await
+            .expect("Failed to unlock database");
+        
+        let db = db.set_passphrase(None).await.expect("Failed to decrypt database");
+        assert!(!db.is_encrypted());
+        drop(db);
+        assert_eq!(header(&path), b"SQLite format 3");
+        let db = Database::open(&path).await.expect("Failed to open database");
+        assert_eq!(db.get_categories().await.expect("Failed to get categories").len(), categories);
+    }
+
+    #[tokio::test]
+    async fn test_schema_migrations() {
+        use crate::schema;
+        use sqlx::migrate::Migrator;
+        
+        let test_app = TestApp::new();
+        let path = test_app.app_dir.join("upgrade.db");
+        let latest = schema::latest_version();
+        
+        // A database left behind by the previous release
+        let older_dir = test_app.app_dir.join("older_migrations");
+        std::fs::create_dir_all(&older_dir).expect("Failed to create dir");
+        let mut files: Vec<_> = std::fs::read_dir(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("migrations"))
+            .expect("Failed to read migrations")
+            .map(|entry| entry.expect("Failed to read entry").path())
+            .collect();
+        files.sort();
+        for file in &files[..files.len() - 1] {
+            std::fs::copy(file, older_dir.join(file.file_name().unwrap())).expect("Failed to copy migration");
+        }
+        let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}?mode=rwc", path.display())).await
+            .expect("Failed to connect");
+        Migrator::new(older_dir.as_path()).await.expect("Failed to load migrations")
+            .run(&pool).await.expect("Failed to run migrations");
+        pool.close().await;
+        
+        let db = Database::open(&path).await.expect("Failed to upgrade database");
+        let status = db.migration_status().await.expect("Failed to get migration status");
+        assert_eq!(status.current_version, latest);
+        assert!(status.pending.is_empty());
+        assert_eq!(status.snapshots.len(), 1);
+        
+        // The snapshot still has the old schema
+        let snapshot = sqlx::SqlitePool::connect(&format!("sqlite:{}", status.snapshots[0])).await
+            .expect("Failed to open snapshot");
+        let version: i64 = sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations")
+            .fetch_one(&snapshot).await.expect("Failed to read snapshot");
+        assert!(version < latest);
+        snapshot.close().await;
+        
+        // Up to date databases are opened without a new snapshot
+        drop(db);
+        let db = Database::open(&path).await.expect("Failed to open database");
+        assert_eq!(db.migration_status().await.expect("Failed to get migration status").snapshots.len(), 1);
+        
+        // A schema from a newer release is refused
+        sqlx::query("INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (?, 'future', 1, x'00', 0)")
+            .bind(latest + 1)
+            .execute(db.pool()).await.expect("Failed to fake a newer schema");
+        drop(db);
+        let error = Database::open(&path).await.err().expect("Opened a newer schema");
+        assert!(error.to_string().contains("Update Yuflow"));
+    }
+
+    #[tokio::test]
+    async fn test_database_integrity() {
+        use crate::backup::BackupManager;
+        
+        let (test_app, db) = setup_test_database().await;
+        let category = db.create_category(CreateCategoryInput { name: "Work".to_string(), color: None }).await
+            .expect("Failed to create category");
+        let task = db.create_task(CreateTaskInput {
+            title: "Quarterly report".to_string(),
+            description: None,
+            priority: Some("high".to_string()),
+            category_id: Some(category.id),
+            due_date: None,
+            tags: vec!["finance".to_string()],
+            status_id: None,
+            parent_id: None,
+        }).await.expect("Failed to create task");
+        
+        let report = db.check_integrity().await.expect("Failed to check database");
+        assert!(report.file_ok);
+        assert!(report.issues.is_empty());
+        
+        // Restores used to keep the old category IDs and fail on "General"
+        let manager = BackupManager::with_dir(test_app.app_dir.join("backups"))
+            .expect("Failed to create backup manager");
+        let backup = manager.create_backup(&db).await.expect("Failed to create backup");
+        manager.restore_backup(&backup.filename, &db).await.expect("Failed to restore backup");
+        let restored = db.get_tasks(None).await.expect("Failed to get tasks").remove(0);
+        let categories = db.get_categories().await.expect("Failed to get categories");
+        let restored_category = categories.iter().find(|c| Some(c.id) == restored.category_id)
+            .expect("Restored task lost its category");
+        assert_eq!(restored_category.name, "Work");
+        assert!(db.check_integrity().await.expect("Failed to check database").issues.is_empty());
+        
+        // Break the invariants behind the app's back
+        let mut connection = db.pool().acquire().await.expect("Failed to get connection");
+        sqlx::query("PRAGMA foreign_keys = OFF").execute(&mut *connection).await.expect("Failed to disable foreign keys");
+        sqlx::query("UPDATE tasks SET category_id = 999, priority_id = NULL, due_date = 'next tuesday' WHERE id = ?")
+            .bind(restored.id)
+            .execute(&mut *connection).await.expect("Failed to break task");
+        sqlx::query("INSERT INTO task_tags (task_id, tag_id) SELECT 999, id FROM tags")
+            .execute(&mut *connection).await.expect("Failed to add orphan tag");
+        sqlx::query("PRAGMA foreign_keys = ON").execute(&mut *connection).await.expect("Failed to enable foreign keys");
+        drop(connection);
+        
+        let report = db.check_integrity().await.expect("Failed to check database");
+        let kinds: Vec<IntegrityIssueKind> = report.issues.iter().map(|i| i.kind).collect();
+        assert!(kinds.contains(&IntegrityIssueKind::MissingCategory));
+        assert!(kinds.contains(&IntegrityIssueKind::InvalidPriority));
+        assert!(kinds.contains(&IntegrityIssueKind::InvalidDate));
+        assert!(kinds.contains(&IntegrityIssueKind::OrphanRow));
+        assert!(report.issues.iter().all(|i| i.repairable));
+        
+        let repaired = db.repair_integrity().await.expect("Failed to repair database");
+        assert!(repaired >= 4);
+        assert!(db.check_integrity().await.expect("Failed to check database").issues.is_empty());
+        
+        let fixed = db.get_task(restored.id).await.expect("Failed to get task").expect("Task not found");
+        assert_eq!(fixed.category_id, None);
+        assert_eq!(fixed.priority, "medium");
+        assert_eq!(fixed.due_date, None);
+        assert_eq!(fixed.title, task.title);
+    }
+
+    #[tokio::test]
+    async fn test_database_maintenance() {
+        let test_app = TestApp::new();
+        let db = Database::open(&test_app.app_dir.join("tuned.db")).await.expect("Failed to open database");
+        
+        let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode").fetch_one(db.pool()).await
+            .expect("Failed to read journal mode");
+        assert_eq!(journal_mode, "wal");
+        let foreign_keys: i64 = sqlx::query_scalar("PRAGMA foreign_keys").fetch_one(db.pool()).await
+            .expect("Failed to read foreign keys");
+        assert_eq!(foreign_keys, 1);
+        
+        let mut ids = Vec::new();
+        for i in 0..200 {
+            let task = db.create_task(CreateTaskInput {
+                title: format!("Task {}", i),
+                description: Some("x".repeat(2000)),
+                priority: None,
+                category_id: None,
+                due_date: None,
+                tags: vec!["bulk".to_string()],
+                status_id: None,
+                parent_id: None,
+            }).await.expect("Failed to create task");
+            ids.push(task.id);
+        }
+        for id in ids {
+            db.delete_task(id).await.expect("Failed to delete task");
+        }
+        
+        // Tags of deleted tasks go with them
+        let tag_rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM task_tags").fetch_one(db.pool()).await
+            .expect("Failed to count tags");
+        assert_eq!(tag_rows, 0);
+        
+        let before = db.size().await.expect("Failed to get size");
+        assert!(before.reclaimable_bytes > 0);
+        assert!(before.wal_bytes > 0);
+        
+        db.run_maintenance().await.expect("Failed to run maintenance");
+        let after = db.size().await.expect("Failed to get size");
+        assert_eq!(after.reclaimable_bytes, 0);
+        assert_eq!(after.wal_bytes, 0);
+        assert!(after.page_count < before.page_count);
+    }
+
+    // Runs the same script against any backend
+    async fn exercise_store(store: &dyn crate::store::TaskStore) {
+        let work = store.create_category(CreateCategoryInput {
+            name: "Work".to_string(),
+            color: None,
+        }).await.expect("Failed to create category");
+        
+        let new_task = |title: &str, priority: &str| CreateTaskInput {
+            title: title.to_string(),
+            description: None,
+            priority: Some(priority.to_string()),
+            category_id: Some(work.id),
+            due_date: None,
+            tags: vec![" Store ".to_string(), "store".to_string()],
+            status_id: None,
+            parent_id: None,
+        };
+        let parent = store.create_task(new_task("Write report", "low")).await.expect("Failed to create task");
+        assert_eq!(parent.tags, vec!["store".to_string()]);
+        let child = store.create_task(CreateTaskInput {
+            parent_id: Some(parent.id),
+            ..new_task("Collect numbers", "high")
+        }).await.expect("Failed to create subtask");
+        assert!(store.create_task(new_task("Book flights", "unknown")).await.is_err());
+        let other = store.create_task(CreateTaskInput {
+            priority: None,
+            ..new_task("Book flights", "high")
+        }).await.expect("Failed to create task");
+        assert_eq!(other.priority, "medium");
+        
+        let done = store.update_task(UpdateTaskInput {
+            id: other.id,
+            description: Patch::Value("Window seat".to_string()),
+            completed: Patch::Value(true),
+            ..UpdateTaskInput::default()
+        }).await.expect("Failed to update task").task;
+        assert!(done.completed);
+        assert!(done.completed_at.is_some());
+        assert_eq!(done.description.as_deref(), Some("Window seat"));
+        
+        let by_priority = store.get_tasks(Some(TaskFilters {
+            sort_by: Some(TaskSortKey::Priority),
+            sort_descending: Some(true),
+            ..Default::default()
+        })).await.expect("Failed to get tasks");
+        let titles: Vec<&str> = by_priority.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["Collect numbers", "Book flights", "Write report"]);
+        
+        let found = store.get_tasks(Some(TaskFilters {
+            search: Some("WINDOW".to_string()),
+            completed: Some(true),
+            ..Default::default()
+        })).await.expect("Failed to search tasks");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, other.id);
+        
+        // Due date bounds compare instants, whatever offset a date carries
+        for (id, due) in [(parent.id, "2025-05-01T09:00:00+02:00"), (other.id, "2025-05-01T08:30:00Z")] {
+            store.update_task(UpdateTaskInput {
+                id,
+                due_date: Patch::Value(due.to_string()),
+                ..UpdateTaskInput::default()
+            }).await.expect("Failed to set due date");
+        }
+        let due = store.get_tasks(Some(TaskFilters {
+            due_date_after: Some("2025-05-01T07:00:00Z".to_string()),
+            due_date_before: Some("2025-05-01T08:00:00Z".to_string()),
+            ..Default::default()
+        })).await.expect("Failed to get tasks");
+        assert_eq!(due.iter().map(|t| t.id).collect::<Vec<_>>(), vec![parent.id]);
+        
+        store.delete_category(work.id).await.expect("Failed to delete category");
+        let categories = store.get_categories().await.expect("Failed to get categories");
+        assert_eq!(categories.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["General"]);
+        let moved = store.get_task(other.id).await.expect("Failed to get task").expect("Task is gone");
+        assert_eq!(moved.category_id, None);
+        
+        store.delete_task(parent.id).await.expect("Failed to delete task");
+        assert!(store.get_task(child.id).await.expect("Failed to get task").is_none());
+        assert_eq!(store.get_tasks(None).await.expect("Failed to get tasks").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_task_store_backends() {
+        let (_app, db) = setup_test_database().await;
+        exercise_store(&db).await;
+        exercise_store(&crate::memory_store::MemoryStore::new()).await;
+        
+        // An in-memory database belongs to the handle that opened it
+        let other = Database::open_in_memory().await.expect("Failed to open database");
+        assert!(other.get_tasks(None).await.expect("Failed to get tasks").is_empty());
+        assert!(other.path().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_backup_between_files() {
+        let test_app = TestApp::new();
+        
+        // Only the crate's top-level API, the way tools outside the app use it
+        let source = crate::Database::open(&test_app.app_dir.join("source.db")).await
+            .expect("Failed to open database");
+        let task = crate::TaskStore::create_task(&source, CreateTaskInput {
+            title: "Ship the CLI".to_string(),
+            description: None,
+            priority: Some("high".to_string()),
+            category_id: Some(1),
+            due_date: None,
+            tags: vec!["tools".to_string()],
+            status_id: None,
+            parent_id: None,
+        }).await.expect("Failed to create task");
+        
+        let manager = crate::BackupManager::with_dir(test_app.app_dir.join("backups"))
+            .expect("Failed to create backup manager");
+        let backup = manager.create_backup(&source).await.expect("Failed to create backup");
+        assert_eq!(backup.task_count, 1);
+        
+        let target = crate::Database::open(&test_app.app_dir.join("target.db")).await
+            .expect("Failed to open database");
+        manager.restore_backup(&backup.filename, &target).await.expect("Failed to restore backup");
+        
+        let restored = target.get_tasks(None).await.expect("Failed to get tasks");
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].title, task.title);
+        assert_eq!(restored[0].priority, "high");
+        assert_eq!(restored[0].tags, vec!["tools".to_string()]);
+        assert_eq!(restored[0].category_id, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_app_errors() {
+        use crate::error::{AppError, Context};
+        use validator::Validate;
+        
+        // Every error reaches the frontend with a code and per-field messages
+        let invalid = CreateTaskInput {
+            title: String::new(),
+            description: None,
+            priority: Some(String::new()),
+            category_id: None,
+            due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        };
+        let error = AppError::from(invalid.validate().unwrap_err());
+        let json = serde_json::to_value(&error).expect("Failed to serialize error");
+        assert_eq!(json["code"], "validation");
+        assert_eq!(json["fields"]["title"][0], "Title must be between 1 and 255 characters");
+        assert_eq!(json["fields"]["priority"][0], "Priority must be between 1 and 50 characters");
+        
+        let locked = AppError::Locked("The app is locked".to_string());
+        assert_eq!(serde_json::to_value(&locked).unwrap(), serde_json::json!({
+            "code": "locked",
+            "message": "The app is locked",
+            "fields": {},
+        }));
+        
+        // Context only prefixes errors the user cannot fix
+        let storage: Result<(), sqlx::Error> = Err(sqlx::Error::PoolTimedOut);
+        let error = storage.context("Failed to get tasks").unwrap_err();
+        assert_eq!(error.code(), "storage");
+        assert!(error.to_string().starts_with("Failed to get tasks: "));
+        let required: Result<(), ModelValidationError> = Err(ModelValidationError::Required { field: "name".to_string() });
+        assert!(matches!(required.context("Failed to save").unwrap_err(), AppError::Validation { fields, .. } if fields.contains_key("name")));
+        
+        // Updating a task that does not exist is reported, not a panic
+        let db = Database::open_in_memory().await.expect("Failed to open database");
+        let missing = UpdateTaskInput {
+            id: 42,
+            title: Patch::Value("Ghost".to_string()),
+            ..UpdateTaskInput::default()
+        };
+        assert!(db.update_task(missing).await.expect("Failed to update task").is_none());
+        let duplicate = db.create_category(CreateCategoryInput {
+            name: "General".to_string(),
+            color: None,
+        }).await;
+        assert_eq!(AppError::from(duplicate.unwrap_err()).code(), "conflict");
+    }
+
+    #[tokio::test]
+    async fn test_validation_layer() {
+        use crate::error::AppError;
+        use crate::memory_store::MemoryStore;
+        use crate::store::TaskStore;
+        use crate::validation::Check;
+        
+        let fields = |error: AppError| match error {
+            AppError::Validation { fields, .. } => fields.into_keys().collect::<Vec<_>>(),
+            other => panic!("Expected a validation error, got {:?}", other),
+        };
+        
+        // Updates are held to the same field rules as new tasks, all at once
+        let update = UpdateTaskInput {
+            id: 1,
+            title: Patch::Value(String::new()),
+            due_date: Patch::Value("tomorrow".to_string()),
+            ..UpdateTaskInput::default()
+        };
+        assert_eq!(fields(update.check().unwrap_err()), vec!["due_date", "title"]);
+        
+        // Every bad field is reported, not only the first one found
+        let create = CreateTaskInput {
+            title: "   ".to_string(),
+            description: Some("x".repeat(1001)),
+            priority: Some(String::new()),
+            category_id: None,
+            due_date: Some("tomorrow".to_string()),
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
+        };
+        assert_eq!(fields(create.check().unwrap_err()), vec!["description", "due_date", "priority", "title"]);
+        let update = UpdateTaskInput {
+            id: 1,
+            title: Patch::Value(" \t".to_string()),
+            completed: Patch::Null,
+            due_date: Patch::Value("tomorrow".to_string()),
+            status_id: Patch::Null,
+            parent_id: Patch::Value(1),
+            ..UpdateTaskInput::default()
+        };
+        assert_eq!(
+            fields(update.check().unwrap_err()),
+            vec!["completed", "due_date", "parent_id", "status_id", "title"]
+        );
+        
+        // Rules across fields
+        let filters = TaskFilters {
+            due_date_after: Some("2024-06-30T00:00:00Z".to_string()),
+            due_date_before: Some("2024-06-01T00:00:00Z".to_string()),
+            ..TaskFilters::default()
+        };
+        assert_eq!(fields(filters.check().unwrap_err()), vec!["due_date_after"]);
+        let template = CreateTemplateInput {
+            name: "Release".to_string(),
+            description: None,
+            category_id: None,
+            tasks: vec![TemplateTask {
+                title: " ".to_string(),
+                description: None,
+                priority: None,
+                tags: Vec::new(),
+                checklist: Vec::new(),
+                due_offset_minutes: None,
+            }],
+        };
+        assert_eq!(fields(template.check().unwrap_err()), vec!["tasks"]);
+        
+        // References are checked in the transaction and reported together
+        let db = Database::open_in_memory().await.expect("Failed to open database");
+        let memory = MemoryStore::new();
+        let dangling = || CreateTaskInput {
+            title: "Dangling".to_string(),
+            description: None,
+            priority: Some("urgent".to_string()),
+            category_id: Some(99),
+            due_date: None,
+            tags: Vec::new(),
+            status_id: Some(99),
+            parent_id: Some(99),
+        };
+        for store in [&db as &dyn TaskStore, &memory] {
+            let error = store.create_task(dangling()).await.unwrap_err();
+            assert_eq!(fields(error), vec!["category_id", "parent_id", "priority", "status_id"]);
+            assert!(store.get_tasks(None).await.expect("Failed to get tasks").is_empty());
+        }
+        
+        let task = db.create_task(CreateTaskInput {
+            priority: Some("HIGH".to_string()),
+            category_id: Some(1),
+            status_id: None,
+            parent_id: None,
+            ..dangling()
+        }).await.expect("Failed to create task");
+        let moved = db.update_task(UpdateTaskInput {
+            id: task.id,
+            priority: Patch::Value("urgent".to_string()),
+            category_id: Patch::Value(99),
+            ..UpdateTaskInput::default()
+        }).await;
+        assert_eq!(fields(moved.unwrap_err()), vec!["category_id", "priority"]);
+        let unchanged = db.get_task(task.id).await.expect("Failed to get task").expect("Task not found");
+        assert_eq!(unchanged.category_id, Some(1));
+        
+        // A status must agree with `completed`, and a task cannot move under
+        // one of its own subtasks
+        for store in [&db as &dyn TaskStore, &memory] {
+            let parent = store.create_task(CreateTaskInput {
+                priority: None,
+                category_id: None,
+                status_id: None,
+                parent_id: None,
+                ..dangling()
+            }).await.expect("Failed to create task");
+            let child = store.create_task(CreateTaskInput {
+                priority: None,
+                category_id: None,
+                status_id: None,
+                parent_id: Some(parent.id),
+                ..dangling()
+            }).await.expect("Failed to create subtask");
+            
+            let conflicting = store.update_task(UpdateTaskInput {
+                id: parent.id,
+                completed: Patch::Value(false),
+                status_id: Patch::Value(4),
+                parent_id: Patch::Value(child.id),
+                ..UpdateTaskInput::default()
+            }).await.unwrap_err();
+            assert_eq!(fields(conflicting), vec!["parent_id", "status_id"]);
+            
+            // A status alone decides completion
+            let done = store.update_task(UpdateTaskInput {
+                id: child.id,
+                status_id: Patch::Value(4),
+                parent_id: Patch::Null,
+                ..UpdateTaskInput::default()
+            }).await.expect("Failed to update task").task;
+            assert!(done.completed);
+            assert_eq!(done.status_id, Some(4));
+            assert_eq!(done.parent_id, None);
+        }
+        
+        // Other writes that refer to rows report missing ones the same way
+        let error = db.add_checklist_item(99, "Orphan").await.unwrap_err();
+        assert_eq!(fields(error), vec!["task_id"]);
+        let error = db.add_attachment(CreateAttachmentInput {
+            task_id: 99,
+            kind: "url".to_string(),
+            target: "https://example.com".to_string(),
+            label: None,
+        }).await.unwrap_err();
+        assert_eq!(fields(error), vec!["task_id"]);
+        let error = db.set_task_field_value(99, 99, Some("3")).await.unwrap_err();
+        assert_eq!(fields(error), vec!["field_id", "task_id"]);
+        let error = db.add_to_plan("2025-03-10", 99, None, None).await.unwrap_err();
+        assert_eq!(fields(error), vec!["task_id"]);
+        assert!(db.get_plan_items("2025-03-10").await.expect("Failed to get plan").is_empty());
+        let error = db.delete_status(3, Some(99)).await.unwrap_err();
+        assert_eq!(fields(error), vec!["reassign_to"]);
+        assert_eq!(db.get_statuses().await.expect("Failed to get statuses").len(), 4);
+        let error = db.reorder_statuses(&[2, 99]).await.unwrap_err();
+        assert_eq!(fields(error), vec!["ids"]);
+        let error = db.delete_priority(1, Some(99)).await.unwrap_err();
+        assert_eq!(fields(error), vec!["reassign_to"]);
+        assert_eq!(db.get_priorities().await.expect("Failed to get priorities").len(), 3);
+        let error = db.transition_task(task.id, 99).await.unwrap_err();
+        assert_eq!(fields(error), vec!["status_id"]);
+        assert!(db.transition_task(999, 1).await.expect("Failed to transition task").is_none());
+        let error = db.create_custom_field(CreateCustomFieldInput {
+            name: "Estimate".to_string(),
+            field_type: "number".to_string(),
+            options: Vec::new(),
+            category_id: Some(99),
+        }).await.unwrap_err();
+        assert_eq!(fields(error), vec!["category_id"]);
+        let error = db.create_template(CreateTemplateInput {
+            category_id: Some(99),
+            ..template
+        }).await.unwrap_err();
+        assert_eq!(fields(error), vec!["category_id"]);
+    }
+
+    #[tokio::test]
+    async fn test_update_task_patches() {
+        use crate::memory_store::MemoryStore;
+        use crate::store::TaskStore;
+        use crate::validation::Check;
+        use serde_json::json;
+        
+        let db = Database::open_in_memory().await.expect("Failed to open database");
+        let memory = MemoryStore::new();
+        let mut outcomes = Vec::new();
+        
+        for store in [&db as &dyn TaskStore, &memory] {
+            let task = store.create_task(CreateTaskInput {
+                title: "Renew passport".to_string(),
+                description: Some("Photos first".to_string()),
+                priority: Some("high".to_string()),
+                category_id: Some(1),
+                due_date: Some("2024-07-01T09:00:00+00:00".to_string()),
+                tags: Vec::new(),
+                status_id: None,
+                parent_id: None,
+            }).await.expect("Failed to create task");
+            
+            // Left out keeps the value, null clears it
+            let clear: UpdateTaskInput = serde_json::from_value(json!({
+                "id": task.id,
+                "description": null,
+                "category_id": null,
+                "due_date": null,
+                "priority": null,
+            })).expect("Failed to parse update");
+            assert_eq!(clear.title, Patch::Absent);
+            assert_eq!(clear.due_date, Patch::Null);
+            
+            let update = store.update_task(clear).await.expect("Failed to update task");
+            assert_eq!(update.task.title, "Renew passport");
+            assert_eq!(update.task.description, None);
+            assert_eq!(update.task.category_id, None);
+            assert_eq!(update.task.due_date, None);
+            assert_eq!(update.task.priority, "medium");
+            let changed: Vec<&str> = update.changes.iter().map(|c| c.field.as_str()).collect();
+            assert_eq!(changed, vec!["description", "priority", "category_id", "due_date"]);
+            assert_eq!(update.changes[1], FieldChange {
+                field: "priority".to_string(),
+                before: json!("high"),
+                after: json!("medium"),
+            });
+            
+            // Values that are already current change nothing
+            let same = store.update_task(UpdateTaskInput {
+                id: task.id,
+                title: Patch::Value("Renew passport".to_string()),
+                due_date: Patch::Null,
+                ..UpdateTaskInput::default()
+            }).await.expect("Failed to update task");
+            assert!(same.changes.is_empty());
+            
+            // Both stores answer the same patches the same way: an empty one
+            // writes nothing, and priority names ignore case
+            let mut outcome = Vec::new();
+            for patch in [
+                UpdateTaskInput { id: task.id, ..UpdateTaskInput::default() },
+                UpdateTaskInput { id: task.id, priority: Patch::Value("HIGH".to_string()), ..UpdateTaskInput::default() },
+            ] {
+                let before = store.get_task(task.id).await.expect("Failed to get task").expect("Task not found");
+                let update = store.update_task(patch).await.expect("Failed to update task");
+                let changed: Vec<String> = update.changes.into_iter().map(|c| c.field).collect();
+                outcome.push((changed, update.task.priority, update.task.updated_at == before.updated_at));
+            }
+            let high = store.get_tasks(Some(TaskFilters {
+                priority: Some("HIGH".to_string()),
+                ..TaskFilters::default()
+            })).await.expect("Failed to get tasks");
+            assert_eq!(high.len(), 1);
+            outcomes.push(outcome);
+        }
+        assert_eq!(outcomes[0][0], (vec![], "medium".to_string(), true));
+        assert_eq!(outcomes[0][1].0, vec!["priority"]);
+        assert_eq!(outcomes[0][1].1, "high");
+        assert_eq!(outcomes[0], outcomes[1]);
+        
+        // Only provided fields are sent back, and a title cannot be cleared
+        let input = UpdateTaskInput {
+            id: 7,
+            due_date: Patch::Null,
+            ..UpdateTaskInput::default()
+        };
+        assert_eq!(serde_json::to_value(&input).unwrap(), json!({ "id": 7, "due_date": null }));
+        let untitled: UpdateTaskInput = serde_json::from_value(json!({ "id": 7, "title": null })).unwrap();
+        assert!(untitled.check().is_err());
+        
+        // An update without fields does not touch the row
+        let task = db.get_tasks(None).await.expect("Failed to get tasks").remove(0);
+        let untouched = db.update_task(UpdateTaskInput { id: task.id, ..UpdateTaskInput::default() })
+            .await
+            .expect("Failed to update task")
+            .expect("Task not found");
+        assert!(untouched.changes.is_empty());
+        assert_eq!(untouched.task.updated_at, task.updated_at);
+    }
+}
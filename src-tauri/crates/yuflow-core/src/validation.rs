@@ -0,0 +1,132 @@
+//! Checks inputs before they are stored. Each input type validates its own
+//! fields through its `#[validate]` attributes and can add rules across
+//! fields by implementing `Check`; references to other rows are checked by
+//! the database inside the transaction that writes. Every violation found
+//! is collected, so a form shows all of its problems at once.
+
+use std::collections::BTreeMap;
+use validator::{Validate, ValidationErrors};
+use crate::error::AppError;
+use crate::models::*;
+
+/// Messages by input field, gathered before anything is reported.
+#[derive(Debug, Default)]
+pub struct Violations {
+    fields: BTreeMap<String, Vec<String>>,
+}
+
+impl Violations {
+    pub fn add(&mut self, field: &str, message: impl Into<String>) {
+        let message = message.into();
+        let messages = self.fields.entry(field.to_string()).or_default();
+        if !messages.contains(&message) {
+            messages.push(message);
+        }
+    }
+
+    /// `Ok` when nothing was found, otherwise one error listing everything.
+    pub fn into_result(self) -> Result<(), AppError> {
+        if self.fields.is_empty() {
+            return Ok(());
+        }
+        let messages: Vec<&str> = self.fields.values().flatten().map(String::as_str).collect();
+        Err(AppError::Validation {
+            message: messages.join("; "),
+            fields: self.fields,
+        })
+    }
+}
+
+impl From<ValidationErrors> for Violations {
+    fn from(errors: ValidationErrors) -> Self {
+        let mut violations = Violations::default();
+        for (field, errors) in errors.field_errors() {
+            for error in errors {
+                // Custom validators only set a code, which holds the message
+                violations.add(field, error.message.as_deref().unwrap_or(&error.code));
+            }
+        }
+        violations
+    }
+}
+
+/// An input that is validated before it is stored.
+pub trait Check: Validate {
+    /// Rules that involve more than one field; none by default.
+    fn cross_field(&self, _violations: &mut Violations) {}
+
+    /// Every violation of the field rules and the cross-field rules.
+    fn violations(&self) -> Violations {
+        let mut violations = self.validate().err().map(Violations::from).unwrap_or_default();
+        self.cross_field(&mut violations);
+        violations
+    }
+
+    fn check(&self) -> Result<(), AppError> {
+        self.violations().into_result()
+    }
+}
+
+// Due dates may move about 100 years either way
+const MAX_SHIFT_MINUTES: i64 = 100 * 366 * 24 * 60;
+
+fn parse_date(date: &str) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    chrono::DateTime::parse_from_rfc3339(date).ok()
+}
+
+impl Check for CreateTaskInput {}
+impl Check for UpdateTaskInput {
+    fn cross_field(&self, violations: &mut Violations) {
+        if self.parent_id.value() == Some(&self.id) {
+            violations.add("parent_id", "A task cannot be its own parent");
+        }
+    }
+}
+
+impl Check for TaskFilters {
+    fn cross_field(&self, violations: &mut Violations) {
+        let after = self.due_date_after.as_deref().and_then(parse_date);
+        let before = self.due_date_before.as_deref().and_then(parse_date);
+        if let (Some(after), Some(before)) = (after, before) {
+            if after > before {
+                violations.add("due_date_after", "The due date range must not end before it starts");
+            }
+        }
+    }
+}
+
+impl Check for CreateCategoryInput {}
+impl Check for CreatePriorityInput {}
+impl Check for UpdatePriorityInput {}
+impl Check for CreateStatusInput {}
+impl Check for UpdateStatusInput {}
+impl Check for CreateCustomFieldInput {}
+impl Check for UpdateCustomFieldInput {}
+impl Check for UpdateChecklistItemInput {}
+impl Check for CreateAttachmentInput {}
+impl Check for DuplicateTaskOptions {
+    fn cross_field(&self, violations: &mut Violations) {
+        if self.shift_due_minutes.is_some_and(|minutes| minutes.abs() > MAX_SHIFT_MINUTES) {
+            violations.add("shift_due_minutes", "Due dates can only move within 100 years");
+        }
+    }
+}
+
+impl Check for CreateTemplateInput {
+    fn cross_field(&self, violations: &mut Violations) {
+        for (index, task) in self.tasks.iter().enumerate() {
+            if task.title.trim().is_empty() || task.title.len() > 255 {
+                violations.add("tasks", format!("Title of task {} must be between 1 and 255 characters", index + 1));
+            }
+            if task.title.starts_with(crate::encryption::SEALED_PREFIX) {
+                violations.add("tasks", format!("Title of task {} cannot start with '{}'", index + 1, crate::encryption::SEALED_PREFIX));
+            }
+            if task.due_offset_minutes.is_some_and(|offset| offset.abs() > MAX_SHIFT_MINUTES) {
+                violations.add("tasks", format!("Due offset of task {} must be within 100 years", index + 1));
+            }
+            if task.checklist.iter().any(|item| item.trim().is_empty() || item.len() > 255) {
+                violations.add("tasks", format!("Checklist items of task {} must be between 1 and 255 characters", index + 1));
+            }
+        }
+    }
+}
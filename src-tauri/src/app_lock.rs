@@ -0,0 +1,265 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use serde::{Deserialize, Serialize};
+use tauri::ipc::Invoke;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use yuflow_core::encryption;
+use yuflow_core::{AppError, Context};
+use crate::privacy::Vault;
+
+/// App-wide lock settings, next to the workspaces registry
+pub const LOCK_FILE: &str = "app_lock.json";
+
+/// Emitted when the app locks itself after being idle or suspended
+pub const LOCKED_EVENT: &str = "app://locked";
+
+/// Commands that keep working while the app is locked
+const ALLOWED_WHILE_LOCKED: [&str; 4] = ["get_lock_status", "unlock_app", "init_database", "get_database_location"];
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct LockConfig {
+    /// Argon2 hash of the passphrase; the lock is off without one
+    pub passphrase_hash: Option<String>,
+    /// Minutes without any command before the app locks; 0 never locks
+    pub idle_minutes: u32,
+    pub lock_on_suspend: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LockStatus {
+    pub enabled: bool,
+    pub locked: bool,
+    pub idle_minutes: u32,
+    pub lock_on_suspend: bool,
+}
+
+struct LockState {
+    path: Option<PathBuf>,
+    config: LockConfig,
+    locked: bool,
+    last_activity: Instant,
+}
+
+pub struct AppLock {
+    state: Mutex<LockState>,
+}
+
+impl Default for AppLock {
+    fn default() -> Self {
+        AppLock {
+            state: Mutex::new(LockState {
+                path: None,
+                config: LockConfig::default(),
+                locked: false,
+                last_activity: Instant::now(),
+            }),
+        }
+    }
+}
+
+impl AppLock {
+    /// Reads the settings at startup. An enabled lock starts out locked.
+    pub fn load(&self, path: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+        let config: LockConfig = if path.exists() {
+            serde_json::from_str(&std::fs::read_to_string(&path)?)?
+        } else {
+            LockConfig::default()
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state.locked = config.passphrase_hash.is_some();
+        state.config = config;
+        state.path = Some(path);
+        Ok(())
+    }
+
+    pub fn status(&self) -> LockStatus {
+        let state = self.state.lock().unwrap();
+        LockStatus {
+            enabled: state.config.passphrase_hash.is_some(),
+            locked: state.locked,
+            idle_minutes: state.config.idle_minutes,
+            lock_on_suspend: state.config.lock_on_suspend,
+        }
+    }
+
+    /// Lets `command` through unless the app is locked, and counts it as
+    /// activity.
+    pub fn check(&self, command: &str) -> Result<(), AppError> {
+        self.check_at(command, Instant::now())
+    }
+
+    pub fn check_at(&self, command: &str, now: Instant) -> Result<(), AppError> {
+        let mut state = self.state.lock().unwrap();
+        if is_idle(&state, now) {
+            state.locked = true;
+        }
+        if state.locked && !ALLOWED_WHILE_LOCKED.contains(&command) {
+            return Err(AppError::Locked("The app is locked".to_string()));
+        }
+        state.last_activity = now;
+        Ok(())
+    }
+
+    /// Locks the app; does nothing when no passphrase is set.
+    pub fn lock(&self) -> LockStatus {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.locked = state.config.passphrase_hash.is_some();
+        }
+        self.status()
+    }
+
+    /// Locks the app when it has been idle too long. Returns true when this
+    /// call locked it.
+    pub fn expire_idle(&self, now: Instant) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.locked && is_idle(&state, now) {
+            state.locked = true;
+            return true;
+        }
+        false
+    }
+
+    /// Locks the app after the system was suspended, if configured to.
+    pub fn suspended(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.locked && state.config.lock_on_suspend && state.config.passphrase_hash.is_some() {
+            state.locked = true;
+            return true;
+        }
+        false
+    }
+
+    pub fn unlock(&self, passphrase: &str) -> Result<LockStatus, AppError> {
+        self.verify(passphrase)?;
+        {
+            let mut state = self.state.lock().unwrap();
+            state.locked = false;
+            state.last_activity = Instant::now();
+        }
+        Ok(self.status())
+    }
+
+    /// Turns the lock on, or changes its passphrase when `current` matches.
+    pub fn set_passphrase(&self, current: Option<&str>, passphrase: &str) -> Result<LockStatus, AppError> {
+        encryption::check_passphrase(passphrase)?;
+        if self.status().enabled {
+            let current = current.ok_or_else(|| AppError::invalid_field("current", "The current passphrase is required"))?;
+            self.verify(current)?;
+        }
+
+        let hash = encryption::hash_passphrase(passphrase).map_err(AppError::Storage)?;
+        self.update(|config| config.passphrase_hash = Some(hash))
+    }
+
+    pub fn disable(&self, passphrase: &str) -> Result<LockStatus, AppError> {
+        self.verify(passphrase)?;
+        self.update(|config| config.passphrase_hash = None)
+    }
+
+    pub fn set_options(&self, idle_minutes: u32, lock_on_suspend: bool) -> Result<LockStatus, AppError> {
+        if idle_minutes > 24 * 60 {
+            return Err(AppError::invalid_field("idle_minutes", "Idle timeout must be at most a day"));
+        }
+        self.update(|config| {
+            config.idle_minutes = idle_minutes;
+            config.lock_on_suspend = lock_on_suspend;
+        })
+    }
+
+    // Hashing is slow on purpose, so it runs without holding the state
+    fn verify(&self, passphrase: &str) -> Result<(), AppError> {
+        let hash = self.state.lock().unwrap().config.passphrase_hash.clone();
+        match hash {
+            Some(hash) if encryption::verify_passphrase(passphrase, &hash) => Ok(()),
+            Some(_) => Err(AppError::invalid_field("passphrase", "Wrong passphrase")),
+            None => Err(AppError::Conflict("The app lock is not enabled".to_string())),
+        }
+    }
+
+    fn update(&self, change: impl FnOnce(&mut LockConfig)) -> Result<LockStatus, AppError> {
+        {
+            let mut state = self.state.lock().unwrap();
+            let mut config = state.config.clone();
+            change(&mut config);
+
+            if let Some(path) = &state.path {
+                let temp_path = path.with_extension("json.tmp");
+                std::fs::write(&temp_path, serde_json::to_string_pretty(&config)?)
+                    .and_then(|_| std::fs::rename(&temp_path, path))
+                    .context("Failed to save lock settings")?;
+            }
+
+            if config.passphrase_hash.is_none() {
+                state.locked = false;
+            }
+            state.config = config;
+            state.last_activity = Instant::now();
+        }
+        Ok(self.status())
+    }
+}
+
+fn is_idle(state: &LockState, now: Instant) -> bool {
+    state.config.passphrase_hash.is_some()
+        && state.config.idle_minutes > 0
+        && now.saturating_duration_since(state.last_activity) >= Duration::from_secs(state.config.idle_minutes as u64 * 60)
+}
+
+/// Whether the gap between two watcher ticks means the system slept. The
+/// monotonic clock stops during suspend on some platforms and the wall clock
+/// can be adjusted, so a large gap on either one counts.
+pub fn slept_between(monotonic_gap: Duration, wall_gap: Duration) -> bool {
+    let threshold = WATCH_INTERVAL * 2 + Duration::from_secs(30);
+    monotonic_gap > threshold || wall_gap > threshold
+}
+
+/// Wraps the command handler so that every command goes through the lock.
+pub fn guard<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke: Invoke<R>| {
+        let webview = invoke.message.webview_ref();
+        let checked = webview.state::<AppLock>().check(invoke.message.command());
+
+        match checked {
+            Ok(()) => handler(invoke),
+            Err(error) => {
+                // Private content stays locked after the app is unlocked
+                webview.state::<Vault>().lock();
+                invoke.resolver.reject(error);
+                true
+            }
+        }
+    }
+}
+
+/// Checks for idleness and suspend in the background so the frontend can
+/// show the lock screen without waiting for its next command.
+pub fn spawn_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+        let mut last_tick = (Instant::now(), SystemTime::now());
+
+        loop {
+            interval.tick().await;
+
+            let now = (Instant::now(), SystemTime::now());
+            let monotonic_gap = now.0.duration_since(last_tick.0);
+            let wall_gap = now.1.duration_since(last_tick.1).unwrap_or_default();
+            last_tick = now;
+
+            let lock = app.state::<AppLock>();
+            let locked = lock.expire_idle(now.0) || (slept_between(monotonic_gap, wall_gap) && lock.suspended());
+            if locked {
+                app.state::<Vault>().lock();
+                let _ = app.emit(LOCKED_EVENT, lock.status());
+            }
+        }
+    });
+}
@@ -1,23 +1,72 @@
 use tauri::State;
 use std::sync::Mutex;
-use crate::database::Database;
-use crate::models::*;
-use crate::backup::{BackupManager, BackupMetadata};
-use validator::Validate;
+use yuflow_core::database::Database;
+use yuflow_core::models::*;
+use yuflow_core::backup::{BackupManager, BackupMetadata};
+use crate::quick_add::{self, QuickAddPreview};
+use yuflow_core::statistics::{self, Statistics, StatisticsRange};
+use crate::focus::{FocusConfig, FocusEngine, FocusState};
+use yuflow_core::templates;
+use yuflow_core::custom_fields;
+use yuflow_core::planning::{self, DailyPlan};
+use crate::urgency::{ScoredTask, UrgencyCoefficients, UrgencyContext};
+use crate::app_lock::{AppLock, LockStatus};
+use crate::privacy::{self, Vault, VaultStatus};
+use crate::location::{DataLocation, DatabaseLocation};
+use yuflow_core::schema::MigrationStatus;
+use yuflow_core::{AppError, Context};
+use crate::workspaces::{WorkspaceInfo, Workspaces};
+use std::collections::HashMap;
+use yuflow_core::validation::Check;
 
 type DatabaseState<'a> = State<'a, Mutex<Option<Database>>>;
+type FocusEngineState<'a> = State<'a, FocusEngine>;
+type AppLockState<'a> = State<'a, AppLock>;
+type VaultState<'a> = State<'a, Vault>;
+
+fn not_initialized() -> AppError {
+    AppError::Storage("Database not initialized".to_string())
+}
+
+/// Resolves the user's offset east of UTC, falling back to the system timezone.
+fn resolve_offset(utc_offset_minutes: Option<i32>) -> Result<chrono::FixedOffset, AppError> {
+    match utc_offset_minutes {
+        Some(minutes) => chrono::FixedOffset::east_opt(minutes * 60)
+            .ok_or_else(|| AppError::invalid_field("utc_offset_minutes", "Invalid UTC offset")),
+        None => Ok(*chrono::Local::now().offset()),
+    }
+}
 
 #[tauri::command]
 pub async fn init_database(
     app_handle: tauri::AppHandle,
+    passphrase: Option<String>,
     db_state: DatabaseState<'_>,
-) -> Result<(), String> {
-    let database = Database::new(&app_handle)
+    focus: FocusEngineState<'_>,
+) -> Result<(), AppError> {
+    // The database stays open when the window reloads
+    if db_state.lock().unwrap().is_some() {
+        return Ok(());
+    }
+    
+    let path = DataLocation::database_path(&app_handle)
+        .context("Failed to initialize database")?;
+    let database = Database::open_with_passphrase(&path, passphrase.as_deref())
+        .await
+        .context("Failed to initialize database")?;
+    
+    database.apply_archive_policy()
         .await
-        .map_err(|e| format!("Failed to initialize database: {}", e))?;
+        .context("Failed to archive completed tasks")?;
+    
+    {
+        let mut db_guard = db_state.lock().unwrap();
+        *db_guard = Some(database.clone());
+    }
     
-    let mut db_guard = db_state.lock().unwrap();
-    *db_guard = Some(database);
+    focus.restore(&app_handle, &database)
+        .await
+        .context("Failed to restore focus session")?;
     
     Ok(())
 }
@@ -26,253 +75,2303 @@ pub async fn init_database(
 pub async fn get_tasks(
     filters: Option<TaskFilters>,
     db_state: DatabaseState<'_>,
-) -> Result<Vec<Task>, String> {
+    focus: FocusEngineState<'_>,
+    vault: VaultState<'_>,
+) -> Result<Vec<Task>, AppError> {
+    if let Some(filters) = &filters {
+        filters.check()?;
+    }
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    let urgency_order = filters
+        .as_ref()
+        .filter(|f| f.sort_by == Some(TaskSortKey::Urgency))
+        .map(|f| f.sort_descending.unwrap_or(false));
+    
+    let mut tasks = db.get_tasks(filters)
+        .await
+        .context("Failed to get tasks")?;
+    
+    if let Some(descending) = urgency_order {
+        urgency_context(&db, &focus).await?.sort(&mut tasks, descending);
+    }
+    vault.reveal_all(&mut tasks);
+    
+    Ok(tasks)
+}
+
+async fn urgency_context(db: &Database, focus: &FocusEngine) -> Result<UrgencyContext, AppError> {
+    let coefficients = UrgencyCoefficients::load(db)
+        .await
+        .context("Failed to get urgency settings")?;
+    let priorities = db.get_priorities()
+        .await
+        .context("Failed to get priorities")?;
+    let all_tasks = db.get_tasks(None)
+        .await
+        .context("Failed to get tasks")?;
+    
+    let state = focus.snapshot();
+    let active_task = state.task_id.filter(|_| state.is_running());
+    
+    Ok(UrgencyContext::new(coefficients, &priorities, &all_tasks, active_task, chrono::Utc::now()))
+}
+
+/// The most urgent open tasks with their scores.
+#[tauri::command]
+pub async fn get_next_actions(
+    limit: Option<usize>,
+    db_state: DatabaseState<'_>,
+    focus: FocusEngineState<'_>,
+    vault: VaultState<'_>,
+) -> Result<Vec<ScoredTask>, AppError> {
     let db = {
         let guard = db_state.lock().unwrap();
         guard
             .as_ref()
             .cloned()
-            .ok_or("Database not initialized")?
+            .ok_or_else(not_initialized)?
     };
     
-    db.get_tasks(filters)
+    let context = urgency_context(&db, &focus).await?;
+    let mut tasks = db.get_tasks(None)
         .await
-        .map_err(|e| format!("Failed to get tasks: {}", e))
+        .context("Failed to get tasks")?;
+    vault.reveal_all(&mut tasks);
+    
+    Ok(context.next_actions(tasks, limit.unwrap_or(10)))
+}
+
+#[tauri::command]
+pub async fn get_urgency_config(
+    db_state: DatabaseState<'_>,
+) -> Result<UrgencyCoefficients, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    UrgencyCoefficients::load(&db)
+        .await
+        .context("Failed to get urgency settings")
+}
+
+#[tauri::command]
+pub async fn set_urgency_config(
+    config: UrgencyCoefficients,
+    db_state: DatabaseState<'_>,
+) -> Result<(), AppError> {
+    // Validate input
+    config.validate()?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    let json = serde_json::to_string(&config)
+        .context("Failed to save urgency settings")?;
+    db.set_setting(crate::urgency::URGENCY_SETTING, &json)
+        .await
+        .context("Failed to save urgency settings")
 }
 
 #[tauri::command]
 pub async fn get_task(
     id: i64,
     db_state: DatabaseState<'_>,
-) -> Result<Option<Task>, String> {
+    vault: VaultState<'_>,
+) -> Result<Option<Task>, AppError> {
     let db = {
         let guard = db_state.lock().unwrap();
         guard
             .as_ref()
             .cloned()
-            .ok_or("Database not initialized")?
+            .ok_or_else(not_initialized)?
     };
     
     db.get_task(id)
         .await
-        .map_err(|e| format!("Failed to get task: {}", e))
+        .map(|task| task.map(|task| vault.revealed(task)))
+        .context("Failed to get task")
 }
 
 #[tauri::command]
 pub async fn create_task(
-    task: CreateTaskInput,
+    mut task: CreateTaskInput,
     db_state: DatabaseState<'_>,
-) -> Result<Task, String> {
+    vault: VaultState<'_>,
+) -> Result<Task, AppError> {
     // Validate input
-    task.validate()
-        .map_err(|e| format!("Validation error: {}", e))?;
+    task.check()?;
     
     let db = {
         let guard = db_state.lock().unwrap();
         guard
             .as_ref()
             .cloned()
-            .ok_or("Database not initialized")?
+            .ok_or_else(not_initialized)?
     };
     
+    // Tasks of a private category are stored encrypted from the start
+    if db.is_category_private(task.category_id)
+        .await
+        .context("Failed to get category")? {
+        task.title = vault.seal(&task.title)?;
+        task.description = task.description.map(|d| vault.seal(&d)).transpose()?;
+    }
+    
     db.create_task(task)
         .await
-        .map_err(|e| format!("Failed to create task: {}", e))
+        .map(|task| vault.revealed(task))
+        .context("Failed to create task")
+}
+
+/// Parses a quick-add line into a task preview without saving it.
+/// `utc_offset_minutes` is the user's offset east of UTC and defaults to the
+/// system timezone.
+#[tauri::command]
+pub async fn quick_add(
+    text: String,
+    utc_offset_minutes: Option<i32>,
+    db_state: DatabaseState<'_>,
+) -> Result<QuickAddPreview, AppError> {
+    let now = chrono::Utc::now().with_timezone(&resolve_offset(utc_offset_minutes)?);
+
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    let categories = db.get_categories()
+        .await
+        .context("Failed to get categories")?;
+    let priorities: Vec<String> = db.get_priorities()
+        .await
+        .context("Failed to get priorities")?
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+    
+    Ok(quick_add::build_preview(quick_add::parse(&text, now, &priorities), &categories))
+}
+
+#[tauri::command]
+pub async fn get_statistics(
+    range: StatisticsRange,
+    db_state: DatabaseState<'_>,
+) -> Result<Statistics, AppError> {
+    let parse_day = |value: &str| chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|e| AppError::validation(format!("Invalid date '{}': {}", value, e)));
+    let from = parse_day(&range.from)?;
+    let to = parse_day(&range.to)?;
+    if from > to {
+        return Err(AppError::invalid_field("from", "Range start must not be after its end"));
+    }
+    let now = chrono::Utc::now().with_timezone(&resolve_offset(range.utc_offset_minutes)?);
+
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    let tasks = db.get_tasks(Some(TaskFilters {
+        include_archived: Some(true),
+        ..Default::default()
+    }))
+        .await
+        .context("Failed to get tasks")?;
+    let categories = db.get_categories()
+        .await
+        .context("Failed to get categories")?;
+    let sessions = db.get_focus_sessions(None)
+        .await
+        .context("Failed to get focus sessions")?;
+    
+    Ok(statistics::compute(&tasks, &categories, &sessions, from, to, range.granularity, now))
 }
 
 #[tauri::command]
 pub async fn update_task(
-    task: UpdateTaskInput,
+    mut task: UpdateTaskInput,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<TaskUpdate, AppError> {
+    // Validate input
+    task.check()?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    let current = db.get_task(task.id)
+        .await
+        .context("Failed to get task")?
+        .ok_or_else(|| AppError::NotFound(format!("Task {} not found", task.id)))?;
+    let sealed = privacy::is_sealed(&current.title);
+    
+    // Edits to a private task are encrypted like the rest of it
+    if sealed {
+        if let Patch::Value(title) = &mut task.title {
+            *title = vault.seal(title)?;
+        }
+        if let Patch::Value(description) = &mut task.description {
+            *description = vault.seal(description)?;
+        }
+    }
+    
+    // Moving into or out of a private category needs the key as well
+    let moved = task.category_id.differs_from(current.category_id.as_ref());
+    if moved && !vault.is_unlocked() {
+        let private = current.is_private || db.is_category_private(task.category_id.value().copied())
+            .await
+            .context("Failed to get category")?;
+        if private != sealed {
+            return Err(privacy::locked());
+        }
+    }
+    
+    let id = task.id;
+    let updated = db.update_task(task)
+        .await
+        .context("Failed to update task")?
+        .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))?
+        .task;
+    let updated = match moved {
+        true => vault.sync_task(&db, updated.id).await?.unwrap_or(updated),
+        false => updated,
+    };
+    
+    // Sealing uses a new nonce every time, so private text is compared as
+    // the user sees it
+    Ok(TaskUpdate::between(&vault.revealed(current), vault.revealed(updated)))
+}
+
+#[tauri::command]
+pub async fn archive_task(
+    id: i64,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<Option<Task>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.set_task_archived(id, true)
+        .await
+        .map(|task| task.map(|task| vault.revealed(task)))
+        .context("Failed to archive task")
+}
+
+#[tauri::command]
+pub async fn unarchive_task(
+    id: i64,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<Option<Task>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.set_task_archived(id, false)
+        .await
+        .map(|task| task.map(|task| vault.revealed(task)))
+        .context("Failed to unarchive task")
+}
+
+/// Runs the auto-archive policy now and returns how many tasks were archived.
+#[tauri::command]
+pub async fn apply_archive_policy(
     db_state: DatabaseState<'_>,
-) -> Result<Task, String> {
+) -> Result<u64, AppError> {
     let db = {
         let guard = db_state.lock().unwrap();
         guard
             .as_ref()
             .cloned()
-            .ok_or("Database not initialized")?
+            .ok_or_else(not_initialized)?
     };
     
-    db.update_task(task)
+    db.apply_archive_policy()
         .await
-        .map_err(|e| format!("Failed to update task: {}", e))
+        .context("Failed to archive completed tasks")
 }
 
 #[tauri::command]
 pub async fn delete_task(
     id: i64,
     db_state: DatabaseState<'_>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let db = {
         let guard = db_state.lock().unwrap();
         guard
             .as_ref()
             .cloned()
-            .ok_or("Database not initialized")?
+            .ok_or_else(not_initialized)?
     };
     
     db.delete_task(id)
         .await
-        .map_err(|e| format!("Failed to delete task: {}", e))
+        .context("Failed to delete task")
 }
 
 #[tauri::command]
-pub async fn get_categories(
+pub async fn duplicate_task(
+    id: i64,
+    options: Option<DuplicateTaskOptions>,
     db_state: DatabaseState<'_>,
-) -> Result<Vec<Category>, String> {
+    vault: VaultState<'_>,
+) -> Result<Option<Task>, AppError> {
+    let mut options = options.unwrap_or_default();
+    
+    // Validate input
+    options.check()?;
+    
     let db = {
         let guard = db_state.lock().unwrap();
         guard
             .as_ref()
             .cloned()
-            .ok_or("Database not initialized")?
+            .ok_or_else(not_initialized)?
     };
     
-    db.get_categories()
+    // A new title for a copy of a private task is encrypted like the original
+    if let Some(title) = &options.title {
+        let original = db.get_task(id)
+            .await
+            .context("Failed to get task")?;
+        if original.is_some_and(|t| privacy::is_sealed(&t.title)) {
+            options.title = Some(vault.seal(title)?);
+        }
+    }
+    
+    db.duplicate_task(id, &options)
         .await
-        .map_err(|e| format!("Failed to get categories: {}", e))
+        .map(|task| task.map(|task| vault.revealed(task)))
+        .context("Failed to duplicate task")
 }
 
 #[tauri::command]
-pub async fn create_category(
-    category: CreateCategoryInput,
+pub async fn transition_task(
+    id: i64,
+    status_id: i64,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<Option<Task>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.transition_task(id, status_id)
+        .await
+        .map(|task| task.map(|task| vault.revealed(task)))
+        .context("Failed to move task")
+}
+
+// Priority commands
+#[tauri::command]
+pub async fn get_priorities(
     db_state: DatabaseState<'_>,
-) -> Result<Category, String> {
+) -> Result<Vec<Priority>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.get_priorities()
+        .await
+        .context("Failed to get priorities")
+}
+
+#[tauri::command]
+pub async fn create_priority(
+    priority: CreatePriorityInput,
+    db_state: DatabaseState<'_>,
+) -> Result<Priority, AppError> {
     // Validate input
-    category.validate()
-        .map_err(|e| format!("Validation error: {}", e))?;
+    priority.check()?;
     
     let db = {
         let guard = db_state.lock().unwrap();
         guard
             .as_ref()
             .cloned()
-            .ok_or("Database not initialized")?
+            .ok_or_else(not_initialized)?
     };
     
-    db.create_category(category)
+    db.create_priority(priority)
         .await
-        .map_err(|e| format!("Failed to create category: {}", e))
+        .context("Failed to create priority")
 }
 
 #[tauri::command]
-pub async fn delete_category(
+pub async fn update_priority(
+    priority: UpdatePriorityInput,
+    db_state: DatabaseState<'_>,
+) -> Result<Priority, AppError> {
+    // Validate input
+    priority.check()?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.update_priority(priority)
+        .await
+        .context("Failed to update priority")
+}
+
+#[tauri::command]
+pub async fn delete_priority(
     id: i64,
+    reassign_to: Option<i64>,
     db_state: DatabaseState<'_>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let db = {
         let guard = db_state.lock().unwrap();
         guard
             .as_ref()
             .cloned()
-            .ok_or("Database not initialized")?
+            .ok_or_else(not_initialized)?
     };
     
-    db.delete_category(id)
+    let priorities = db.get_priorities()
+        .await
+        .context("Failed to get priorities")?;
+    if priorities.len() <= 1 {
+        return Err(AppError::Conflict("The last priority cannot be deleted".to_string()));
+    }
+    
+    db.delete_priority(id, reassign_to)
         .await
-        .map_err(|e| format!("Failed to delete category: {}", e))
+        .context("Failed to delete priority")
 }
 
+// Status commands
 #[tauri::command]
-pub async fn get_setting(
-    key: String,
+pub async fn get_statuses(
     db_state: DatabaseState<'_>,
-) -> Result<Option<String>, String> {
+) -> Result<Vec<TaskStatus>, AppError> {
     let db = {
         let guard = db_state.lock().unwrap();
         guard
             .as_ref()
             .cloned()
-            .ok_or("Database not initialized")?
+            .ok_or_else(not_initialized)?
     };
     
-    db.get_setting(&key)
+    db.get_statuses()
         .await
-        .map_err(|e| format!("Failed to get setting: {}", e))
+        .context("Failed to get statuses")
 }
 
 #[tauri::command]
-pub async fn set_setting(
-    key: String,
-    value: String,
+pub async fn create_status(
+    status: CreateStatusInput,
     db_state: DatabaseState<'_>,
-) -> Result<(), String> {
+) -> Result<TaskStatus, AppError> {
+    // Validate input
+    status.check()?;
+    
     let db = {
         let guard = db_state.lock().unwrap();
         guard
             .as_ref()
             .cloned()
-            .ok_or("Database not initialized")?
+            .ok_or_else(not_initialized)?
     };
     
-    db.set_setting(&key, &value)
+    db.create_status(status)
         .await
-        .map_err(|e| format!("Failed to set setting: {}", e))
+        .context("Failed to create status")
+}
+
+/// `completed` is derived from statuses, so at least one open and one done
+/// status must always remain.
+async fn ensure_not_last_of_kind(db: &Database, id: i64) -> Result<(), AppError> {
+    let statuses = db.get_statuses()
+        .await
+        .context("Failed to get statuses")?;
+    let status = statuses
+        .iter()
+        .find(|s| s.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("Status {} not found", id)))?;
+    
+    if statuses.iter().filter(|s| s.is_done == status.is_done).count() <= 1 {
+        let kind = if status.is_done { "done" } else { "open" };
+        return Err(AppError::Conflict(format!("'{}' is the last {} status", status.name, kind)));
+    }
+    
+    Ok(())
 }
 
-// Backup commands
 #[tauri::command]
-pub async fn create_backup(
-    app_handle: tauri::AppHandle,
+pub async fn update_status(
+    status: UpdateStatusInput,
     db_state: DatabaseState<'_>,
-) -> Result<BackupMetadata, String> {
+) -> Result<TaskStatus, AppError> {
+    // Validate input
+    status.check()?;
+    
     let db = {
         let guard = db_state.lock().unwrap();
         guard
             .as_ref()
             .cloned()
-            .ok_or("Database not initialized")?
+            .ok_or_else(not_initialized)?
     };
     
-    let backup_manager = BackupManager::new(&app_handle)
-        .map_err(|e| format!("Failed to initialize backup manager: {}", e))?;
+    if status.is_done.is_some() {
+        let current = db.get_statuses()
+            .await
+            .context("Failed to get statuses")?
+            .into_iter()
+            .find(|s| s.id == status.id)
+            .ok_or_else(|| AppError::NotFound(format!("Status {} not found", status.id)))?;
+        if status.is_done != Some(current.is_done) {
+            ensure_not_last_of_kind(&db, status.id).await?;
+        }
+    }
     
-    backup_manager.create_backup(&db)
+    db.update_status(status)
         .await
-        .map_err(|e| format!("Failed to create backup: {}", e))
+        .context("Failed to update status")
 }
 
 #[tauri::command]
-pub async fn restore_backup(
-    app_handle: tauri::AppHandle,
-    filename: String,
+pub async fn reorder_statuses(
+    ids: Vec<i64>,
+    db_state: DatabaseState<'_>,
+) -> Result<Vec<TaskStatus>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.reorder_statuses(&ids)
+        .await
+        .context("Failed to reorder statuses")
+}
+
+#[tauri::command]
+pub async fn delete_status(
+    id: i64,
+    reassign_to: Option<i64>,
     db_state: DatabaseState<'_>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     let db = {
         let guard = db_state.lock().unwrap();
         guard
             .as_ref()
             .cloned()
-            .ok_or("Database not initialized")?
+            .ok_or_else(not_initialized)?
     };
     
-    let backup_manager = BackupManager::new(&app_handle)
-        .map_err(|e| format!("Failed to initialize backup manager: {}", e))?;
+    ensure_not_last_of_kind(&db, id).await?;
     
-    backup_manager.restore_backup(&filename, &db)
+    db.delete_status(id, reassign_to)
         .await
-        .map_err(|e| format!("Failed to restore backup: {}", e))
+        .context("Failed to delete status")
 }
 
 #[tauri::command]
-pub async fn list_backups(
-    app_handle: tauri::AppHandle,
-) -> Result<Vec<BackupMetadata>, String> {
-    let backup_manager = BackupManager::new(&app_handle)
-        .map_err(|e| format!("Failed to initialize backup manager: {}", e))?;
+pub async fn get_categories(
+    db_state: DatabaseState<'_>,
+) -> Result<Vec<Category>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
     
-    backup_manager.list_backups()
-        .map_err(|e| format!("Failed to list backups: {}", e))
+    db.get_categories()
+        .await
+        .context("Failed to get categories")
 }
 
 #[tauri::command]
-pub async fn delete_backup(
-    app_handle: tauri::AppHandle,
-    filename: String,
-) -> Result<(), String> {
-    let backup_manager = BackupManager::new(&app_handle)
-        .map_err(|e| format!("Failed to initialize backup manager: {}", e))?;
+pub async fn create_category(
+    category: CreateCategoryInput,
+    db_state: DatabaseState<'_>,
+) -> Result<Category, AppError> {
+    // Validate input
+    category.check()?;
     
-    backup_manager.delete_backup(&filename)
-        .map_err(|e| format!("Failed to delete backup: {}", e))
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.create_category(category)
+        .await
+        .context("Failed to create category")
+}
+
+#[tauri::command]
+pub async fn delete_category(
+    id: i64,
+    db_state: DatabaseState<'_>,
+) -> Result<(), AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.delete_category(id)
+        .await
+        .context("Failed to delete category")
+}
+
+// Checklist commands
+#[tauri::command]
+pub async fn get_checklist(
+    task_id: i64,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<Vec<ChecklistItem>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    let mut items = db.get_checklist(Some(task_id))
+        .await
+        .context("Failed to get checklist")?;
+    vault.reveal_checklist(&mut items);
+    Ok(items)
+}
+
+#[tauri::command]
+pub async fn add_checklist_item(
+    task_id: i64,
+    title: String,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<ChecklistItem, AppError> {
+    if title.trim().is_empty() || title.len() > 255 {
+        return Err(AppError::invalid_field("title", "Checklist item must be between 1 and 255 characters"));
+    }
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    // Items of a private task are encrypted like the rest of it
+    let stored = match db.get_task(task_id).await.context("Failed to get task")? {
+        Some(task) => vault.seal_for(&task, &title)?,
+        None => title.clone(),
+    };
+    let mut item = db.add_checklist_item(task_id, &stored)
+        .await
+        .context("Failed to add checklist item")?;
+    item.title = title;
+    Ok(item)
+}
+
+#[tauri::command]
+pub async fn update_checklist_item(
+    mut item: UpdateChecklistItemInput,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<(), AppError> {
+    // Validate input
+    item.check()?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    if let Some(title) = &mut item.title {
+        let current = db.get_checklist_item(item.id)
+            .await
+            .context("Failed to get checklist item")?;
+        let task = match current {
+            Some(current) => db.get_task(current.task_id).await.context("Failed to get task")?,
+            None => None,
+        };
+        if let Some(task) = task {
+            *title = vault.seal_for(&task, title)?;
+        }
+    }
+    
+    db.update_checklist_item(item)
+        .await
+        .context("Failed to update checklist item")
+}
+
+#[tauri::command]
+pub async fn delete_checklist_item(
+    id: i64,
+    db_state: DatabaseState<'_>,
+) -> Result<(), AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.delete_checklist_item(id)
+        .await
+        .context("Failed to delete checklist item")
+}
+
+// Daily plan commands
+/// Checks a `YYYY-MM-DD` plan date and returns it normalized.
+fn plan_date(date: &str) -> Result<String, AppError> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|d| d.to_string())
+        .map_err(|_| AppError::invalid_field("date", format!("Invalid date '{}', expected YYYY-MM-DD", date)))
+}
+
+fn check_minutes(minutes: Option<u32>, what: &str) -> Result<(), AppError> {
+    match minutes {
+        Some(m) if m > 24 * 60 => Err(AppError::invalid_field(
+            &format!("{}_minutes", what.to_lowercase()),
+            format!("{} cannot exceed 1440 minutes", what),
+        )),
+        _ => Ok(()),
+    }
+}
+
+async fn load_daily_plan(db: &Database, vault: &Vault, date: String) -> Result<DailyPlan, AppError> {
+    let mut items = db.get_plan_items(&date)
+        .await
+        .context("Failed to get plan")?;
+    for item in &mut items {
+        vault.reveal(&mut item.task);
+    }
+    let capacity = match db.get_plan_capacity(&date)
+        .await
+        .context("Failed to get plan")? {
+        Some(capacity) => capacity,
+        None => db.get_setting(planning::CAPACITY_SETTING)
+            .await
+            .context("Failed to get setting")?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(planning::DEFAULT_CAPACITY_MINUTES),
+    };
+    
+    Ok(planning::build(date, capacity, items))
+}
+
+#[tauri::command]
+pub async fn get_daily_plan(
+    date: String,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<DailyPlan, AppError> {
+    let date = plan_date(&date)?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    load_daily_plan(&db, &vault, date).await
+}
+
+#[tauri::command]
+pub async fn add_to_plan(
+    date: String,
+    task_id: i64,
+    estimate_minutes: Option<u32>,
+    position: Option<usize>,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<DailyPlan, AppError> {
+    // Validate input
+    let date = plan_date(&date)?;
+    check_minutes(estimate_minutes, "Estimate")?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.add_to_plan(&date, task_id, estimate_minutes, position)
+        .await
+        .context("Failed to add task to plan")?;
+    
+    load_daily_plan(&db, &vault, date).await
+}
+
+#[tauri::command]
+pub async fn remove_from_plan(
+    date: String,
+    task_id: i64,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<DailyPlan, AppError> {
+    let date = plan_date(&date)?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.remove_from_plan(&date, task_id)
+        .await
+        .context("Failed to remove task from plan")?;
+    
+    load_daily_plan(&db, &vault, date).await
+}
+
+#[tauri::command]
+pub async fn reorder_plan(
+    date: String,
+    task_ids: Vec<i64>,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<DailyPlan, AppError> {
+    let date = plan_date(&date)?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.reorder_plan(&date, &task_ids)
+        .await
+        .context("Failed to reorder plan")?;
+    
+    load_daily_plan(&db, &vault, date).await
+}
+
+/// Sets the minutes available on a day; `null` falls back to the
+/// `daily_capacity_minutes` setting.
+#[tauri::command]
+pub async fn set_plan_capacity(
+    date: String,
+    capacity_minutes: Option<u32>,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<DailyPlan, AppError> {
+    // Validate input
+    let date = plan_date(&date)?;
+    check_minutes(capacity_minutes, "Capacity")?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.set_plan_capacity(&date, capacity_minutes)
+        .await
+        .context("Failed to set capacity")?;
+    
+    load_daily_plan(&db, &vault, date).await
+}
+
+/// Moves unfinished tasks from the previous plan into `date`. Meant to be
+/// called when a day is opened; repeated calls do nothing.
+#[tauri::command]
+pub async fn carry_over_plan(
+    date: String,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<DailyPlan, AppError> {
+    let date = plan_date(&date)?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.carry_over_plan(&date)
+        .await
+        .context("Failed to carry over plan")?;
+    
+    load_daily_plan(&db, &vault, date).await
+}
+
+// Custom field commands
+#[tauri::command]
+pub async fn get_custom_fields(
+    category_id: Option<i64>,
+    db_state: DatabaseState<'_>,
+) -> Result<Vec<CustomField>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.get_custom_fields(category_id)
+        .await
+        .context("Failed to get custom fields")
+}
+
+#[tauri::command]
+pub async fn create_custom_field(
+    field: CreateCustomFieldInput,
+    db_state: DatabaseState<'_>,
+) -> Result<CustomField, AppError> {
+    // Validate input
+    field.check()?;
+    custom_fields::check_options(&field.field_type, &field.options)
+        .map_err(|e| AppError::invalid_field("options", e))?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.create_custom_field(field)
+        .await
+        .context("Failed to create custom field")
+}
+
+#[tauri::command]
+pub async fn update_custom_field(
+    field: UpdateCustomFieldInput,
+    db_state: DatabaseState<'_>,
+) -> Result<Option<CustomField>, AppError> {
+    // Validate input
+    field.check()?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    if let Some(options) = &field.options {
+        let current = db.get_custom_field(field.id)
+            .await
+            .context("Failed to get custom field")?
+            .ok_or_else(|| AppError::NotFound(format!("Custom field {} not found", field.id)))?;
+        custom_fields::check_options(&current.field_type, options)
+            .map_err(|e| AppError::invalid_field("options", e))?;
+    }
+    
+    db.update_custom_field(field)
+        .await
+        .context("Failed to update custom field")
+}
+
+#[tauri::command]
+pub async fn delete_custom_field(
+    id: i64,
+    db_state: DatabaseState<'_>,
+) -> Result<(), AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.delete_custom_field(id)
+        .await
+        .context("Failed to delete custom field")
+}
+
+/// Sets or, with `value: null`, clears a custom field on a task.
+#[tauri::command]
+pub async fn set_task_field_value(
+    task_id: i64,
+    field_id: i64,
+    value: Option<String>,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<Option<Task>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    let field = db.get_custom_field(field_id)
+        .await
+        .context("Failed to get custom field")?
+        .ok_or_else(|| AppError::NotFound(format!("Custom field {} not found", field_id)))?;
+    let Some(task) = db.get_task(task_id)
+        .await
+        .context("Failed to get task")? else {
+        return Ok(None);
+    };
+    
+    // Validate input
+    let value = match value {
+        Some(value) => {
+            custom_fields::check_scope(&field, task.category_id)
+                .map_err(AppError::validation)?;
+            let value = custom_fields::normalize_value(&field, &value)
+                .map_err(|e| AppError::invalid_field("value", e))?;
+            Some(vault.seal_for(&task, &value)?)
+        }
+        None => None,
+    };
+    
+    db.set_task_field_value(task_id, field_id, value.as_deref())
+        .await
+        .context("Failed to set custom field value")?;
+    
+    db.get_task(task_id)
+        .await
+        .map(|task| task.map(|task| vault.revealed(task)))
+        .context("Failed to get task")
+}
+
+// Link commands
+/// Decrypts the titles of private tasks on both ends of the links.
+fn reveal_links(vault: &Vault, mut links: Vec<TaskLink>) -> Vec<TaskLink> {
+    for link in &mut links {
+        link.source_title = vault.reveal_title(&link.source_title);
+        link.target_title = link.target_title.as_deref().map(|title| vault.reveal_title(title));
+    }
+    links
+}
+
+#[tauri::command]
+pub async fn get_links(
+    task_id: i64,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<Vec<TaskLink>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.get_links(task_id)
+        .await
+        .map(|links| reveal_links(&vault, links))
+        .context("Failed to get links")
+}
+
+#[tauri::command]
+pub async fn get_backlinks(
+    task_id: i64,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<Vec<TaskLink>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.get_backlinks(task_id)
+        .await
+        .map(|links| reveal_links(&vault, links))
+        .context("Failed to get backlinks")
+}
+
+#[tauri::command]
+pub async fn get_dangling_links(
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<Vec<TaskLink>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.get_dangling_links()
+        .await
+        .map(|links| reveal_links(&vault, links))
+        .context("Failed to get dangling links")
+}
+
+// Attachment commands
+#[tauri::command]
+pub async fn get_attachments(
+    task_id: i64,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<Vec<Attachment>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    let mut attachments = db.get_attachments(Some(task_id))
+        .await
+        .context("Failed to get attachments")?;
+    vault.reveal_attachments(&mut attachments);
+    Ok(attachments)
+}
+
+#[tauri::command]
+pub async fn add_attachment(
+    mut input: CreateAttachmentInput,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<Attachment, AppError> {
+    // Validate input
+    input.check()?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    // Paths and labels of a private task are encrypted like the rest of it
+    let plain = (input.target.clone(), input.label.clone());
+    if let Some(task) = db.get_task(input.task_id).await.context("Failed to get task")? {
+        input.target = vault.seal_for(&task, &input.target)?;
+        input.label = input.label.map(|label| vault.seal_for(&task, &label)).transpose()?;
+    }
+    
+    let mut attachment = db.add_attachment(input)
+        .await
+        .context("Failed to add attachment")?;
+    (attachment.target, attachment.label) = plain;
+    Ok(attachment)
+}
+
+#[tauri::command]
+pub async fn delete_attachment(
+    id: i64,
+    db_state: DatabaseState<'_>,
+) -> Result<(), AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.delete_attachment(id)
+        .await
+        .context("Failed to delete attachment")
+}
+
+// Template commands
+#[tauri::command]
+pub async fn get_templates(
+    db_state: DatabaseState<'_>,
+) -> Result<Vec<TaskTemplate>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.get_templates()
+        .await
+        .context("Failed to get templates")
+}
+
+#[tauri::command]
+pub async fn create_template(
+    template: CreateTemplateInput,
+    db_state: DatabaseState<'_>,
+) -> Result<TaskTemplate, AppError> {
+    // Validate input
+    template.check()?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.create_template(template)
+        .await
+        .context("Failed to create template")
+}
+
+/// Loads tasks together with their checklists for turning them into a template.
+async fn tasks_with_checklists(db: &Database, tasks: Vec<Task>) -> Result<Vec<(Task, Vec<ChecklistItem>)>, AppError> {
+    let mut result = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        // Templates are stored in plaintext
+        if privacy::is_sealed(&task.title) {
+            return Err(AppError::validation("Private tasks cannot be saved as templates"));
+        }
+        let checklist = db.get_checklist(Some(task.id))
+            .await
+            .context("Failed to get checklist")?;
+        result.push((task, checklist));
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn save_task_template(
+    task_id: i64,
+    name: String,
+    db_state: DatabaseState<'_>,
+) -> Result<TaskTemplate, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    let task = db.get_task(task_id)
+        .await
+        .context("Failed to get task")?
+        .ok_or_else(|| AppError::NotFound(format!("Task {} not found", task_id)))?;
+    let category_id = task.category_id;
+    let tasks = tasks_with_checklists(&db, vec![task]).await?;
+    
+    let template = CreateTemplateInput {
+        name,
+        description: None,
+        category_id,
+        tasks: templates::template_tasks_from(&tasks),
+    };
+    template.check()?;
+    
+    db.create_template(template)
+        .await
+        .context("Failed to create template")
+}
+
+/// Saves every open task of a category as one template.
+#[tauri::command]
+pub async fn save_category_template(
+    category_id: i64,
+    name: String,
+    db_state: DatabaseState<'_>,
+) -> Result<TaskTemplate, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    let tasks = db.get_tasks(Some(TaskFilters {
+        completed: Some(false),
+        category_id: Some(category_id),
+        ..Default::default()
+    }))
+        .await
+        .context("Failed to get tasks")?;
+    let tasks = tasks_with_checklists(&db, tasks).await?;
+    
+    let template = CreateTemplateInput {
+        name,
+        description: None,
+        category_id: Some(category_id),
+        tasks: templates::template_tasks_from(&tasks),
+    };
+    template.check()?;
+    
+    db.create_template(template)
+        .await
+        .context("Failed to create template")
+}
+
+#[tauri::command]
+pub async fn delete_template(
+    id: i64,
+    db_state: DatabaseState<'_>,
+) -> Result<(), AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.delete_template(id)
+        .await
+        .context("Failed to delete template")
+}
+
+/// Creates all tasks of a template in one transaction. `anchor_date` is an
+/// RFC 3339 timestamp that relative due dates are counted from.
+#[tauri::command]
+pub async fn instantiate_template(
+    id: i64,
+    vars: HashMap<String, String>,
+    anchor_date: String,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<Vec<Task>, AppError> {
+    let anchor = chrono::DateTime::parse_from_rfc3339(&anchor_date)
+        .map_err(|e| AppError::invalid_field("anchor_date", format!("Invalid anchor date '{}': {}", anchor_date, e)))?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    let template = db.get_template(id)
+        .await
+        .context("Failed to get template")?
+        .ok_or_else(|| AppError::NotFound(format!("Template {} not found", id)))?;
+    
+    let mut instances = templates::instantiate(&template, &vars, anchor)?;
+    for instance in &mut instances {
+        instance.task.check()?;
+        
+        if db.is_category_private(instance.task.category_id)
+            .await
+            .context("Failed to get category")? {
+            instance.task.title = vault.seal(&instance.task.title)?;
+            instance.task.description = instance.task.description.take().map(|d| vault.seal(&d)).transpose()?;
+            for item in &mut instance.checklist {
+                *item = vault.seal(item)?;
+            }
+        }
+    }
+    
+    db.create_tasks_with_checklists(instances)
+        .await
+        .map(|tasks| tasks.into_iter().map(|task| vault.revealed(task)).collect())
+        .context("Failed to create tasks from template")
+}
+
+#[tauri::command]
+pub async fn get_setting(
+    key: String,
+    db_state: DatabaseState<'_>,
+) -> Result<Option<String>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.get_setting(&key)
+        .await
+        .context("Failed to get setting")
+}
+
+#[tauri::command]
+pub async fn set_setting(
+    key: String,
+    value: String,
+    db_state: DatabaseState<'_>,
+) -> Result<(), AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.set_setting(&key, &value)
+        .await
+        .context("Failed to set setting")
+}
+
+// Focus session commands
+#[tauri::command]
+pub async fn get_focus_config(
+    db_state: DatabaseState<'_>,
+) -> Result<FocusConfig, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    FocusEngine::load_config(&db)
+        .await
+        .context("Failed to get focus settings")
+}
+
+#[tauri::command]
+pub async fn set_focus_config(
+    config: FocusConfig,
+    db_state: DatabaseState<'_>,
+) -> Result<(), AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    FocusEngine::save_config(&db, &config)
+        .await
+        .context("Failed to save focus settings")
+}
+
+#[tauri::command]
+pub async fn get_focus_state(
+    focus: FocusEngineState<'_>,
+) -> Result<FocusState, AppError> {
+    Ok(focus.snapshot())
+}
+
+#[tauri::command]
+pub async fn start_focus(
+    app_handle: tauri::AppHandle,
+    task_id: Option<i64>,
+    db_state: DatabaseState<'_>,
+    focus: FocusEngineState<'_>,
+) -> Result<FocusState, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    if let Some(id) = task_id {
+        db.get_task(id)
+            .await
+            .context("Failed to get task")?
+            .ok_or_else(|| AppError::NotFound(format!("Task {} not found", id)))?;
+    }
+    
+    focus.start(&app_handle, &db, task_id)
+        .await
+        .context("Failed to start focus session")
+}
+
+#[tauri::command]
+pub async fn pause_focus(
+    app_handle: tauri::AppHandle,
+    db_state: DatabaseState<'_>,
+    focus: FocusEngineState<'_>,
+) -> Result<FocusState, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    focus.pause(&app_handle, &db)
+        .await
+        .context("Failed to pause focus session")
+}
+
+#[tauri::command]
+pub async fn resume_focus(
+    app_handle: tauri::AppHandle,
+    db_state: DatabaseState<'_>,
+    focus: FocusEngineState<'_>,
+) -> Result<FocusState, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    focus.resume(&app_handle, &db)
+        .await
+        .context("Failed to resume focus session")
+}
+
+#[tauri::command]
+pub async fn stop_focus(
+    app_handle: tauri::AppHandle,
+    db_state: DatabaseState<'_>,
+    focus: FocusEngineState<'_>,
+) -> Result<FocusState, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    focus.stop(&app_handle, &db)
+        .await
+        .context("Failed to stop focus session")
+}
+
+#[tauri::command]
+pub async fn skip_focus_phase(
+    app_handle: tauri::AppHandle,
+    db_state: DatabaseState<'_>,
+    focus: FocusEngineState<'_>,
+) -> Result<FocusState, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    focus.skip(&app_handle, &db)
+        .await
+        .context("Failed to skip focus phase")
+}
+
+#[tauri::command]
+pub async fn get_focus_sessions(
+    task_id: Option<i64>,
+    db_state: DatabaseState<'_>,
+) -> Result<Vec<FocusSession>, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.get_focus_sessions(task_id)
+        .await
+        .context("Failed to get focus sessions")
+}
+
+// Backup commands
+fn backup_manager(app_handle: &tauri::AppHandle) -> Result<BackupManager, AppError> {
+    let dir = DataLocation::backup_dir(app_handle)
+        .context("Failed to initialize backup manager")?;
+    BackupManager::with_dir(dir)
+        .context("Failed to initialize backup manager")
+}
+
+#[tauri::command]
+pub async fn create_backup(
+    app_handle: tauri::AppHandle,
+    db_state: DatabaseState<'_>,
+) -> Result<BackupMetadata, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    let backup_manager = backup_manager(&app_handle)?;
+    
+    backup_manager.create_backup(&db)
+        .await
+        .context("Failed to create backup")
+}
+
+#[tauri::command]
+pub async fn restore_backup(
+    app_handle: tauri::AppHandle,
+    filename: String,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<(), AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    let backup_manager = backup_manager(&app_handle)?;
+    
+    // The restored content may be under a different key
+    vault.lock();
+    
+    backup_manager.restore_backup(&filename, &db)
+        .await
+        .context("Failed to restore backup")
+}
+
+#[tauri::command]
+pub async fn list_backups(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<BackupMetadata>, AppError> {
+    let backup_manager = backup_manager(&app_handle)?;
+    
+    backup_manager.list_backups()
+        .context("Failed to list backups")
+}
+
+#[tauri::command]
+pub async fn delete_backup(
+    app_handle: tauri::AppHandle,
+    filename: String,
+) -> Result<(), AppError> {
+    let backup_manager = backup_manager(&app_handle)?;
+    
+    backup_manager.delete_backup(&filename)
+        .context("Failed to delete backup")
+}
+
+// Workspace commands
+#[tauri::command]
+pub async fn list_workspaces(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<WorkspaceInfo>, AppError> {
+    Workspaces::from_app(&app_handle)
+        .and_then(|workspaces| workspaces.list())
+        .context("Failed to list workspaces")
+}
+
+#[tauri::command]
+pub async fn create_workspace(
+    app_handle: tauri::AppHandle,
+    name: String,
+) -> Result<WorkspaceInfo, AppError> {
+    let workspaces = Workspaces::from_app(&app_handle)
+        .context("Failed to create workspace")?;
+    let active = workspaces.active()
+        .context("Failed to create workspace")?;
+    
+    let workspace = workspaces.create(&name)
+        .context("Failed to create workspace")?;
+    
+    Ok(workspaces.info(&workspace, &active.id))
+}
+
+#[tauri::command]
+pub async fn rename_workspace(
+    app_handle: tauri::AppHandle,
+    id: String,
+    name: String,
+) -> Result<WorkspaceInfo, AppError> {
+    let workspaces = Workspaces::from_app(&app_handle)
+        .context("Failed to rename workspace")?;
+    let active = workspaces.active()
+        .context("Failed to rename workspace")?;
+    
+    let workspace = workspaces.rename(&id, &name)
+        .context("Failed to rename workspace")?;
+    
+    Ok(workspaces.info(&workspace, &active.id))
+}
+
+#[tauri::command]
+pub async fn switch_workspace(
+    app_handle: tauri::AppHandle,
+    id: String,
+    passphrase: Option<String>,
+    db_state: DatabaseState<'_>,
+    focus: FocusEngineState<'_>,
+    vault: VaultState<'_>,
+) -> Result<WorkspaceInfo, AppError> {
+    let workspaces = Workspaces::from_app(&app_handle)
+        .context("Failed to switch workspace")?;
+    let workspace = workspaces.get(&id)
+        .context("Failed to switch workspace")?;
+    let path = workspaces.database_path(&workspace.id);
+    
+    open_database_at(&app_handle, &db_state, &focus, &vault, &path, passphrase.as_deref()).await?;
+    
+    workspaces.set_active(&workspace.id)
+        .context("Failed to switch workspace")?;
+    DataLocation::from_app(&app_handle).set_database(None);
+    
+    Ok(workspaces.info(&workspace, &workspace.id))
+}
+
+#[tauri::command]
+pub async fn delete_workspace(
+    app_handle: tauri::AppHandle,
+    id: String,
+) -> Result<(), AppError> {
+    Workspaces::from_app(&app_handle)
+        .and_then(|workspaces| workspaces.delete(&id))
+        .context("Failed to delete workspace")
+}
+
+// Database location commands
+#[tauri::command]
+pub async fn get_database_location(
+    app_handle: tauri::AppHandle,
+    db_state: DatabaseState<'_>,
+) -> Result<DatabaseLocation, AppError> {
+    let open_path = db_state
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|db| db.path().map(|p| p.to_path_buf()));
+    
+    // Before init_database this is the file it will open, so the frontend
+    // knows whether to ask for a passphrase
+    let path = match open_path {
+        Some(path) => path,
+        None => DataLocation::database_path(&app_handle)
+            .context("Failed to resolve database path")?,
+    };
+    
+    Ok(DatabaseLocation::new(&app_handle, &path))
+}
+
+/// Opens the database file at `path`, creating and migrating it if needed.
+#[tauri::command]
+pub async fn open_database(
+    app_handle: tauri::AppHandle,
+    path: String,
+    passphrase: Option<String>,
+    db_state: DatabaseState<'_>,
+    focus: FocusEngineState<'_>,
+    vault: VaultState<'_>,
+) -> Result<DatabaseLocation, AppError> {
+    let path = std::path::absolute(path.trim())
+        .map_err(|e| AppError::invalid_field("path", format!("Invalid database path: {}", e)))?;
+    
+    open_database_at(&app_handle, &db_state, &focus, &vault, &path, passphrase.as_deref()).await?;
+    
+    DataLocation::from_app(&app_handle).set_database(Some(path.clone()));
+    
+    Ok(DatabaseLocation::new(&app_handle, &path))
+}
+
+/// Replaces the open database with the one at `path`. The new database is
+/// opened before the current one is touched, so a failure leaves the app on
+/// the database it was using.
+async fn open_database_at(
+    app_handle: &tauri::AppHandle,
+    db_state: &DatabaseState<'_>,
+    focus: &FocusEngineState<'_>,
+    vault: &VaultState<'_>,
+    path: &std::path::Path,
+    passphrase: Option<&str>,
+) -> Result<(), AppError> {
+    let current = db_state.lock().unwrap().clone();
+    if current.as_ref().and_then(|db| db.path()) == Some(path) {
+        return Ok(());
+    }
+    
+    let database = Database::open_with_passphrase(path, passphrase)
+        .await
+        .context("Failed to open database")?;
+    
+    database.apply_archive_policy()
+        .await
+        .context("Failed to archive completed tasks")?;
+    
+    if let Some(current) = &current {
+        // A running timer belongs to the old database; park it there
+        if focus.snapshot().is_running() {
+            focus.pause(app_handle, current)
+                .await
+                .context("Failed to pause focus session")?;
+        }
+    }
+    
+    // Commands that already cloned the old database finish against it; the
+    // old pool and its file lock are released once the last of them is done
+    {
+        let mut db_guard = db_state.lock().unwrap();
+        *db_guard = Some(database.clone());
+    }
+    drop(current);
+    vault.lock();
+    
+    focus.restore(app_handle, &database)
+        .await
+        .context("Failed to restore focus session")?;
+    
+    Ok(())
+}
+
+/// Applied and pending schema migrations of the open database.
+#[tauri::command]
+pub async fn get_migration_status(
+    db_state: DatabaseState<'_>,
+) -> Result<MigrationStatus, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.migration_status()
+        .await
+        .context("Failed to get migration status")
+}
+
+// Integrity and maintenance commands
+/// Checks the database file and the app's own invariants; changes nothing.
+#[tauri::command]
+pub async fn check_database(
+    db_state: DatabaseState<'_>,
+) -> Result<IntegrityReport, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.check_integrity()
+        .await
+        .context("Failed to check database")
+}
+
+/// Fixes what `check_database` reports as repairable and checks again.
+#[tauri::command]
+pub async fn repair_database(
+    db_state: DatabaseState<'_>,
+) -> Result<RepairReport, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    let repaired = db.repair_integrity()
+        .await
+        .context("Failed to repair database")?;
+    let report = db.check_integrity()
+        .await
+        .context("Failed to check database")?;
+    
+    Ok(RepairReport { repaired, report })
+}
+
+/// Size of the database on disk and how much of it maintenance can free.
+#[tauri::command]
+pub async fn get_database_size(
+    db_state: DatabaseState<'_>,
+) -> Result<DatabaseSize, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.size()
+        .await
+        .context("Failed to get database size")
+}
+
+/// Runs the background maintenance now and returns the resulting size.
+#[tauri::command]
+pub async fn run_database_maintenance(
+    db_state: DatabaseState<'_>,
+) -> Result<DatabaseSize, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.run_maintenance()
+        .await
+        .context("Failed to run database maintenance")?;
+    
+    db.size()
+        .await
+        .context("Failed to get database size")
+}
+
+// Encryption commands
+#[tauri::command]
+pub async fn enable_database_encryption(
+    app_handle: tauri::AppHandle,
+    passphrase: String,
+    db_state: DatabaseState<'_>,
+) -> Result<DatabaseLocation, AppError> {
+    yuflow_core::encryption::check_passphrase(&passphrase)?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    if db.is_encrypted() {
+        return Err(AppError::Conflict("The database is already encrypted".to_string()));
+    }
+    
+    rekey_database(&app_handle, &db_state, db, Some(&passphrase)).await
+}
+
+#[tauri::command]
+pub async fn disable_database_encryption(
+    app_handle: tauri::AppHandle,
+    passphrase: String,
+    db_state: DatabaseState<'_>,
+) -> Result<DatabaseLocation, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    check_current_passphrase(&db, &passphrase).await?;
+    
+    rekey_database(&app_handle, &db_state, db, None).await
+}
+
+#[tauri::command]
+pub async fn change_database_passphrase(
+    app_handle: tauri::AppHandle,
+    current_passphrase: String,
+    new_passphrase: String,
+    db_state: DatabaseState<'_>,
+) -> Result<DatabaseLocation, AppError> {
+    yuflow_core::encryption::check_passphrase(&new_passphrase)?;
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    check_current_passphrase(&db, &current_passphrase).await?;
+    
+    rekey_database(&app_handle, &db_state, db, Some(&new_passphrase)).await
+}
+
+async fn check_current_passphrase(db: &Database, passphrase: &str) -> Result<(), AppError> {
+    if !db.is_encrypted() {
+        return Err(AppError::Conflict("The database is not encrypted".to_string()));
+    }
+    
+    let valid = db.verify_passphrase(passphrase)
+        .await
+        .context("Failed to check passphrase")?;
+    
+    if valid { Ok(()) } else { Err(AppError::invalid_field("passphrase", "Wrong passphrase")) }
+}
+
+/// Rewrites the open database under a new key and shares the reopened one.
+async fn rekey_database(
+    app_handle: &tauri::AppHandle,
+    db_state: &DatabaseState<'_>,
+    db: Database,
+    passphrase: Option<&str>,
+) -> Result<DatabaseLocation, AppError> {
+    let database = db.set_passphrase(passphrase)
+        .await
+        .context("Failed to migrate database file")?;
+    
+    {
+        let mut db_guard = db_state.lock().unwrap();
+        *db_guard = Some(database.clone());
+    }
+    
+    let path = database.path().map(|p| p.to_path_buf()).unwrap_or_default();
+    Ok(DatabaseLocation::new(app_handle, &path))
+}
+
+// App lock commands
+#[tauri::command]
+pub async fn get_lock_status(
+    lock: AppLockState<'_>,
+) -> Result<LockStatus, AppError> {
+    Ok(lock.status())
+}
+
+#[tauri::command]
+pub async fn lock_app(
+    lock: AppLockState<'_>,
+    vault: VaultState<'_>,
+) -> Result<LockStatus, AppError> {
+    vault.lock();
+    Ok(lock.lock())
+}
+
+#[tauri::command]
+pub async fn unlock_app(
+    passphrase: String,
+    lock: AppLockState<'_>,
+) -> Result<LockStatus, AppError> {
+    lock.unlock(&passphrase)
+}
+
+/// Keeps the idle timer from running out while the user is active without
+/// calling other commands; the lock counts every command as activity.
+#[tauri::command]
+pub async fn report_activity() -> Result<(), AppError> {
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_lock_passphrase(
+    current_passphrase: Option<String>,
+    new_passphrase: String,
+    lock: AppLockState<'_>,
+) -> Result<LockStatus, AppError> {
+    lock.set_passphrase(current_passphrase.as_deref(), &new_passphrase)
+}
+
+#[tauri::command]
+pub async fn disable_app_lock(
+    passphrase: String,
+    lock: AppLockState<'_>,
+) -> Result<LockStatus, AppError> {
+    lock.disable(&passphrase)
+}
+
+#[tauri::command]
+pub async fn set_lock_options(
+    idle_minutes: u32,
+    lock_on_suspend: bool,
+    lock: AppLockState<'_>,
+) -> Result<LockStatus, AppError> {
+    lock.set_options(idle_minutes, lock_on_suspend)
+}
+
+// Private content commands
+#[tauri::command]
+pub async fn get_private_status(
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<VaultStatus, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    vault.status(&db).await
+}
+
+/// Sets the passphrase for private tasks of the open database and unlocks them.
+#[tauri::command]
+pub async fn setup_private_content(
+    passphrase: String,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<VaultStatus, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    vault.setup(&db, &passphrase).await?;
+    vault.status(&db).await
+}
+
+#[tauri::command]
+pub async fn unlock_private_content(
+    passphrase: String,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<VaultStatus, AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    vault.unlock(&db, &passphrase).await?;
+    vault.status(&db).await
+}
+
+#[tauri::command]
+pub async fn lock_private_content(
+    vault: VaultState<'_>,
+) -> Result<(), AppError> {
+    vault.lock();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn change_private_passphrase(
+    current_passphrase: String,
+    new_passphrase: String,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<(), AppError> {
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    vault.change_passphrase(&db, &current_passphrase, &new_passphrase).await
+}
+
+/// Marks a task private and encrypts its title and description, or the
+/// reverse. A task in a private category stays encrypted either way.
+#[tauri::command]
+pub async fn set_task_private(
+    id: i64,
+    private: bool,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<Option<Task>, AppError> {
+    if !vault.is_unlocked() {
+        return Err(privacy::locked());
+    }
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    db.set_task_private(id, private, &*vault)
+        .await
+        .map(|task| task.map(|task| vault.revealed(task)))
+        .context("Failed to update task")
+}
+
+/// Marks a category private, which encrypts all of its tasks, archived ones
+/// included, or the reverse.
+#[tauri::command]
+pub async fn set_category_private(
+    id: i64,
+    private: bool,
+    db_state: DatabaseState<'_>,
+    vault: VaultState<'_>,
+) -> Result<(), AppError> {
+    if !vault.is_unlocked() {
+        return Err(privacy::locked());
+    }
+    
+    let db = {
+        let guard = db_state.lock().unwrap();
+        guard
+            .as_ref()
+            .cloned()
+            .ok_or_else(not_initialized)?
+    };
+    
+    // The flag and the content of every task change in one transaction
+    let found = db.set_category_private(id, private, &*vault)
+        .await
+        .context("Failed to update category")?;
+    if !found {
+        return Err(AppError::NotFound(format!("Category {} not found", id)));
+    }
+    
+    Ok(())
 }
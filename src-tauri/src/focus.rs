@@ -0,0 +1,398 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration as StdDuration;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use yuflow_core::database::Database;
+use yuflow_core::models::*;
+use yuflow_core::AppError;
+
+pub const FOCUS_CONFIG_SETTING: &str = "focus_config";
+pub const FOCUS_STATE_SETTING: &str = "focus_state";
+
+/// Emitted every second while a phase is running
+pub const FOCUS_TICK_EVENT: &str = "focus://tick";
+/// Emitted whenever a phase ends, is skipped or is stopped
+pub const FOCUS_PHASE_EVENT: &str = "focus://phase-ended";
+
+const TRAY_ID: &str = "main";
+
+type FocusResult<T> = Result<T, AppError>;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct FocusConfig {
+    pub work_minutes: u32,
+    pub short_break_minutes: u32,
+    pub long_break_minutes: u32,
+    /// A long break replaces every n-th short break
+    pub long_break_every: u32,
+    pub auto_start_breaks: bool,
+    pub auto_start_work: bool,
+}
+
+impl Default for FocusConfig {
+    fn default() -> Self {
+        FocusConfig {
+            work_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            long_break_every: 4,
+            auto_start_breaks: true,
+            auto_start_work: false,
+        }
+    }
+}
+
+impl FocusConfig {
+    pub fn validate(&self) -> Result<(), AppError> {
+        let lengths = [self.work_minutes, self.short_break_minutes, self.long_break_minutes];
+        if lengths.iter().any(|m| *m == 0 || *m > 240) {
+            return Err(AppError::validation("Session lengths must be between 1 and 240 minutes"));
+        }
+        if self.long_break_every == 0 {
+            return Err(AppError::invalid_field("long_break_every", "Long break cadence must be at least 1"));
+        }
+        Ok(())
+    }
+
+    fn seconds(&self, phase: FocusPhase) -> i64 {
+        let minutes = match phase {
+            FocusPhase::Work => self.work_minutes,
+            FocusPhase::ShortBreak => self.short_break_minutes,
+            FocusPhase::LongBreak => self.long_break_minutes,
+        };
+        minutes as i64 * 60
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl FocusPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FocusPhase::Work => "work",
+            FocusPhase::ShortBreak => "short_break",
+            FocusPhase::LongBreak => "long_break",
+        }
+    }
+}
+
+/// Persisted timer state. A phase is running when `ends_at` is set and
+/// paused when only `remaining_seconds` is; no phase means idle.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct FocusState {
+    pub phase: Option<FocusPhase>,
+    pub task_id: Option<i64>,
+    pub started_at: Option<String>,
+    pub ends_at: Option<String>,
+    pub remaining_seconds: Option<i64>,
+    pub planned_seconds: i64,
+    /// Work sessions finished in the current cycle, used for the long break cadence
+    pub completed_work_sessions: u32,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FocusTick {
+    pub state: FocusState,
+    pub remaining_seconds: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FocusPhaseEnded {
+    /// Absent when the phase ended before any time was spent in it
+    pub session: Option<FocusSession>,
+    pub next: FocusState,
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value).ok().map(|t| t.with_timezone(&Utc))
+}
+
+impl FocusState {
+    pub fn is_running(&self) -> bool {
+        self.phase.is_some() && self.ends_at.is_some()
+    }
+
+    pub fn remaining(&self, now: DateTime<Utc>) -> i64 {
+        match (self.ends_at.as_deref().and_then(parse_time), self.remaining_seconds) {
+            (Some(ends_at), _) => (ends_at - now).num_seconds().max(0),
+            (None, Some(remaining)) => remaining,
+            _ => 0,
+        }
+    }
+
+    /// Starts `phase` immediately, or leaves it paused at full length.
+    pub fn begin(
+        config: &FocusConfig,
+        phase: FocusPhase,
+        task_id: Option<i64>,
+        completed_work_sessions: u32,
+        running: bool,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let planned_seconds = config.seconds(phase);
+        let mut state = FocusState {
+            phase: Some(phase),
+            task_id,
+            started_at: None,
+            ends_at: None,
+            remaining_seconds: Some(planned_seconds),
+            planned_seconds,
+            completed_work_sessions,
+        };
+        if running {
+            state.resume(now);
+        }
+        state
+    }
+
+    pub fn pause(&mut self, now: DateTime<Utc>) {
+        if self.is_running() {
+            self.remaining_seconds = Some(self.remaining(now));
+            self.ends_at = None;
+        }
+    }
+
+    pub fn resume(&mut self, now: DateTime<Utc>) {
+        if self.phase.is_some() && !self.is_running() {
+            let remaining = self.remaining(now);
+            self.started_at.get_or_insert_with(|| now.to_rfc3339());
+            self.ends_at = Some((now + Duration::seconds(remaining)).to_rfc3339());
+            self.remaining_seconds = None;
+        }
+    }
+
+    /// Ends the current phase, returning the session to record and the state
+    /// to continue with. `completed` is false when the phase is cut short;
+    /// `advance` picks the next phase of the cycle instead of going idle.
+    pub fn finish(
+        &self,
+        config: &FocusConfig,
+        now: DateTime<Utc>,
+        completed: bool,
+        advance: bool,
+    ) -> Option<(NewFocusSession, FocusState)> {
+        let phase = self.phase?;
+        let ended_at = if completed {
+            self.ends_at.as_deref().and_then(parse_time).unwrap_or(now)
+        } else {
+            now
+        };
+        let remaining = if completed { 0 } else { self.remaining(now) };
+
+        let session = NewFocusSession {
+            task_id: self.task_id,
+            kind: phase.as_str().to_string(),
+            started_at: self.started_at.clone().unwrap_or_else(|| ended_at.to_rfc3339()),
+            ended_at: ended_at.to_rfc3339(),
+            planned_seconds: self.planned_seconds,
+            actual_seconds: self.planned_seconds - remaining,
+            completed,
+        };
+
+        if !advance {
+            return Some((session, FocusState::default()));
+        }
+
+        let next = match phase {
+            FocusPhase::Work => {
+                let done = self.completed_work_sessions + u32::from(completed);
+                let long = completed && done.is_multiple_of(config.long_break_every);
+                let break_phase = if long { FocusPhase::LongBreak } else { FocusPhase::ShortBreak };
+                FocusState::begin(config, break_phase, self.task_id, done, config.auto_start_breaks, ended_at)
+            }
+            FocusPhase::ShortBreak => FocusState::begin(
+                config, FocusPhase::Work, self.task_id, self.completed_work_sessions, config.auto_start_work, ended_at,
+            ),
+            // A long break closes the cycle
+            FocusPhase::LongBreak => FocusState::begin(
+                config, FocusPhase::Work, self.task_id, 0, config.auto_start_work, ended_at,
+            ),
+        };
+
+        Some((session, next))
+    }
+}
+
+/// Focus session engine, managed as Tauri state. The timer task holds the
+/// generation it was started for and exits as soon as a newer one exists.
+#[derive(Default)]
+pub struct FocusEngine {
+    state: Mutex<FocusState>,
+    generation: AtomicU64,
+}
+
+impl FocusEngine {
+    pub fn snapshot(&self) -> FocusState {
+        self.state.lock().unwrap().clone()
+    }
+
+    pub async fn load_config(db: &Database) -> FocusResult<FocusConfig> {
+        let config = db.get_setting(FOCUS_CONFIG_SETTING).await?;
+        Ok(config
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default())
+    }
+
+    pub async fn save_config(db: &Database, config: &FocusConfig) -> FocusResult<()> {
+        config.validate()?;
+        db.set_setting(FOCUS_CONFIG_SETTING, &serde_json::to_string(config)?).await?;
+        Ok(())
+    }
+
+    /// Reloads the persisted state at startup. A phase that ran out while the
+    /// app was closed is recorded as completed and the next one waits paused.
+    pub async fn restore(&self, app: &AppHandle, db: &Database) -> FocusResult<FocusState> {
+        let state: FocusState = db
+            .get_setting(FOCUS_STATE_SETTING)
+            .await?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        let now = Utc::now();
+        if state.is_running() && state.remaining(now) == 0 {
+            let config = Self::load_config(db).await?;
+            if let Some((session, mut next)) = state.finish(&config, now, true, true) {
+                Self::record(db, session).await?;
+                next.pause(now);
+                return self.apply(app, db, next).await;
+            }
+        }
+
+        self.apply(app, db, state).await
+    }
+
+    pub async fn start(&self, app: &AppHandle, db: &Database, task_id: Option<i64>) -> FocusResult<FocusState> {
+        let config = Self::load_config(db).await?;
+        let now = Utc::now();
+
+        // Starting over interrupts whatever phase is active
+        let current = self.snapshot();
+        if let Some((session, _)) = current.finish(&config, now, false, false) {
+            Self::record(db, session).await?;
+        }
+
+        let next = FocusState::begin(&config, FocusPhase::Work, task_id, current.completed_work_sessions, true, now);
+        self.apply(app, db, next).await
+    }
+
+    pub async fn pause(&self, app: &AppHandle, db: &Database) -> FocusResult<FocusState> {
+        let mut state = self.snapshot();
+        state.pause(Utc::now());
+        self.apply(app, db, state).await
+    }
+
+    pub async fn resume(&self, app: &AppHandle, db: &Database) -> FocusResult<FocusState> {
+        let mut state = self.snapshot();
+        state.resume(Utc::now());
+        self.apply(app, db, state).await
+    }
+
+    /// Stops the active phase and goes idle.
+    pub async fn stop(&self, app: &AppHandle, db: &Database) -> FocusResult<FocusState> {
+        self.end_phase(app, db, false, false).await
+    }
+
+    /// Cuts the active phase short and moves on to the next one.
+    pub async fn skip(&self, app: &AppHandle, db: &Database) -> FocusResult<FocusState> {
+        self.end_phase(app, db, false, true).await
+    }
+
+    async fn end_phase(&self, app: &AppHandle, db: &Database, completed: bool, advance: bool) -> FocusResult<FocusState> {
+        let config = Self::load_config(db).await?;
+        let current = self.snapshot();
+
+        match current.finish(&config, Utc::now(), completed, advance) {
+            Some((session, next)) => {
+                let session = Self::record(db, session).await?;
+                let next = self.apply(app, db, next).await?;
+                let _ = app.emit(FOCUS_PHASE_EVENT, FocusPhaseEnded { session, next: next.clone() });
+                Ok(next)
+            }
+            None => Ok(current),
+        }
+    }
+
+    /// Records a finished phase, skipping phases that never actually ran.
+    async fn record(db: &Database, session: NewFocusSession) -> FocusResult<Option<FocusSession>> {
+        if !session.completed && session.actual_seconds <= 0 {
+            return Ok(None);
+        }
+        Ok(Some(db.record_focus_session(session).await?))
+    }
+
+    /// Replaces and persists the state, then restarts the timer if needed.
+    async fn apply(&self, app: &AppHandle, db: &Database, state: FocusState) -> FocusResult<FocusState> {
+        db.set_setting(FOCUS_STATE_SETTING, &serde_json::to_string(&state)?).await?;
+
+        let generation = {
+            let mut guard = self.state.lock().unwrap();
+            *guard = state.clone();
+            self.generation.fetch_add(1, Ordering::SeqCst) + 1
+        };
+
+        update_tray(app, &state, state.remaining(Utc::now()));
+        if state.is_running() {
+            spawn_timer(app.clone(), generation);
+        }
+
+        Ok(state)
+    }
+}
+
+fn spawn_timer(app: AppHandle, generation: u64) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(StdDuration::from_secs(1));
+
+        loop {
+            interval.tick().await;
+
+            let engine = app.state::<FocusEngine>();
+            if engine.generation.load(Ordering::SeqCst) != generation {
+                break;
+            }
+
+            let state = engine.snapshot();
+            let remaining = state.remaining(Utc::now());
+            let _ = app.emit(FOCUS_TICK_EVENT, FocusTick { state: state.clone(), remaining_seconds: remaining });
+            update_tray(&app, &state, remaining);
+
+            if remaining == 0 {
+                let db = app.state::<Mutex<Option<Database>>>().lock().unwrap().clone();
+                if let Some(db) = db {
+                    if let Err(e) = engine.end_phase(&app, &db, true, true).await {
+                        eprintln!("Failed to finish focus phase: {}", e);
+                    }
+                }
+                break;
+            }
+        }
+    });
+}
+
+fn update_tray(app: &AppHandle, state: &FocusState, remaining: i64) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+
+    let tooltip = match state.phase {
+        Some(phase) => {
+            let label = match phase {
+                FocusPhase::Work => "Focus",
+                FocusPhase::ShortBreak | FocusPhase::LongBreak => "Break",
+            };
+            let paused = if state.is_running() { "" } else { " (paused)" };
+            format!("Yuflow - {} {:02}:{:02}{}", label, remaining / 60, remaining % 60, paused)
+        }
+        None => "Yuflow".to_string(),
+    };
+    let _ = tray.set_tooltip(Some(tooltip));
+}
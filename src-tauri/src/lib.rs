@@ -1,37 +1,147 @@
 mod commands;
-mod database;
-mod models;
-mod backup;
+mod quick_add;
+mod focus;
+mod urgency;
+mod workspaces;
+mod location;
+mod app_lock;
+mod privacy;
+mod maintenance;
 
 #[cfg(test)]
 mod tests;
 
 use std::sync::Mutex;
+use tauri::Manager;
 use commands::*;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let options = location::LaunchOptions::parse(std::env::args().skip(1))
+        .unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        });
+    let data_location = location::DataLocation::new(options)
+        .expect("Failed to resolve data location");
+    
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_sql::Builder::default().build())
-        .manage(Mutex::new(None::<database::Database>))
-        .invoke_handler(tauri::generate_handler![
+        .manage(Mutex::new(None::<yuflow_core::Database>))
+        .manage(focus::FocusEngine::default())
+        .manage(data_location)
+        .manage(app_lock::AppLock::default())
+        .manage(privacy::Vault::default())
+        .setup(|app| {
+            let handle = app.handle();
+            let root = location::DataLocation::from_app(handle).root(handle)?;
+            handle.state::<app_lock::AppLock>().load(root.join(app_lock::LOCK_FILE))?;
+            app_lock::spawn_watcher(handle.clone());
+            maintenance::spawn_scheduler(handle.clone());
+            Ok(())
+        })
+        .invoke_handler(app_lock::guard(tauri::generate_handler![
             init_database,
             get_tasks,
             get_task,
+            get_next_actions,
+            get_urgency_config,
+            set_urgency_config,
             create_task,
+            quick_add,
+            get_statistics,
             update_task,
             delete_task,
+            duplicate_task,
+            archive_task,
+            unarchive_task,
+            apply_archive_policy,
+            transition_task,
+            get_priorities,
+            create_priority,
+            update_priority,
+            delete_priority,
+            get_statuses,
+            create_status,
+            update_status,
+            reorder_statuses,
+            delete_status,
             get_categories,
             create_category,
             delete_category,
+            get_checklist,
+            add_checklist_item,
+            update_checklist_item,
+            delete_checklist_item,
+            get_daily_plan,
+            add_to_plan,
+            remove_from_plan,
+            reorder_plan,
+            set_plan_capacity,
+            carry_over_plan,
+            get_custom_fields,
+            create_custom_field,
+            update_custom_field,
+            delete_custom_field,
+            set_task_field_value,
+            get_links,
+            get_backlinks,
+            get_dangling_links,
+            get_attachments,
+            add_attachment,
+            delete_attachment,
+            get_templates,
+            create_template,
+            save_task_template,
+            save_category_template,
+            delete_template,
+            instantiate_template,
             get_setting,
             set_setting,
+            get_focus_config,
+            set_focus_config,
+            get_focus_state,
+            start_focus,
+            pause_focus,
+            resume_focus,
+            stop_focus,
+            skip_focus_phase,
+            get_focus_sessions,
             create_backup,
             restore_backup,
             list_backups,
-            delete_backup
-        ])
+            delete_backup,
+            list_workspaces,
+            create_workspace,
+            rename_workspace,
+            switch_workspace,
+            delete_workspace,
+            get_database_location,
+            open_database,
+            get_migration_status,
+            check_database,
+            repair_database,
+            get_database_size,
+            run_database_maintenance,
+            enable_database_encryption,
+            disable_database_encryption,
+            change_database_passphrase,
+            get_lock_status,
+            lock_app,
+            unlock_app,
+            report_activity,
+            set_lock_passphrase,
+            disable_app_lock,
+            set_lock_options,
+            get_private_status,
+            setup_private_content,
+            unlock_private_content,
+            lock_private_content,
+            change_private_passphrase,
+            set_task_private,
+            set_category_private
+        ]))
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use yuflow_core::encryption::KeyInfo;
+use crate::workspaces::{WorkspaceError, Workspaces};
+
+/// Marker file next to the executable that turns on portable mode.
+pub const PORTABLE_MARKER: &str = "yuflow.portable";
+/// Data directory next to the executable in portable mode
+pub const PORTABLE_DATA_DIR: &str = "data";
+
+/// Command line options that decide where data is read from.
+#[derive(Debug, Default, PartialEq)]
+pub struct LaunchOptions {
+    /// `--database <path>`: open this file instead of the active workspace
+    pub database: Option<PathBuf>,
+    /// `--portable`: keep all data next to the executable
+    pub portable: bool,
+}
+
+impl LaunchOptions {
+    /// Parses the arguments after the program name. Unknown arguments are
+    /// ignored so that platform-specific flags do not stop the app.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = LaunchOptions::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if arg == "--portable" {
+                options.portable = true;
+            } else if arg == "--database" || arg == "--db" {
+                let path = args.next().ok_or_else(|| format!("{} needs a file path", arg))?;
+                options.database = Some(PathBuf::from(path));
+            } else if let Some(path) = arg.strip_prefix("--database=").or_else(|| arg.strip_prefix("--db=")) {
+                options.database = Some(PathBuf::from(path));
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatabaseLocation {
+    pub path: String,
+    /// Opened by path rather than through a workspace
+    pub custom: bool,
+    pub portable: bool,
+    /// A passphrase is needed to open it
+    pub encrypted: bool,
+}
+
+impl DatabaseLocation {
+    pub fn new(app_handle: &AppHandle, path: &Path) -> Self {
+        let location = DataLocation::from_app(app_handle);
+        DatabaseLocation {
+            path: path.display().to_string(),
+            custom: location.database().as_deref() == Some(path),
+            portable: location.is_portable(),
+            encrypted: KeyInfo::path(path).exists(),
+        }
+    }
+}
+
+/// Where the app keeps its data, decided at launch and managed as state.
+#[derive(Debug, Default)]
+pub struct DataLocation {
+    /// Data directory next to the executable, in portable mode
+    portable_root: Option<PathBuf>,
+    /// Database file opened instead of the active workspace
+    database: Mutex<Option<PathBuf>>,
+}
+
+impl DataLocation {
+    pub fn new(options: LaunchOptions) -> std::io::Result<Self> {
+        let executable = std::env::current_exe()?;
+        let executable_dir = executable.parent().unwrap_or(Path::new("."));
+        let portable = options.portable || executable_dir.join(PORTABLE_MARKER).exists();
+
+        let database = match options.database {
+            Some(path) => Some(std::path::absolute(path)?),
+            None => None,
+        };
+
+        Ok(DataLocation {
+            portable_root: portable.then(|| executable_dir.join(PORTABLE_DATA_DIR)),
+            database: Mutex::new(database),
+        })
+    }
+
+    pub fn is_portable(&self) -> bool {
+        self.portable_root.is_some()
+    }
+
+    /// Directory holding the workspaces.
+    pub fn root(&self, app_handle: &AppHandle) -> tauri::Result<PathBuf> {
+        match &self.portable_root {
+            Some(root) => Ok(root.clone()),
+            None => app_handle.path().app_data_dir(),
+        }
+    }
+
+    /// The file opened with `--database` or `open_database`, if any.
+    pub fn database(&self) -> Option<PathBuf> {
+        self.database.lock().unwrap().clone()
+    }
+
+    pub fn set_database(&self, path: Option<PathBuf>) {
+        *self.database.lock().unwrap() = path;
+    }
+
+    /// File the app opens: the one chosen at launch, or else the database
+    /// of the active workspace.
+    pub fn database_path(app_handle: &AppHandle) -> Result<PathBuf, WorkspaceError> {
+        if let Some(path) = Self::from_app(app_handle).database() {
+            return Ok(path);
+        }
+
+        let workspaces = Workspaces::from_app(app_handle)?;
+        let workspace = workspaces.active()?;
+        Ok(workspaces.database_path(&workspace.id))
+    }
+
+    /// Where backups of the open database go: next to a file opened by
+    /// path, or else in the active workspace.
+    pub fn backup_dir(app_handle: &AppHandle) -> Result<PathBuf, WorkspaceError> {
+        if let Some(path) = Self::from_app(app_handle).database() {
+            let parent = path.parent().map(PathBuf::from).unwrap_or_default();
+            return Ok(parent.join("backups"));
+        }
+
+        let workspaces = Workspaces::from_app(app_handle)?;
+        let workspace = workspaces.active()?;
+        Ok(workspaces.backup_dir(&workspace.id))
+    }
+
+    pub fn from_app(app_handle: &AppHandle) -> tauri::State<'_, DataLocation> {
+        app_handle.state::<DataLocation>()
+    }
+}
@@ -0,0 +1,30 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use yuflow_core::database::Database;
+
+/// How often the open database is tidied up
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// First run after startup, so maintenance does not compete with loading
+const STARTUP_DELAY: Duration = Duration::from_secs(10 * 60);
+
+/// Runs `Database::run_maintenance` in the background on whichever database
+/// is open at the time. Failures are logged and retried on the next run.
+pub fn spawn_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+        let mut interval = tokio::time::interval(MAINTENANCE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let database = app.state::<Mutex<Option<Database>>>().lock().unwrap().clone();
+            if let Some(database) = database {
+                if let Err(e) = database.run_maintenance().await {
+                    eprintln!("Database maintenance failed: {}", e);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,253 @@
+use std::sync::Mutex;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use yuflow_core::database::Database;
+use yuflow_core::encryption::{self, KeyInfo, Sealer, SEALED_PREFIX};
+use yuflow_core::models::*;
+use yuflow_core::{AppError, Context};
+
+/// Holds the wrapped content key of the workspace
+pub const VAULT_SETTING: &str = "private_vault";
+
+/// Shown instead of the title of a private task while the key is locked
+pub const LOCKED_TITLE: &str = "Private task";
+
+/// Shown instead of checklist items and attachments of a private task while
+/// the key is locked
+pub const LOCKED_TEXT: &str = "Private";
+
+const NONCE_LENGTH: usize = 12;
+
+/// The content key, encrypted under a key derived from the passphrase. Only
+/// the wrapped form is stored, so changing the passphrase re-wraps the key
+/// and leaves the encrypted tasks as they are.
+#[derive(Debug, Serialize, Deserialize)]
+struct VaultRecord {
+    key_info: KeyInfo,
+    wrapped_key: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VaultStatus {
+    /// A passphrase has been set up for private content
+    pub configured: bool,
+    pub unlocked: bool,
+}
+
+pub use yuflow_core::encryption::is_sealed;
+
+fn seal_with(cipher: &Aes256Gcm, plaintext: &[u8]) -> Result<String, AppError> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| AppError::Storage("Failed to encrypt".to_string()))?;
+
+    let mut payload = nonce.to_vec();
+    payload.extend(ciphertext);
+    Ok(format!("{}{}", SEALED_PREFIX, encryption::to_hex(&payload)))
+}
+
+/// What using the key while it is locked fails with.
+pub fn locked() -> AppError {
+    AppError::Locked("Unlock private tasks first".to_string())
+}
+
+fn open_with(cipher: &Aes256Gcm, sealed: &str) -> Option<Vec<u8>> {
+    let payload = encryption::from_hex(sealed.strip_prefix(SEALED_PREFIX)?)?;
+    if payload.len() < NONCE_LENGTH {
+        return None;
+    }
+    let (nonce, ciphertext) = payload.split_at(NONCE_LENGTH);
+    cipher.decrypt(Nonce::from_slice(nonce), ciphertext).ok()
+}
+
+/// Key for private tasks. It only ever lives in memory, from unlocking until
+/// the vault or the app is locked again.
+#[derive(Default)]
+pub struct Vault {
+    cipher: Mutex<Option<Aes256Gcm>>,
+}
+
+impl Vault {
+    async fn load_record(db: &Database) -> Result<Option<VaultRecord>, AppError> {
+        let record = db.get_setting(VAULT_SETTING)
+            .await
+            .context("Failed to get private key")?;
+        record
+            .map(|json| serde_json::from_str(&json).context("Invalid private key record"))
+            .transpose()
+    }
+
+    async fn save_record(db: &Database, key: &[u8], passphrase: &str) -> Result<(), AppError> {
+        let key_info = KeyInfo::generate();
+        let wrapping = key_info.derive(passphrase).map_err(AppError::Storage)?;
+        let wrapped_key = seal_with(&Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(wrapping.as_bytes())), key)?;
+
+        let json = serde_json::to_string(&VaultRecord { key_info, wrapped_key })
+            .context("Failed to save private key")?;
+        db.set_setting(VAULT_SETTING, &json)
+            .await
+            .context("Failed to save private key")
+    }
+
+    async fn unwrap_key(db: &Database, passphrase: &str) -> Result<Vec<u8>, AppError> {
+        let record = Self::load_record(db)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Private tasks are not set up".to_string()))?;
+        let wrapping = record.key_info.derive(passphrase).map_err(AppError::Storage)?;
+        open_with(&Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(wrapping.as_bytes())), &record.wrapped_key)
+            .ok_or_else(|| AppError::invalid_field("passphrase", "Wrong passphrase"))
+    }
+
+    pub async fn status(&self, db: &Database) -> Result<VaultStatus, AppError> {
+        Ok(VaultStatus {
+            configured: Self::load_record(db).await?.is_some(),
+            unlocked: self.is_unlocked(),
+        })
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.cipher.lock().unwrap().is_some()
+    }
+
+    /// Creates the content key for a workspace and unlocks it.
+    pub async fn setup(&self, db: &Database, passphrase: &str) -> Result<(), AppError> {
+        encryption::check_passphrase(passphrase)?;
+        if Self::load_record(db).await?.is_some() {
+            return Err(AppError::Conflict("Private tasks are already set up".to_string()));
+        }
+
+        let key = Aes256Gcm::generate_key(OsRng);
+        Self::save_record(db, &key, passphrase).await?;
+        *self.cipher.lock().unwrap() = Some(Aes256Gcm::new(&key));
+        Ok(())
+    }
+
+    pub async fn unlock(&self, db: &Database, passphrase: &str) -> Result<(), AppError> {
+        let key = Self::unwrap_key(db, passphrase).await?;
+        *self.cipher.lock().unwrap() = Some(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)));
+        Ok(())
+    }
+
+    pub async fn change_passphrase(&self, db: &Database, current: &str, passphrase: &str) -> Result<(), AppError> {
+        encryption::check_passphrase(passphrase)?;
+        let key = Self::unwrap_key(db, current).await?;
+        Self::save_record(db, &key, passphrase).await
+    }
+
+    pub fn lock(&self) {
+        *self.cipher.lock().unwrap() = None;
+    }
+
+    pub fn seal(&self, plaintext: &str) -> Result<String, AppError> {
+        let cipher = self.cipher.lock().unwrap();
+        let cipher = cipher.as_ref().ok_or_else(locked)?;
+        seal_with(cipher, plaintext.as_bytes())
+    }
+
+    /// Plaintext of `text`, which is returned as is when it is not sealed.
+    pub fn open(&self, text: &str) -> Result<String, AppError> {
+        if !is_sealed(text) {
+            return Ok(text.to_string());
+        }
+        let cipher = self.cipher.lock().unwrap();
+        let cipher = cipher.as_ref().ok_or_else(locked)?;
+        open_with(cipher, text)
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| AppError::Storage("Failed to decrypt private task".to_string()))
+    }
+
+    /// Decrypts a task for display, or swaps in a placeholder while locked.
+    /// A task whose description or custom field values cannot be decrypted
+    /// is shown locked too, so saving it cannot overwrite the stored text.
+    pub fn reveal(&self, task: &mut Task) {
+        if !is_sealed(&task.title) {
+            return;
+        }
+        let opened = self.open(&task.title).and_then(|title| {
+            let description = task.description.as_deref().map(|d| self.open(d)).transpose()?;
+            let custom_fields = task.custom_fields
+                .iter()
+                .map(|(field_id, value)| Ok((*field_id, self.open(value)?)))
+                .collect::<Result<_, AppError>>()?;
+            Ok((title, description, custom_fields))
+        });
+        match opened {
+            Ok((title, description, custom_fields)) => {
+                task.title = title;
+                task.description = description;
+                task.custom_fields = custom_fields;
+            }
+            Err(_) => {
+                task.title = LOCKED_TITLE.to_string();
+                task.description = None;
+                task.custom_fields.clear();
+                task.locked = true;
+            }
+        }
+    }
+
+    pub fn revealed(&self, mut task: Task) -> Task {
+        self.reveal(&mut task);
+        task
+    }
+
+    pub fn reveal_all(&self, tasks: &mut [Task]) {
+        for task in tasks {
+            self.reveal(task);
+        }
+    }
+
+    pub fn reveal_title(&self, title: &str) -> String {
+        self.open(title).unwrap_or_else(|_| LOCKED_TITLE.to_string())
+    }
+
+    pub fn reveal_checklist(&self, items: &mut [ChecklistItem]) {
+        for item in items {
+            item.title = self.open(&item.title).unwrap_or_else(|_| LOCKED_TEXT.to_string());
+        }
+    }
+
+    pub fn reveal_attachments(&self, attachments: &mut [Attachment]) {
+        for attachment in attachments {
+            match self.open(&attachment.target) {
+                Ok(target) => {
+                    attachment.target = target;
+                    attachment.label = attachment.label.as_deref().map(|l| self.open(l)).transpose().ok().flatten();
+                }
+                Err(_) => {
+                    attachment.target = String::new();
+                    attachment.label = Some(LOCKED_TEXT.to_string());
+                }
+            }
+        }
+    }
+
+    /// Encrypts `text` when it belongs to a task whose title is encrypted.
+    pub fn seal_for(&self, task: &Task, text: &str) -> Result<String, AppError> {
+        if is_sealed(&task.title) {
+            self.seal(text)
+        } else {
+            Ok(text.to_string())
+        }
+    }
+
+    /// Encrypts or decrypts a task's content so that it matches its own
+    /// flag and its category's. Returns the task as stored.
+    pub async fn sync_task(&self, db: &Database, id: i64) -> Result<Option<Task>, AppError> {
+        db.sync_private_content(id, self)
+            .await
+            .context("Failed to update task")
+    }
+}
+
+impl Sealer for Vault {
+    fn seal(&self, plaintext: &str) -> Result<String, AppError> {
+        Vault::seal(self, plaintext)
+    }
+
+    fn open(&self, text: &str) -> Result<String, AppError> {
+        Vault::open(self, text)
+    }
+}
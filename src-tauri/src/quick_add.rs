@@ -0,0 +1,271 @@
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone, Weekday};
+use serde::{Deserialize, Serialize};
+use yuflow_core::models::*;
+
+/// Result of parsing a quick-add line. Nothing is written to the database;
+/// the frontend shows the preview and commits it with `create_task`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuickAddPreview {
+    pub task: CreateTaskInput,
+    pub category: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ParsedQuickAdd {
+    pub title: String,
+    pub priority: Option<String>,
+    pub tags: Vec<String>,
+    pub category: Option<String>,
+    pub due_date: Option<DateTime<FixedOffset>>,
+}
+
+// Time used when only a day is given, e.g. "tomorrow"
+const END_OF_DAY: (u32, u32) = (23, 59);
+
+const PREPOSITIONS: [&str; 4] = ["on", "by", "at", "due"];
+
+// "in N days" further out than this (about 100 years) is not read as a date
+const MAX_DAYS_AHEAD: i64 = 36_500;
+
+/// Parses a line such as `Send invoice to ACME tomorrow 5pm !high #billing @Work`.
+///
+/// `now` carries the user's UTC offset, so relative dates ("tomorrow",
+/// "friday", "in 3 days") resolve against the user's calendar day.
+/// `!name` only sets the priority when `priorities` contains the name (or
+/// one of the short aliases of the built-in levels).
+pub fn parse(text: &str, now: DateTime<FixedOffset>, priorities: &[String]) -> ParsedQuickAdd {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let today = now.date_naive();
+
+    let mut parsed = ParsedQuickAdd::default();
+    let mut title_words: Vec<&str> = Vec::new();
+    let mut date: Option<NaiveDate> = None;
+    let mut time: Option<NaiveTime> = None;
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+
+        if let Some(tag) = token.strip_prefix('#').filter(|t| !t.is_empty()) {
+            let tag = tag.to_lowercase();
+            if !parsed.tags.contains(&tag) {
+                parsed.tags.push(tag);
+            }
+            i += 1;
+            continue;
+        }
+
+        if let Some(category) = token.strip_prefix('@').filter(|c| !c.is_empty()) {
+            parsed.category = Some(category.replace('_', " "));
+            i += 1;
+            continue;
+        }
+
+        if let Some(priority) = token.strip_prefix('!').and_then(|p| find_priority(p, priorities)) {
+            parsed.priority = Some(priority.to_string());
+            i += 1;
+            continue;
+        }
+
+        // A leading preposition is only swallowed when a date or time follows it
+        let skip = usize::from(PREPOSITIONS.contains(&token.to_lowercase().as_str()));
+
+        if date.is_none() {
+            if let Some((d, used)) = parse_date_at(&tokens, i + skip, today) {
+                date = Some(d);
+                i += skip + used;
+                continue;
+            }
+        }
+
+        if time.is_none() {
+            if let Some((t, used)) = parse_time_at(&tokens, i + skip) {
+                time = Some(t);
+                i += skip + used;
+                continue;
+            }
+        }
+
+        title_words.push(token);
+        i += 1;
+    }
+
+    parsed.title = title_words.join(" ");
+
+    if date.is_some() || time.is_some() {
+        let day = date.unwrap_or_else(|| match time {
+            // A bare time that has already passed today means tomorrow
+            Some(t) if t <= now.time() => today + Duration::days(1),
+            _ => today,
+        });
+        let time = time.unwrap_or_else(|| {
+            NaiveTime::from_hms_opt(END_OF_DAY.0, END_OF_DAY.1, 0).expect("valid end of day")
+        });
+
+        parsed.due_date = now
+            .offset()
+            .from_local_datetime(&day.and_time(time))
+            .single();
+    }
+
+    parsed
+}
+
+/// Turns a parsed line into a `CreateTaskInput`, resolving the category
+/// name against the existing categories.
+pub fn build_preview(parsed: ParsedQuickAdd, categories: &[Category]) -> QuickAddPreview {
+    let mut warnings = Vec::new();
+
+    let category = parsed.category.as_ref().and_then(|name| {
+        let found = categories.iter().find(|c| c.name.eq_ignore_ascii_case(name));
+        if found.is_none() {
+            warnings.push(format!("Unknown category '{}'", name));
+        }
+        found
+    });
+
+    if parsed.title.is_empty() {
+        warnings.push("Title is empty".to_string());
+    }
+
+    QuickAddPreview {
+        task: CreateTaskInput {
+            title: parsed.title,
+            description: None,
+            priority: parsed.priority,
+            category_id: category.map(|c| c.id),
+            due_date: parsed.due_date.map(|d| d.to_rfc3339()),
+            tags: parsed.tags,
+            status_id: None,
+            parent_id: None,
+        },
+        category: category.map(|c| c.name.clone()),
+        warnings,
+    }
+}
+
+/// Matches a `!priority` token against the priority names, case-insensitively.
+fn find_priority<'a>(value: &str, priorities: &'a [String]) -> Option<&'a str> {
+    let value = value.to_lowercase();
+    let alias = match value.as_str() {
+        "l" => "low",
+        "med" | "m" => "medium",
+        "h" => "high",
+        other => other,
+    };
+
+    priorities
+        .iter()
+        .find(|name| name.eq_ignore_ascii_case(&value) || name.eq_ignore_ascii_case(alias))
+        .map(String::as_str)
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    match value {
+        "monday" => Some(Weekday::Mon),
+        "tuesday" => Some(Weekday::Tue),
+        "wednesday" => Some(Weekday::Wed),
+        "thursday" => Some(Weekday::Thu),
+        "friday" => Some(Weekday::Fri),
+        "saturday" => Some(Weekday::Sat),
+        "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Days from `today` until `weekday`; `strictly_after` skips today itself.
+fn days_until(today: NaiveDate, weekday: Weekday, strictly_after: bool) -> i64 {
+    let diff = (weekday.num_days_from_monday() as i64 - today.weekday().num_days_from_monday() as i64 + 7) % 7;
+    if diff == 0 && strictly_after {
+        7
+    } else {
+        diff
+    }
+}
+
+/// Tries to read a date starting at `tokens[i]`, returning it together with
+/// the number of tokens it spans.
+fn parse_date_at(tokens: &[&str], i: usize, today: NaiveDate) -> Option<(NaiveDate, usize)> {
+    let word = tokens.get(i)?.to_lowercase();
+    let next = tokens.get(i + 1).map(|t| t.to_lowercase());
+
+    match word.as_str() {
+        "today" => return Some((today, 1)),
+        "tomorrow" | "tmrw" => return Some((today + Duration::days(1), 1)),
+        "next" => {
+            let next = next?;
+            if next == "week" {
+                return Some((today + Duration::days(7), 2));
+            }
+            let weekday = parse_weekday(&next)?;
+            return Some((today + Duration::days(days_until(today, weekday, true)), 2));
+        }
+        "in" => {
+            let amount: i64 = next?.parse().ok().filter(|amount| *amount > 0)?;
+            let unit = tokens.get(i + 2)?.to_lowercase();
+            let days = match unit.trim_end_matches('s') {
+                "day" => amount,
+                "week" => amount.checked_mul(7)?,
+                _ => return None,
+            };
+            if days > MAX_DAYS_AHEAD {
+                return None;
+            }
+            return today.checked_add_signed(Duration::try_days(days)?).map(|date| (date, 3));
+        }
+        _ => {}
+    }
+
+    if let Some(weekday) = parse_weekday(&word) {
+        return Some((today + Duration::days(days_until(today, weekday, false)), 1));
+    }
+
+    NaiveDate::parse_from_str(&word, "%Y-%m-%d").ok().map(|d| (d, 1))
+}
+
+/// Tries to read a time of day starting at `tokens[i]`: `5pm`, `5:30pm`,
+/// `5 pm`, `17:00` or `noon`.
+fn parse_time_at(tokens: &[&str], i: usize) -> Option<(NaiveTime, usize)> {
+    let word = tokens.get(i)?.to_lowercase();
+
+    if word == "noon" {
+        return NaiveTime::from_hms_opt(12, 0, 0).map(|t| (t, 1));
+    }
+
+    if let Some(time) = parse_clock(&word) {
+        return Some((time, 1));
+    }
+
+    let meridiem = tokens.get(i + 1)?.to_lowercase();
+    if meridiem == "am" || meridiem == "pm" {
+        return parse_clock(&format!("{}{}", word, meridiem)).map(|t| (t, 2));
+    }
+
+    None
+}
+
+fn parse_clock(value: &str) -> Option<NaiveTime> {
+    let (clock, meridiem) = if let Some(c) = value.strip_suffix("am") {
+        (c, Some(false))
+    } else if let Some(c) = value.strip_suffix("pm") {
+        (c, Some(true))
+    } else {
+        (value, None)
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) if m.len() == 2 => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        // A bare number is only a time when it carries am/pm
+        None if meridiem.is_some() => (clock.parse::<u32>().ok()?, 0),
+        _ => return None,
+    };
+
+    let hour = match meridiem {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
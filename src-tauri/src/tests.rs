@@ -1,8 +1,9 @@
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::*;
-    use crate::database::Database;
+    use validator::Validate;
+    use yuflow_core::models::*;
+    use yuflow_core::database::Database;
     use tempfile::TempDir;
     use std::path::PathBuf;
 
@@ -40,333 +41,441 @@ mod tests {
     async fn setup_test_database() -> (TestApp, Database) {
         let test_app = TestApp::new();
         
-        // Create a mock database URL for testing
-        let db_path = test_app.app_dir.join("test.db");
-        let database_url = format!("sqlite:{}", db_path.display());
-        
-        let pool = sqlx::SqlitePool::connect(&database_url).await
-            .expect("Failed to connect to test database");
-        
-        // Run migrations
-        sqlx::migrate!("./migrations").run(&pool).await
-            .expect("Failed to run migrations");
-        
-        let database = Database { pool };
+        let database = Database::open_in_memory().await
+            .expect("Failed to open test database");
         
         (test_app, database)
     }
 
+    #[test]
+    fn test_quick_add_parse() {
+        use crate::quick_add;
+        use chrono::{FixedOffset, TimeZone};
+
+        let offset = FixedOffset::east_opt(-3 * 3600).unwrap();
+        // Wednesday afternoon, local time
+        let now = offset.with_ymd_and_hms(2025, 3, 12, 14, 0, 0).unwrap();
+        let priorities: Vec<String> = ["low", "medium", "high"].map(String::from).to_vec();
+
+        let parsed = quick_add::parse("Send invoice to ACME tomorrow 5pm !high #billing @Work", now, &priorities);
+        
+        assert_eq!(parsed.title, "Send invoice to ACME");
+        assert_eq!(parsed.priority, Some("high".to_string()));
+        assert_eq!(parsed.tags, vec!["billing"]);
+        assert_eq!(parsed.category, Some("Work".to_string()));
+        assert_eq!(parsed.due_date.unwrap().to_rfc3339(), "2025-03-13T17:00:00-03:00");
+
+        // Prepositions are only consumed together with a date
+        let parsed = quick_add::parse("Meet Ana at the office on friday", now, &priorities);
+        assert_eq!(parsed.title, "Meet Ana at the office");
+        assert_eq!(parsed.due_date.unwrap().to_rfc3339(), "2025-03-14T23:59:00-03:00");
+
+        // A time that already passed today rolls over to tomorrow
+        let parsed = quick_add::parse("Call mom 9:30am", now, &priorities);
+        assert_eq!(parsed.title, "Call mom");
+        assert_eq!(parsed.due_date.unwrap().to_rfc3339(), "2025-03-13T09:30:00-03:00");
+
+        let parsed = quick_add::parse("Renew passport in 2 weeks !wat", now, &priorities);
+        assert_eq!(parsed.title, "Renew passport !wat");
+        assert_eq!(parsed.priority, None);
+        assert_eq!(parsed.due_date.unwrap().date_naive().to_string(), "2025-03-26");
+
+        // Amounts that are not a reasonable number of days stay in the title
+        for text in ["Call mom in 99999999999999 days", "Call mom in 9999999999999999 weeks", "Call mom in -3 days"] {
+            let parsed = quick_add::parse(text, now, &priorities);
+            assert_eq!(parsed.title, text);
+            assert_eq!(parsed.due_date, None);
+        }
+    }
+
     #[tokio::test]
-    async fn test_create_and_get_task() {
+    async fn test_quick_add_preview() {
+        use crate::quick_add;
+
         let (_app, db) = setup_test_database().await;
         
-        let task_input = CreateTaskInput {
-            title: "Test Task".to_string(),
-            description: Some("Test Description".to_string()),
-            priority: Some("high".to_string()),
-            category_id: None,
-            due_date: None,
-        };
-
-        // Test validation
-        assert!(task_input.validate().is_ok());
+        let categories = db.get_categories().await
+            .expect("Failed to get categories");
+        let now = chrono::Utc::now().fixed_offset();
 
-        // Create task
-        let created_task = db.create_task(task_input).await
-            .expect("Failed to create task");
+        let preview = quick_add::build_preview(quick_add::parse("Water plants @general #home", now, &[]), &categories);
         
-        assert_eq!(created_task.title, "Test Task");
-        assert_eq!(created_task.description, Some("Test Description".to_string()));
-        assert_eq!(created_task.priority, "high");
-        assert!(!created_task.completed);
+        assert_eq!(preview.task.title, "Water plants");
+        assert_eq!(preview.task.category_id, Some(1));
+        assert_eq!(preview.category, Some("General".to_string()));
+        assert!(preview.warnings.is_empty());
+        assert!(preview.task.validate().is_ok());
 
-        // Get task
-        let retrieved_task = db.get_task(created_task.id).await
-            .expect("Failed to get task")
-            .expect("Task not found");
+        let preview = quick_add::build_preview(quick_add::parse("Water plants @garden", now, &[]), &categories);
         
-        assert_eq!(retrieved_task.id, created_task.id);
-        assert_eq!(retrieved_task.title, created_task.title);
+        assert_eq!(preview.task.category_id, None);
+        assert_eq!(preview.warnings, vec!["Unknown category 'garden'"]);
     }
 
     #[tokio::test]
-    async fn test_update_task() {
+    async fn test_focus_session_cycle() {
+        use crate::focus::{FocusConfig, FocusPhase, FocusState};
+        use chrono::{Duration, Utc};
+
         let (_app, db) = setup_test_database().await;
         
-        let task_input = CreateTaskInput {
-            title: "Original Title".to_string(),
-            description: None,
-            priority: Some("low".to_string()),
-            category_id: None,
-            due_date: None,
-        };
-
-        let created_task = db.create_task(task_input).await
-            .expect("Failed to create task");
-
-        let update_input = UpdateTaskInput {
-            id: created_task.id,
-            title: Some("Updated Title".to_string()),
-            description: Some("New Description".to_string()),
-            completed: Some(true),
-            priority: Some("high".to_string()),
-            category_id: None,
-            due_date: None,
+        let config = FocusConfig {
+            long_break_every: 2,
+            ..FocusConfig::default()
         };
-
-        let updated_task = db.update_task(update_input).await
-            .expect("Failed to update task");
-
-        assert_eq!(updated_task.title, "Updated Title");
-        assert_eq!(updated_task.description, Some("New Description".to_string()));
-        assert_eq!(updated_task.priority, "high");
-        assert!(updated_task.completed);
+        let start = Utc::now();
+
+        let mut state = FocusState::begin(&config, FocusPhase::Work, None, 0, true, start);
+        assert!(state.is_running());
+        assert_eq!(state.remaining(start + Duration::minutes(5)), 20 * 60);
+
+        // Pausing freezes the remaining time
+        state.pause(start + Duration::minutes(5));
+        assert!(!state.is_running());
+        assert_eq!(state.remaining(start + Duration::minutes(30)), 20 * 60);
+        state.resume(start + Duration::minutes(10));
+
+        let end = start + Duration::minutes(30);
+        let (session, next) = state.finish(&config, end, true, true).unwrap();
+        assert_eq!(session.kind, "work");
+        assert_eq!(session.actual_seconds, 25 * 60);
+        assert_eq!(next.phase, Some(FocusPhase::ShortBreak));
+        assert!(next.is_running());
+        assert_eq!(next.completed_work_sessions, 1);
+
+        let recorded = db.record_focus_session(session).await
+            .expect("Failed to record session");
+        let sessions = db.get_focus_sessions(None).await
+            .expect("Failed to get sessions");
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, recorded.id);
+        assert!(sessions[0].completed);
+
+        // Breaks do not auto-start work by default
+        let (_, work) = next.finish(&config, end + Duration::minutes(5), true, true).unwrap();
+        assert_eq!(work.phase, Some(FocusPhase::Work));
+        assert!(!work.is_running());
+
+        let mut work = work;
+        work.resume(end + Duration::minutes(6));
+        let (_, long_break) = work.finish(&config, end + Duration::minutes(31), true, true).unwrap();
+        assert_eq!(long_break.phase, Some(FocusPhase::LongBreak));
+        assert_eq!(long_break.planned_seconds, 15 * 60);
+
+        // Stopping early records partial time and goes idle
+        let (partial, idle) = long_break.finish(&config, end + Duration::minutes(35), false, false).unwrap();
+        assert!(!partial.completed);
+        assert_eq!(partial.actual_seconds, 4 * 60);
+        assert_eq!(idle, FocusState::default());
     }
 
     #[tokio::test]
-    async fn test_delete_task() {
+    async fn test_urgency_scoring() {
+        use crate::urgency::{UrgencyCoefficients, UrgencyContext};
+
         let (_app, db) = setup_test_database().await;
-        
-        let task_input = CreateTaskInput {
-            title: "Task to Delete".to_string(),
+        let now = chrono::Utc::now();
+        let input = |title: &str, priority: &str, due: Option<chrono::DateTime<chrono::Utc>>, tags: Vec<&str>, parent_id| CreateTaskInput {
+            title: title.to_string(),
             description: None,
-            priority: Some("medium".to_string()),
-            category_id: None,
-            due_date: None,
+            priority: Some(priority.to_string()),
+            category_id: Some(1),
+            due_date: due.map(|d| d.to_rfc3339()),
+            tags: tags.into_iter().map(str::to_string).collect(),
+            status_id: None,
+            parent_id,
         };
-
-        let created_task = db.create_task(task_input).await
+        
+        let overdue = db.create_task(input("Overdue", "low", Some(now - chrono::Duration::days(10)), vec![], None)).await
+            .expect("Failed to create task");
+        let important = db.create_task(input("Important", "high", None, vec![], None)).await
+            .expect("Failed to create task");
+        let next = db.create_task(input("Tagged next", "medium", None, vec!["next"], None)).await
+            .expect("Failed to create task");
+        let step = db.create_task(input("First step", "medium", None, vec![], Some(important.id))).await
             .expect("Failed to create task");
-
-        // Delete task
-        db.delete_task(created_task.id).await
-            .expect("Failed to delete task");
-
-        // Verify task is deleted
-        let retrieved_task = db.get_task(created_task.id).await
-            .expect("Failed to query task");
         
-        assert!(retrieved_task.is_none());
-    }
-
-    #[tokio::test]
-    async fn test_create_and_get_category() {
-        let (_app, db) = setup_test_database().await;
+        let mut coefficients = UrgencyCoefficients::load(&db).await.expect("Failed to load coefficients");
+        assert_eq!(coefficients, UrgencyCoefficients::default());
+        coefficients.tags.insert("next".to_string(), 15.0);
         
-        let category_input = CreateCategoryInput {
-            name: "Work".to_string(),
-            color: Some("#FF5733".to_string()),
-        };
-
-        // Test validation
-        assert!(category_input.validate().is_ok());
-
-        let created_category = db.create_category(category_input).await
-            .expect("Failed to create category");
-
-        assert_eq!(created_category.name, "Work");
-        assert_eq!(created_category.color, "#FF5733");
-
-        // Get all categories
-        let categories = db.get_categories().await
-            .expect("Failed to get categories");
+        let priorities = db.get_priorities().await.expect("Failed to get priorities");
+        let tasks = db.get_tasks(None).await.expect("Failed to get tasks");
+        let context = UrgencyContext::new(coefficients, &priorities, &tasks, Some(step.id), now);
         
-        assert!(categories.iter().any(|c| c.name == "Work"));
+        // 15 + 0.8 for the tag, 12 for the overdue task, 6 - 5 for the blocked parent,
+        // 3 + 8 + 4 for the blocking subtask with a running timer
+        let ranked: Vec<i64> = context.next_actions(tasks.clone(), 10).iter().map(|s| s.task.id).collect();
+        assert_eq!(ranked, vec![next.id, step.id, overdue.id, important.id]);
+        assert_eq!(context.next_actions(tasks.clone(), 2).len(), 2);
+        
+        let mut sorted = tasks;
+        context.sort(&mut sorted, false);
+        assert_eq!(sorted[0].id, important.id);
     }
 
     #[tokio::test]
-    async fn test_task_filters() {
-        let (_app, db) = setup_test_database().await;
+    async fn test_workspaces() {
+        use crate::workspaces::{Workspaces, DEFAULT_WORKSPACE};
         
-        // Create test tasks
-        let task1 = CreateTaskInput {
-            title: "High Priority Task".to_string(),
-            description: None,
-            priority: Some("high".to_string()),
-            category_id: None,
-            due_date: None,
-        };
+        let test_app = TestApp::new();
+        let workspaces = Workspaces::new(test_app.app_dir.clone());
         
-        let task2 = CreateTaskInput {
-            title: "Low Priority Task".to_string(),
-            description: None,
-            priority: Some("low".to_string()),
-            category_id: None,
-            due_date: None,
-        };
-
-        let created_task1 = db.create_task(task1).await.expect("Failed to create task1");
-        let created_task2 = db.create_task(task2).await.expect("Failed to create task2");
-
-        // Mark task1 as completed
-        db.update_task(UpdateTaskInput {
-            id: created_task1.id,
-            title: None,
-            description: None,
-            completed: Some(true),
-            priority: None,
-            category_id: None,
-            due_date: None,
-        }).await.expect("Failed to update task1");
-
-        // Test filter by completed status
-        let completed_filter = TaskFilters {
-            completed: Some(true),
-            priority: None,
-            category_id: None,
-            search: None,
-            due_date_before: None,
-            due_date_after: None,
-        };
-
-        let completed_tasks = db.get_tasks(Some(completed_filter)).await
-            .expect("Failed to get completed tasks");
+        // Existing installs start out in the default workspace
+        let active = workspaces.active().expect("Failed to get active workspace");
+        assert_eq!(active.id, DEFAULT_WORKSPACE);
+        assert_eq!(workspaces.database_path(DEFAULT_WORKSPACE), test_app.app_dir.join("yuflow.db"));
         
-        assert_eq!(completed_tasks.len(), 1);
-        assert_eq!(completed_tasks[0].title, "High Priority Task");
-
-        // Test filter by priority
-        let priority_filter = TaskFilters {
-            completed: None,
-            priority: Some("low".to_string()),
-            category_id: None,
-            search: None,
-            due_date_before: None,
-            due_date_after: None,
-        };
-
-        let low_priority_tasks = db.get_tasks(Some(priority_filter)).await
-            .expect("Failed to get low priority tasks");
+        let work = workspaces.create("Work Stuff").expect("Failed to create workspace");
+        assert_eq!(work.id, "work-stuff");
+        assert!(workspaces.backup_dir(&work.id).is_dir());
+        assert!(workspaces.create("work stuff").is_err());
+        assert!(workspaces.create("  ").is_err());
+        
+        let renamed = workspaces.rename(&work.id, "Office").expect("Failed to rename workspace");
+        assert_eq!(renamed.id, work.id);
+        
+        // A new workspace with the old name gets its own directory
+        let again = workspaces.create("Work Stuff").expect("Failed to create workspace");
+        assert_eq!(again.id, "work-stuff-2");
+        
+        // Databases and their settings are separate
+        let default_db = Database::open(&workspaces.database_path(DEFAULT_WORKSPACE)).await
+            .expect("Failed to open default workspace");
+        let work_db = Database::open(&workspaces.database_path(&work.id)).await
+            .expect("Failed to open workspace");
+        default_db.set_setting("theme", "dark").await.expect("Failed to set setting");
+        assert_eq!(work_db.get_setting("theme").await.expect("Failed to get setting"), None);
+        
+        workspaces.set_active(&work.id).expect("Failed to switch workspace");
+        let list = workspaces.list().expect("Failed to list workspaces");
+        assert_eq!(list.len(), 3);
+        assert!(list.iter().any(|w| w.active && w.workspace.name == "Office"));
         
-        assert_eq!(low_priority_tasks.len(), 1);
-        assert_eq!(low_priority_tasks[0].title, "Low Priority Task");
+        // Neither the active nor the default workspace can be deleted
+        assert!(workspaces.delete(&work.id).is_err());
+        assert!(workspaces.delete(DEFAULT_WORKSPACE).is_err());
+        
+        workspaces.delete(&again.id).expect("Failed to delete workspace");
+        assert!(!workspaces.directory(&again.id).exists());
+        assert!(workspaces.get(&again.id).is_err());
+        assert_eq!(workspaces.active().expect("Failed to get active workspace").id, work.id);
     }
 
     #[tokio::test]
-    async fn test_settings() {
-        let (_app, db) = setup_test_database().await;
+    async fn test_open_database_path() {
+        use crate::location::LaunchOptions;
         
-        // Set a setting
-        db.set_setting("theme", "dark").await
-            .expect("Failed to set setting");
-
-        // Get the setting
-        let value = db.get_setting("theme").await
-            .expect("Failed to get setting");
+        let test_app = TestApp::new();
+        let path = test_app.app_dir.join("elsewhere").join("mine.db");
         
-        assert_eq!(value, Some("dark".to_string()));
-
-        // Get non-existent setting
-        let missing = db.get_setting("nonexistent").await
-            .expect("Failed to query missing setting");
+        // The file and its directory are created and migrated on open
+        let db = Database::open(&path).await.expect("Failed to open database");
+        assert_eq!(db.path(), Some(path.as_path()));
+        assert!(db.get_priorities().await.expect("Failed to get priorities").len() >= 3);
         
-        assert!(missing.is_none());
-
-        // Update setting
-        db.set_setting("theme", "light").await
-            .expect("Failed to update setting");
-
-        let updated_value = db.get_setting("theme").await
-            .expect("Failed to get updated setting");
+        // A second writer is refused until the first one closes
+        assert!(Database::open(&path).await.is_err());
+        let clone = db.clone();
+        drop(db);
+        assert!(Database::open(&path).await.is_err());
+        drop(clone);
+        let reopened = Database::open(&path).await.expect("Failed to reopen database");
+        assert_eq!(reopened.path(), Some(path.as_path()));
         
-        assert_eq!(updated_value, Some("light".to_string()));
+        assert!(Database::open(&test_app.app_dir).await.is_err());
+        
+        let args = |list: &[&str]| LaunchOptions::parse(list.iter().map(|a| a.to_string()));
+        assert_eq!(args(&[]).unwrap(), LaunchOptions::default());
+        let options = args(&["--portable", "--database", "/data/yuflow.db"]).unwrap();
+        assert!(options.portable);
+        assert_eq!(options.database, Some(PathBuf::from("/data/yuflow.db")));
+        assert_eq!(args(&["--db=tasks.db"]).unwrap().database, Some(PathBuf::from("tasks.db")));
+        assert!(args(&["--database"]).is_err());
     }
 
     #[tokio::test]
-    async fn test_validation_errors() {
-        // Test invalid task title (empty)
-        let invalid_task = CreateTaskInput {
-            title: "".to_string(),
-            description: None,
-            priority: Some("high".to_string()),
-            category_id: None,
-            due_date: None,
-        };
-
-        assert!(invalid_task.validate().is_err());
-
-        // Test invalid priority
-        let invalid_priority = CreateTaskInput {
-            title: "Valid Title".to_string(),
-            description: None,
-            priority: Some("invalid".to_string()),
-            category_id: None,
-            due_date: None,
-        };
-
-        assert!(invalid_priority.validate().is_err());
-
-        // Test invalid category name (empty)
-        let invalid_category = CreateCategoryInput {
-            name: "".to_string(),
-            color: Some("#FF5733".to_string()),
-        };
-
-        assert!(invalid_category.validate().is_err());
-
-        // Test invalid color format
-        let invalid_color = CreateCategoryInput {
-            name: "Valid Name".to_string(),
-            color: Some("invalid-color".to_string()),
-        };
-
-        assert!(invalid_color.validate().is_err());
+    async fn test_app_lock() {
+        use crate::app_lock::{self, AppLock};
+        use yuflow_core::AppError;
+        use std::time::{Duration, Instant};
+        
+        let test_app = TestApp::new();
+        let path = test_app.app_dir.join(app_lock::LOCK_FILE);
+        let lock = AppLock::default();
+        lock.load(path.clone()).expect("Failed to load lock settings");
+        
+        // Without a passphrase nothing is ever locked
+        assert!(!lock.status().enabled);
+        assert!(!lock.lock().locked);
+        assert!(lock.check("get_tasks").is_ok());
+        assert!(lock.set_passphrase(None, "short").is_err());
+        
+        lock.set_passphrase(None, "family secret").expect("Failed to enable lock");
+        assert!(lock.status().enabled);
+        assert!(lock.set_passphrase(Some("nope"), "other secret").is_err());
+        
+        assert!(lock.lock().locked);
+        assert!(matches!(lock.check("get_tasks"), Err(AppError::Locked(_))));
+        assert!(lock.check("unlock_app").is_ok());
+        assert!(lock.unlock("wrong secret").is_err());
+        assert!(!lock.unlock("family secret").expect("Failed to unlock").locked);
+        assert!(lock.check("get_tasks").is_ok());
+        
+        // Idle timeout, counted from the last command
+        lock.set_options(5, true).expect("Failed to set options");
+        let start = Instant::now();
+        assert!(lock.check_at("get_tasks", start).is_ok());
+        assert!(!lock.expire_idle(start + Duration::from_secs(4 * 60)));
+        assert!(lock.check_at("get_tasks", start + Duration::from_secs(6 * 60)).is_err());
+        lock.unlock("family secret").expect("Failed to unlock");
+        
+        assert!(lock.suspended());
+        assert!(app_lock::slept_between(Duration::from_secs(15), Duration::from_secs(3600)));
+        assert!(!app_lock::slept_between(Duration::from_secs(15), Duration::from_secs(16)));
+        
+        // An enabled lock is locked again after a restart
+        let restarted = AppLock::default();
+        restarted.load(path.clone()).expect("Failed to load lock settings");
+        assert!(restarted.status().locked);
+        assert_eq!(restarted.status().idle_minutes, 5);
+        
+        restarted.disable("family secret").expect("Failed to disable lock");
+        assert!(!restarted.status().locked);
+        assert!(restarted.check("get_tasks").is_ok());
     }
 
     #[tokio::test]
-    async fn test_search_functionality() {
-        let (_app, db) = setup_test_database().await;
+    async fn test_private_tasks() {
+        use yuflow_core::backup::BackupManager;
+        use crate::privacy::{self, Vault};
         
-        // Create test tasks
-        let task1 = CreateTaskInput {
-            title: "Learn Rust Programming".to_string(),
-            description: Some("Study Rust language fundamentals".to_string()),
-            priority: Some("high".to_string()),
-            category_id: None,
+        let (test_app, db) = setup_test_database().await;
+        let vault = Vault::default();
+        let task_input = |title: &str, category_id| CreateTaskInput {
+            title: title.to_string(),
+            description: Some("Bring the referral".to_string()),
+            priority: None,
+            category_id,
             due_date: None,
+            tags: Vec::new(),
+            status_id: None,
+            parent_id: None,
         };
         
-        let task2 = CreateTaskInput {
-            title: "Buy Groceries".to_string(),
-            description: Some("Milk, bread, and fruits".to_string()),
-            priority: Some("medium".to_string()),
-            category_id: None,
-            due_date: None,
-        };
-
-        db.create_task(task1).await.expect("Failed to create task1");
-        db.create_task(task2).await.expect("Failed to create task2");
-
-        // Search by title
-        let search_filter = TaskFilters {
-            completed: None,
-            priority: None,
-            category_id: None,
-            search: Some("Rust".to_string()),
-            due_date_before: None,
-            due_date_after: None,
-        };
-
-        let search_results = db.get_tasks(Some(search_filter)).await
-            .expect("Failed to search tasks");
+        assert!(!vault.status(&db).await.expect("Failed to get status").configured);
+        assert!(vault.setup(&db, "short").await.is_err());
+        vault.setup(&db, "private matters").await.expect("Failed to set up private tasks");
+        assert!(vault.setup(&db, "private matters").await.is_err());
         
-        assert_eq!(search_results.len(), 1);
-        assert_eq!(search_results[0].title, "Learn Rust Programming");
-
-        // Search by description
-        let desc_search_filter = TaskFilters {
-            completed: None,
-            priority: None,
+        let task = db.create_task(task_input("Doctor appointment", None)).await
+            .expect("Failed to create task");
+        db.add_checklist_item(task.id, "Insurance card").await.expect("Failed to add checklist item");
+        db.add_attachment(CreateAttachmentInput {
+            task_id: task.id,
+            kind: "file".to_string(),
+            target: "/home/me/referral.pdf".to_string(),
+            label: Some("Referral".to_string()),
+        }).await.expect("Failed to add attachment");
+        let clinic = db.create_custom_field(CreateCustomFieldInput {
+            name: "Clinic".to_string(),
+            field_type: "text".to_string(),
+            options: Vec::new(),
             category_id: None,
-            search: Some("Milk".to_string()),
-            due_date_before: None,
-            due_date_after: None,
-        };
-
-        let desc_results = db.get_tasks(Some(desc_search_filter)).await
-            .expect("Failed to search by description");
+        }).await.expect("Failed to create field");
+        db.set_task_field_value(task.id, clinic.id, Some("Northside")).await.expect("Failed to set value");
+        
+        // Everything a private task holds is encrypted, not only its title
+        let stored = db.set_task_private(task.id, true, &vault).await
+            .expect("Failed to mark task private")
+            .expect("Task not found");
+        assert!(privacy::is_sealed(&stored.title));
+        assert!(privacy::is_sealed(stored.description.as_deref().unwrap()));
+        assert!(privacy::is_sealed(&stored.custom_fields[&clinic.id]));
+        let mut checklist = db.get_checklist(Some(task.id)).await.expect("Failed to get checklist");
+        assert!(privacy::is_sealed(&checklist[0].title));
+        let mut attachments = db.get_attachments(Some(task.id)).await.expect("Failed to get attachments");
+        assert!(privacy::is_sealed(&attachments[0].target));
+        assert!(privacy::is_sealed(attachments[0].label.as_deref().unwrap()));
+        let revealed = vault.revealed(stored.clone());
+        assert_eq!(revealed.title, "Doctor appointment");
+        assert_eq!(revealed.description.as_deref(), Some("Bring the referral"));
+        assert_eq!(revealed.custom_fields[&clinic.id], "Northside");
+        vault.reveal_checklist(&mut checklist);
+        assert_eq!(checklist[0].title, "Insurance card");
+        vault.reveal_attachments(&mut attachments);
+        assert_eq!(attachments[0].target, "/home/me/referral.pdf");
+        assert_eq!(attachments[0].label.as_deref(), Some("Referral"));
+        
+        // Without the key only a placeholder is shown
+        vault.lock();
+        let locked = vault.revealed(stored.clone());
+        assert!(locked.locked);
+        assert_eq!(locked.title, privacy::LOCKED_TITLE);
+        assert_eq!(locked.description, None);
+        assert!(locked.custom_fields.is_empty());
+        let mut checklist = db.get_checklist(Some(task.id)).await.expect("Failed to get checklist");
+        vault.reveal_checklist(&mut checklist);
+        assert_eq!(checklist[0].title, privacy::LOCKED_TEXT);
+        assert!(vault.seal("New title").is_err());
+        assert!(vault.unlock(&db, "wrong passphrase").await.is_err());
+        vault.unlock(&db, "private matters").await.expect("Failed to unlock");
+        
+        // A description that does not decrypt locks the task rather than emptying it
+        let mut damaged = stored.clone();
+        damaged.description = Some(format!("{}00", yuflow_core::encryption::SEALED_PREFIX));
+        let damaged = vault.revealed(damaged);
+        assert!(damaged.locked);
+        assert_eq!(damaged.description, None);
+        
+        // Only encrypted titles may look encrypted
+        assert!(task_input("enc:v1:notes", None).validate().is_err());
+        
+        // Tasks follow their category in and out of privacy
+        let category = db.create_category(CreateCategoryInput { name: "Health".to_string(), color: None }).await
+            .expect("Failed to create category");
+        let checkup = db.create_task(task_input("Yearly checkup", Some(category.id))).await
+            .expect("Failed to create task");
+        db.add_checklist_item(checkup.id, "Fast the night before").await.expect("Failed to add checklist item");
+        
+        // The flag only changes together with the content of every task
+        vault.lock();
+        assert!(db.set_category_private(category.id, true, &vault).await.is_err());
+        assert!(!db.is_category_private(Some(category.id)).await.expect("Failed to get category"));
+        vault.unlock(&db, "private matters").await.expect("Failed to unlock");
+        
+        assert!(db.set_category_private(category.id, true, &vault).await.expect("Failed to mark category private"));
+        assert!(db.is_category_private(Some(category.id)).await.expect("Failed to get category"));
+        let stored_checkup = db.get_task(checkup.id).await.expect("Failed to get task").expect("Task not found");
+        assert!(privacy::is_sealed(&stored_checkup.title));
+        let checklist = db.get_checklist(Some(checkup.id)).await.expect("Failed to get checklist");
+        assert!(privacy::is_sealed(&checklist[0].title));
+        
+        // Backups hold the ciphertext and the wrapped key, never the plaintext
+        let manager = BackupManager::with_dir(test_app.app_dir.join("backups"))
+            .expect("Failed to create backup manager");
+        let backup = manager.create_backup(&db).await.expect("Failed to create backup");
+        let json = std::fs::read_to_string(test_app.app_dir.join("backups").join(&backup.filename))
+            .expect("Failed to read backup");
+        assert!(!json.contains("Doctor appointment"));
+        assert!(!json.contains("Yearly checkup"));
+        assert!(!json.contains("Insurance card"));
+        assert!(!json.contains("referral.pdf"));
+        assert!(!json.contains("Northside"));
+        
+        db.set_category_private(category.id, false, &vault).await.expect("Failed to update category");
+        let plain = db.get_task(checkup.id).await.expect("Failed to get task").expect("Task not found");
+        assert_eq!(plain.title, "Yearly checkup");
+        let checklist = db.get_checklist(Some(checkup.id)).await.expect("Failed to get checklist");
+        assert_eq!(checklist[0].title, "Fast the night before");
         
-        assert_eq!(desc_results.len(), 1);
-        assert_eq!(desc_results[0].title, "Buy Groceries");
+        // A new passphrase re-wraps the same key
+        vault.change_passphrase(&db, "private matters", "even more private").await
+            .expect("Failed to change passphrase");
+        vault.lock();
+        assert!(vault.unlock(&db, "private matters").await.is_err());
+        vault.unlock(&db, "even more private").await.expect("Failed to unlock");
+        let stored = db.get_task(task.id).await.expect("Failed to get task").expect("Task not found");
+        assert_eq!(vault.revealed(stored).title, "Doctor appointment");
     }
 }
@@ -0,0 +1,202 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use yuflow_core::database::Database;
+use yuflow_core::models::*;
+use yuflow_core::statistics::parse_timestamp;
+use yuflow_core::AppError;
+
+pub const URGENCY_SETTING: &str = "urgency_coefficients";
+
+/// Coefficients of the urgency score, in the spirit of Taskwarrior. Every
+/// term is a factor between 0 and 1 multiplied by its coefficient, and the
+/// score is their sum.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct UrgencyCoefficients {
+    /// Scaled by where the priority sits between the lowest and highest weight
+    pub priority: f64,
+    /// Full weight from a week overdue down to a fifth of it two weeks ahead
+    pub due: f64,
+    /// Grows linearly until `max_age_days`
+    pub age: f64,
+    pub max_age_days: f64,
+    /// Open subtask of an open task; finishing it unblocks the parent
+    pub blocking: f64,
+    /// Task that still has open subtasks
+    pub blocked: f64,
+    /// Task with at least one tag
+    pub tagged: f64,
+    /// Extra weight for specific tags, e.g. `{"next": 15.0}`
+    pub tags: BTreeMap<String, f64>,
+    /// Task the focus timer is running for
+    pub active: f64,
+}
+
+impl Default for UrgencyCoefficients {
+    fn default() -> Self {
+        UrgencyCoefficients {
+            priority: 6.0,
+            due: 12.0,
+            age: 2.0,
+            max_age_days: 365.0,
+            blocking: 8.0,
+            blocked: -5.0,
+            tagged: 1.0,
+            tags: BTreeMap::new(),
+            active: 4.0,
+        }
+    }
+}
+
+impl UrgencyCoefficients {
+    pub fn validate(&self) -> Result<(), AppError> {
+        let values = [self.priority, self.due, self.age, self.blocking, self.blocked, self.tagged, self.active];
+        if values.iter().chain(self.tags.values()).any(|v| !v.is_finite()) {
+            return Err(AppError::validation("Coefficients must be finite numbers"));
+        }
+        if !(self.max_age_days.is_finite() && self.max_age_days > 0.0) {
+            return Err(AppError::invalid_field("max_age_days", "Maximum age must be a positive number of days"));
+        }
+        Ok(())
+    }
+
+    pub async fn load(db: &Database) -> Result<Self, sqlx::Error> {
+        let coefficients = db.get_setting(URGENCY_SETTING).await?;
+        Ok(coefficients
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScoredTask {
+    #[serde(flatten)]
+    pub task: Task,
+    pub urgency: f64,
+}
+
+/// Everything the score needs besides the task itself.
+pub struct UrgencyContext {
+    coefficients: UrgencyCoefficients,
+    priority_weights: HashMap<String, i64>,
+    weight_range: (i64, i64),
+    blocking: HashSet<i64>,
+    blocked: HashSet<i64>,
+    active_task: Option<i64>,
+    now: DateTime<Utc>,
+}
+
+impl UrgencyContext {
+    /// `tasks` should hold every task so that subtask relations are complete.
+    pub fn new(
+        coefficients: UrgencyCoefficients,
+        priorities: &[Priority],
+        tasks: &[Task],
+        active_task: Option<i64>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let open: HashSet<i64> = tasks.iter().filter(|t| !t.completed).map(|t| t.id).collect();
+
+        let mut blocking = HashSet::new();
+        let mut blocked = HashSet::new();
+        for task in tasks.iter().filter(|t| !t.completed) {
+            if let Some(parent_id) = task.parent_id.filter(|p| open.contains(p)) {
+                blocking.insert(task.id);
+                blocked.insert(parent_id);
+            }
+        }
+
+        let weights = priorities.iter().map(|p| p.weight);
+        UrgencyContext {
+            coefficients,
+            priority_weights: priorities.iter().map(|p| (p.name.to_lowercase(), p.weight)).collect(),
+            weight_range: (weights.clone().min().unwrap_or(0), weights.max().unwrap_or(0)),
+            blocking,
+            blocked,
+            active_task,
+            now,
+        }
+    }
+
+    /// Urgency of an open task; completed tasks score zero.
+    pub fn score(&self, task: &Task) -> f64 {
+        if task.completed {
+            return 0.0;
+        }
+        let c = &self.coefficients;
+
+        let priority = match self.priority_weights.get(&task.priority.to_lowercase()) {
+            Some(weight) if self.weight_range.1 > self.weight_range.0 => {
+                (weight - self.weight_range.0) as f64 / (self.weight_range.1 - self.weight_range.0) as f64
+            }
+            _ => 0.0,
+        };
+
+        let due = task
+            .due_date
+            .as_deref()
+            .and_then(parse_timestamp)
+            .map(|due| due_factor((due - self.now).num_minutes() as f64 / (24.0 * 60.0)))
+            .unwrap_or(0.0);
+
+        let age = parse_timestamp(&task.created_at)
+            .map(|created| {
+                let days = (self.now - created).num_minutes() as f64 / (24.0 * 60.0);
+                (days / c.max_age_days).clamp(0.0, 1.0)
+            })
+            .unwrap_or(0.0);
+
+        let tagged = match task.tags.len() {
+            0 => 0.0,
+            1 => 0.8,
+            2 => 0.9,
+            _ => 1.0,
+        };
+        let tag_bonus: f64 = task.tags.iter().filter_map(|tag| c.tags.get(tag)).sum();
+
+        let flag = |set: &HashSet<i64>| if set.contains(&task.id) { 1.0 } else { 0.0 };
+        let active = if self.active_task == Some(task.id) { 1.0 } else { 0.0 };
+
+        c.priority * priority
+            + c.due * due
+            + c.age * age
+            + c.blocking * flag(&self.blocking)
+            + c.blocked * flag(&self.blocked)
+            + c.tagged * tagged
+            + tag_bonus
+            + c.active * active
+    }
+
+    pub fn sort(&self, tasks: &mut [Task], descending: bool) {
+        tasks.sort_by(|a, b| {
+            let order = self.score(a).total_cmp(&self.score(b));
+            if descending { order.reverse() } else { order }
+        });
+    }
+
+    /// The `limit` most urgent open tasks.
+    pub fn next_actions(&self, tasks: Vec<Task>, limit: usize) -> Vec<ScoredTask> {
+        let mut scored: Vec<ScoredTask> = tasks
+            .into_iter()
+            .filter(|t| !t.completed && t.archived_at.is_none())
+            .map(|task| ScoredTask { urgency: self.score(&task), task })
+            .collect();
+
+        scored.sort_by(|a, b| b.urgency.total_cmp(&a.urgency));
+        scored.truncate(limit);
+        scored
+    }
+}
+
+/// 1.0 from seven days overdue, falling linearly to 0.2 at fourteen days
+/// ahead (Taskwarrior's curve).
+fn due_factor(days_until_due: f64) -> f64 {
+    if days_until_due <= -7.0 {
+        1.0
+    } else if days_until_due >= 14.0 {
+        0.2
+    } else {
+        (14.0 - days_until_due) * 0.8 / 21.0 + 0.2
+    }
+}
@@ -0,0 +1,293 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use thiserror::Error;
+use yuflow_core::AppError;
+use crate::location::DataLocation;
+
+/// The workspace that existed before workspaces did. Its files stay directly
+/// in the app data directory so that existing installs keep their data.
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+const REGISTRY_FILE: &str = "workspaces.json";
+const WORKSPACES_DIR: &str = "workspaces";
+const DATABASE_FILE: &str = "yuflow.db";
+const BACKUPS_DIR: &str = "backups";
+const MAX_NAME_LENGTH: usize = 100;
+
+// Serializes read-modify-write cycles on the registry file
+static REGISTRY_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Error, Debug)]
+pub enum WorkspaceError {
+    #[error("Workspace '{0}' not found")]
+    NotFound(String),
+    #[error("{0}")]
+    Invalid(String),
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("Corrupt workspace registry: {0}")]
+    Registry(#[from] serde_json::Error),
+}
+
+impl From<WorkspaceError> for AppError {
+    fn from(error: WorkspaceError) -> Self {
+        match error {
+            WorkspaceError::NotFound(_) => AppError::NotFound(error.to_string()),
+            WorkspaceError::Invalid(message) => AppError::validation(message),
+            WorkspaceError::Io(e) => e.into(),
+            WorkspaceError::Registry(_) => AppError::Storage(error.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Workspace {
+    /// Directory name; stays the same when the workspace is renamed
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkspaceInfo {
+    #[serde(flatten)]
+    pub workspace: Workspace,
+    pub active: bool,
+    pub database_path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Registry {
+    active: String,
+    workspaces: Vec<Workspace>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry {
+            active: DEFAULT_WORKSPACE.to_string(),
+            workspaces: vec![Workspace {
+                id: DEFAULT_WORKSPACE.to_string(),
+                name: "Default".to_string(),
+                created_at: Utc::now().to_rfc3339(),
+            }],
+        }
+    }
+}
+
+impl Registry {
+    fn find(&self, id: &str) -> Result<&Workspace, WorkspaceError> {
+        self.workspaces
+            .iter()
+            .find(|w| w.id == id)
+            .ok_or_else(|| WorkspaceError::NotFound(id.to_string()))
+    }
+
+    /// Trims the name and checks that no other workspace uses it.
+    fn check_name(&self, name: &str, except: Option<&str>) -> Result<String, WorkspaceError> {
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+            return Err(WorkspaceError::Invalid(format!(
+                "Workspace name must be between 1 and {} characters",
+                MAX_NAME_LENGTH
+            )));
+        }
+        if self.workspaces.iter().any(|w| Some(w.id.as_str()) != except && w.name.eq_ignore_ascii_case(name)) {
+            return Err(WorkspaceError::Invalid(format!("A workspace named '{}' already exists", name)));
+        }
+        Ok(name.to_string())
+    }
+}
+
+/// Named workspaces, each with its own database file, backups folder and
+/// settings. The registry lives next to them in the app data directory, or
+/// next to the executable in portable mode.
+pub struct Workspaces {
+    root: PathBuf,
+}
+
+impl Workspaces {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Workspaces { root: root.into() }
+    }
+
+    pub fn from_app(app_handle: &AppHandle) -> Result<Self, WorkspaceError> {
+        let root = DataLocation::from_app(app_handle)
+            .root(app_handle)
+            .map_err(|e| WorkspaceError::Invalid(format!("Failed to get data directory: {}", e)))?;
+        Ok(Workspaces::new(root))
+    }
+
+    pub fn directory(&self, id: &str) -> PathBuf {
+        if id == DEFAULT_WORKSPACE {
+            self.root.clone()
+        } else {
+            self.root.join(WORKSPACES_DIR).join(id)
+        }
+    }
+
+    pub fn database_path(&self, id: &str) -> PathBuf {
+        self.directory(id).join(DATABASE_FILE)
+    }
+
+    pub fn backup_dir(&self, id: &str) -> PathBuf {
+        self.directory(id).join(BACKUPS_DIR)
+    }
+
+    pub fn active(&self) -> Result<Workspace, WorkspaceError> {
+        let registry = self.load()?;
+        registry.find(&registry.active).cloned()
+    }
+
+    pub fn get(&self, id: &str) -> Result<Workspace, WorkspaceError> {
+        self.load()?.find(id).cloned()
+    }
+
+    pub fn list(&self) -> Result<Vec<WorkspaceInfo>, WorkspaceError> {
+        let registry = self.load()?;
+        Ok(registry
+            .workspaces
+            .iter()
+            .map(|w| self.info(w, &registry.active))
+            .collect())
+    }
+
+    pub fn info(&self, workspace: &Workspace, active: &str) -> WorkspaceInfo {
+        WorkspaceInfo {
+            workspace: workspace.clone(),
+            active: workspace.id == active,
+            database_path: self.database_path(&workspace.id).display().to_string(),
+        }
+    }
+
+    /// Registers a workspace and creates its directory. The database itself
+    /// is created and migrated the first time the workspace is opened.
+    pub fn create(&self, name: &str) -> Result<Workspace, WorkspaceError> {
+        let _lock = REGISTRY_LOCK.lock().unwrap();
+        let mut registry = self.load()?;
+        let name = registry.check_name(name, None)?;
+
+        let base = slugify(&name);
+        let mut id = base.clone();
+        let mut suffix = 2;
+        while id == DEFAULT_WORKSPACE
+            || registry.workspaces.iter().any(|w| w.id == id)
+            || self.directory(&id).exists()
+        {
+            id = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+
+        std::fs::create_dir_all(self.backup_dir(&id))?;
+
+        let workspace = Workspace {
+            id,
+            name,
+            created_at: Utc::now().to_rfc3339(),
+        };
+        registry.workspaces.push(workspace.clone());
+        self.save(&registry)?;
+
+        Ok(workspace)
+    }
+
+    pub fn rename(&self, id: &str, name: &str) -> Result<Workspace, WorkspaceError> {
+        let _lock = REGISTRY_LOCK.lock().unwrap();
+        let mut registry = self.load()?;
+        registry.find(id)?;
+        let name = registry.check_name(name, Some(id))?;
+
+        let workspace = registry.workspaces.iter_mut().find(|w| w.id == id).unwrap();
+        workspace.name = name;
+        let workspace = workspace.clone();
+        self.save(&registry)?;
+
+        Ok(workspace)
+    }
+
+    /// Records which workspace is opened at startup.
+    pub fn set_active(&self, id: &str) -> Result<(), WorkspaceError> {
+        let _lock = REGISTRY_LOCK.lock().unwrap();
+        let mut registry = self.load()?;
+        registry.find(id)?;
+        registry.active = id.to_string();
+        self.save(&registry)
+    }
+
+    /// Removes a workspace together with its database and backups. The
+    /// default and the active workspace cannot be deleted.
+    pub fn delete(&self, id: &str) -> Result<(), WorkspaceError> {
+        let _lock = REGISTRY_LOCK.lock().unwrap();
+        let mut registry = self.load()?;
+        registry.find(id)?;
+
+        if id == DEFAULT_WORKSPACE {
+            return Err(WorkspaceError::Invalid("The default workspace cannot be deleted".to_string()));
+        }
+        if id == registry.active {
+            return Err(WorkspaceError::Invalid(
+                "Switch to another workspace before deleting this one".to_string(),
+            ));
+        }
+
+        registry.workspaces.retain(|w| w.id != id);
+        self.save(&registry)?;
+
+        let directory = self.directory(id);
+        if directory.exists() {
+            std::fs::remove_dir_all(directory)?;
+        }
+
+        Ok(())
+    }
+
+    fn load(&self) -> Result<Registry, WorkspaceError> {
+        let path = self.root.join(REGISTRY_FILE);
+        if !path.exists() {
+            return Ok(Registry::default());
+        }
+
+        let mut registry: Registry = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        if !registry.workspaces.iter().any(|w| w.id == DEFAULT_WORKSPACE) {
+            registry.workspaces.insert(0, Registry::default().workspaces.remove(0));
+        }
+        if registry.find(&registry.active).is_err() {
+            registry.active = DEFAULT_WORKSPACE.to_string();
+        }
+        Ok(registry)
+    }
+
+    // Written to a temporary file first so a crash never leaves half a registry
+    fn save(&self, registry: &Registry) -> Result<(), WorkspaceError> {
+        std::fs::create_dir_all(&self.root)?;
+        let path = self.root.join(REGISTRY_FILE);
+        let temp_path = path.with_extension("json.tmp");
+        std::fs::write(&temp_path, serde_json::to_string_pretty(registry)?)?;
+        std::fs::rename(temp_path, path)?;
+        Ok(())
+    }
+}
+
+/// Directory-safe form of a workspace name.
+fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    let slug: String = slug.chars().take(40).collect();
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "workspace".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
@@ -6,6 +6,7 @@ import type {
   Tag, 
   CreateTaskInput, 
   UpdateTaskInput, 
+  TaskUpdate, 
   CreateCategoryInput, 
   TaskFilters 
 } from '../../types';
@@ -36,7 +37,8 @@ export class DesktopDatabaseAdapter implements DatabaseAdapter {
   }
 
   async updateTask(task: UpdateTaskInput): Promise<Task> {
-    return await invoke('update_task', { task });
+    const update = await invoke<TaskUpdate>('update_task', { task });
+    return update.task;
   }
 
   async deleteTask(id: number): Promise<void> {
@@ -48,14 +48,28 @@ export interface CreateTaskInput {
   due_date?: string;
 }
 
+// Fields left out keep their value; null clears a field
 export interface UpdateTaskInput {
   id: number;
   title?: string;
-  description?: string;
+  description?: string | null;
   completed?: boolean;
-  priority?: Priority;
-  category_id?: number;
-  due_date?: string;
+  priority?: Priority | null;
+  category_id?: number | null;
+  due_date?: string | null;
+  status_id?: number;
+  parent_id?: number | null;
+}
+
+export interface FieldChange {
+  field: string;
+  before: unknown;
+  after: unknown;
+}
+
+export interface TaskUpdate {
+  task: Task;
+  changes: FieldChange[];
 }
 
 export interface CreateCategoryInput {